@@ -0,0 +1,56 @@
+//! Micro-benchmarks for the cheapest operations on checkouts: checking an item
+//! out and releasing it, taking and dropping references to a shared checkout,
+//! and downgrading.
+//!
+//! Each of these takes nanoseconds, so the suite is quick to run, and is
+//! meant as a smoke test for regressions in the slab's hot paths. Run it
+//! with `cargo bench --features bench --bench guards`; pass a filter such as
+//! `-- fixed/` to run a subset. `tests/perf.rs` checks the same operations
+//! against a fixed budget.
+use criterion::{criterion_group, criterion_main, Criterion};
+use natatorium::{fixed, growable};
+use std::{hint::black_box, time::Duration};
+
+fn fixed(c: &mut Criterion) {
+    let pool: fixed::Pool<String> = fixed::Pool::with_capacity(16);
+    c.bench_function("fixed/checkout_drop", |b| {
+        b.iter(|| drop(black_box(pool.checkout())))
+    });
+    c.bench_function("fixed/downgrade", |b| {
+        b.iter(|| drop(black_box(pool.checkout().downgrade())))
+    });
+
+    let shared = pool.checkout().downgrade();
+    c.bench_function("fixed/shared_clone_drop", |b| {
+        b.iter(|| drop(black_box(shared.clone())))
+    });
+    let key = shared.key();
+    c.bench_function("fixed/get_by_key", |b| {
+        b.iter(|| drop(black_box(pool.get(black_box(key)))))
+    });
+}
+
+fn growable(c: &mut Criterion) {
+    let pool: growable::Pool<String> = growable::Pool::with_capacity(16);
+    c.bench_function("growable/checkout_drop", |b| {
+        b.iter(|| drop(black_box(pool.checkout())))
+    });
+    c.bench_function("growable/downgrade", |b| {
+        b.iter(|| drop(black_box(pool.checkout().downgrade())))
+    });
+
+    let shared = pool.checkout().downgrade();
+    c.bench_function("growable/shared_clone_drop", |b| {
+        b.iter(|| drop(black_box(shared.clone())))
+    });
+}
+
+criterion_group! {
+    name = guards;
+    // Keep runs short, so that the whole suite can be run after every change.
+    config = Criterion::default()
+        .warm_up_time(Duration::from_millis(250))
+        .measurement_time(Duration::from_secs(1));
+    targets = fixed, growable
+}
+criterion_main!(guards);
@@ -0,0 +1,155 @@
+//! Pools compression and decompression contexts, which are expensive to set
+//! up, across several worker threads.
+//!
+//! `flate2`'s contexts don't implement `Clear`, so they are wrapped in a
+//! `ClearWith` that resets them. With dirty tracking, a context is only reset
+//! if it was used since it was last checked out. A context that reports an
+//! error is replaced with a fresh one, rather than trusted to reset cleanly.
+//!
+//! Run with `cargo run --example compression`.
+use flate2::{Compress, Compression, Decompress, FlushCompress, FlushDecompress, Status};
+use natatorium::{fixed::Pool, Builder, ClearWith};
+use std::thread;
+
+type Compressor = ClearWith<Compress, fn(&mut Compress)>;
+type Decompressor = ClearWith<Decompress, fn(&mut Decompress)>;
+
+const WORKERS: usize = 4;
+const MESSAGES: usize = 64;
+
+fn new_compressor() -> Compress {
+    Compress::new(Compression::best(), false)
+}
+
+fn new_decompressor() -> Decompress {
+    Decompress::new(false)
+}
+
+fn reset_compressor(ctx: &mut Compress) {
+    ctx.reset();
+}
+
+fn reset_decompressor(ctx: &mut Decompress) {
+    ctx.reset(false);
+}
+
+/// Compresses `input` into `out` as a single stream.
+fn compress(ctx: &mut Compress, input: &[u8], out: &mut Vec<u8>) {
+    // A context's totals count from when it was last reset, so a pooled
+    // context that was handed out without being reset would be caught here.
+    assert_eq!(ctx.total_in(), 0, "compressor was not reset");
+    loop {
+        out.reserve(input.len() / 2 + 64);
+        let consumed = ctx.total_in() as usize;
+        let status = ctx
+            .compress_vec(&input[consumed..], out, FlushCompress::Finish)
+            .expect("compressing to a vec can't fail");
+        if status == Status::StreamEnd {
+            return;
+        }
+    }
+}
+
+/// Decompresses a single stream from `input` into `out`.
+fn decompress(ctx: &mut Decompress, input: &[u8], out: &mut Vec<u8>) -> Result<(), String> {
+    assert_eq!(ctx.total_in(), 0, "decompressor was not reset");
+    loop {
+        out.reserve(input.len() * 2 + 64);
+        let consumed = ctx.total_in() as usize;
+        let before = ctx.total_out();
+        let status = ctx
+            .decompress_vec(&input[consumed..], out, FlushDecompress::None)
+            .map_err(|error| error.to_string())?;
+        match status {
+            Status::StreamEnd => return Ok(()),
+            // Out of input, and no more output: the stream was truncated.
+            _ if ctx.total_in() as usize == input.len() && ctx.total_out() == before => {
+                return Err("truncated stream".to_string())
+            }
+            _ => {}
+        }
+    }
+}
+
+fn message(worker: usize, n: usize) -> Vec<u8> {
+    format!("worker {} says hello for the {}th time! ", worker, n)
+        .repeat(n % 8 + 1)
+        .into_bytes()
+}
+
+fn main() {
+    // One context of each kind per worker, so no worker ever waits.
+    let compressors: Pool<Compressor> = Builder::new()
+        .fixed()
+        .with_elements(WORKERS)
+        .with_fn(new_compressor)
+        .with_dirty_tracking()
+        .with_clear_fn(reset_compressor as fn(&mut Compress))
+        .finish();
+    let decompressors: Pool<Decompressor> = Builder::new()
+        .fixed()
+        .with_elements(WORKERS)
+        .with_fn(new_decompressor)
+        .with_dirty_tracking()
+        .with_clear_fn(reset_decompressor as fn(&mut Decompress))
+        .finish();
+    let buffers: Pool<Vec<u8>> = Pool::with_capacity(WORKERS * 2);
+
+    let replaced = thread::scope(|scope| {
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|worker| {
+                let (compressors, decompressors, buffers) =
+                    (&compressors, &decompressors, &buffers);
+                scope.spawn(move || {
+                    let mut replaced = 0;
+                    for n in 0..MESSAGES {
+                        let input = message(worker, n);
+                        let mut compressed = buffers.checkout();
+                        compress(&mut compressors.checkout(), &input, &mut compressed);
+
+                        // Every so often, corrupt the stream to exercise the
+                        // error path.
+                        if n % 16 == 15 {
+                            let len = compressed.len();
+                            compressed.truncate(len / 2);
+                        }
+
+                        let mut ctx = decompressors.checkout();
+                        let mut output = buffers.checkout();
+                        match decompress(&mut ctx, &compressed, &mut output) {
+                            Ok(()) => assert_eq!(*output, input),
+                            Err(_) => {
+                                // Don't return a context in an unknown state
+                                // to the pool.
+                                ctx.detach_with(|| {
+                                    ClearWith::new(
+                                        new_decompressor(),
+                                        reset_decompressor as fn(&mut Decompress),
+                                    )
+                                });
+                                replaced += 1;
+                            }
+                        }
+                    }
+                    replaced
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .sum::<usize>()
+    });
+
+    println!(
+        "{} messages sent through {} compressors and {} decompressors",
+        WORKERS * MESSAGES,
+        compressors.capacity(),
+        decompressors.capacity(),
+    );
+    println!(
+        "{} corrupted streams; {} decompressors replaced",
+        replaced,
+        decompressors.stats().detached,
+    );
+}
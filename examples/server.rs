@@ -0,0 +1,177 @@
+//! Simulates an HTTP-like server that builds each response in a pooled
+//! buffer, and compares how the different ways of sizing the pool behave when
+//! there are more concurrent requests than buffers.
+//!
+//! Three modes are compared:
+//!
+//! - **fixed**: a fixed pool with fewer buffers than workers. When every
+//!   buffer is in use, workers block in `checkout` until one is released, so
+//!   the pool bounds memory use at the cost of latency.
+//! - **growable**: a growable pool that starts out with the same number of
+//!   buffers, and allocates more whenever it runs out. Workers never wait, but
+//!   the pool keeps every buffer it ever allocated.
+//! - **overflow**: a fixed pool of the same size, where a worker that finds it
+//!   exhausted allocates a throwaway buffer instead of waiting. The pool has
+//!   no built-in overflow mode; this is built out of `try_checkout` and an
+//!   ordinary `Vec`, and the overflow count is tracked by the example itself.
+//!
+//! Run with `cargo run --release --example server -- [WORKERS] [REQUESTS]
+//! [BUFFERS] [RESPONSE_BYTES]`, where `REQUESTS` is the number of requests
+//! handled by each worker.
+use natatorium::{fixed, growable};
+use std::{
+    env,
+    ops::DerefMut,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[derive(Debug, Clone, Copy)]
+struct Config {
+    workers: usize,
+    requests: usize,
+    buffers: usize,
+    response_bytes: usize,
+}
+
+impl Config {
+    fn from_args() -> Self {
+        let mut args = env::args().skip(1).map(|arg| {
+            arg.parse::<usize>()
+                .unwrap_or_else(|e| panic!("invalid argument {:?}: {}", arg, e))
+        });
+        Self {
+            workers: args.next().unwrap_or(16),
+            requests: args.next().unwrap_or(2_000),
+            buffers: args.next().unwrap_or(4),
+            response_bytes: args.next().unwrap_or(16 * 1024),
+        }
+    }
+}
+
+/// What a mode's checkout hands the request handler.
+enum Buffer<B> {
+    Pooled(B),
+    Overflow(Vec<u8>),
+}
+
+impl<B: DerefMut<Target = Vec<u8>>> Buffer<B> {
+    fn as_mut(&mut self) -> &mut Vec<u8> {
+        match self {
+            Buffer::Pooled(buf) => buf,
+            Buffer::Overflow(buf) => buf,
+        }
+    }
+}
+
+/// Writes a response into `buf`, and then "sends" it by checksumming it and
+/// yielding, so that the buffer is held across a point where other workers
+/// can run.
+fn handle_request(request: usize, buf: &mut Vec<u8>, response_bytes: usize) -> u64 {
+    buf.extend_from_slice(b"HTTP/1.1 200 OK\r\n\r\n");
+    buf.resize(response_bytes, (request % 251) as u8);
+    thread::yield_now();
+    buf.iter().map(|&b| b as u64).sum()
+}
+
+struct Report {
+    elapsed: Duration,
+    latencies: Vec<Duration>,
+}
+
+fn run<B, F>(config: Config, checkout: F) -> Report
+where
+    B: DerefMut<Target = Vec<u8>>,
+    F: Fn() -> Buffer<B> + Sync,
+{
+    let start = Instant::now();
+    let latencies = thread::scope(|scope| {
+        let workers: Vec<_> = (0..config.workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut latencies = Vec::with_capacity(config.requests);
+                    let mut checksum = 0u64;
+                    for request in 0..config.requests {
+                        let t0 = Instant::now();
+                        let mut buf = checkout();
+                        checksum = checksum.wrapping_add(handle_request(
+                            request,
+                            buf.as_mut(),
+                            config.response_bytes,
+                        ));
+                        drop(buf);
+                        latencies.push(t0.elapsed());
+                    }
+                    assert_ne!(checksum, 0);
+                    latencies
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| worker.join().expect("worker panicked"))
+            .collect::<Vec<_>>()
+    });
+    Report {
+        elapsed: start.elapsed(),
+        latencies,
+    }
+}
+
+fn print_report(mode: &str, config: Config, mut report: Report, pool: String) {
+    report.latencies.sort_unstable();
+    let percentile = |p: usize| report.latencies[(report.latencies.len() - 1) * p / 100];
+    let total = config.workers * config.requests;
+    println!(
+        "{:<9} {:>10.0} req/s  p50 {:>10?}  p99 {:>10?}  max {:>10?}  {}",
+        mode,
+        total as f64 / report.elapsed.as_secs_f64(),
+        percentile(50),
+        percentile(99),
+        report.latencies.last().unwrap(),
+        pool,
+    );
+}
+
+fn main() {
+    let config = Config::from_args();
+    println!(
+        "{} workers x {} requests, {} pooled buffers, {}-byte responses\n",
+        config.workers, config.requests, config.buffers, config.response_bytes
+    );
+
+    let pool: fixed::Pool<Vec<u8>> = fixed::Pool::with_capacity(config.buffers);
+    let report = run(config, || Buffer::Pooled(pool.checkout()));
+    let stats = format!(
+        "buffers: {}, contended checkouts: {}",
+        pool.capacity(),
+        pool.retry_histogram().contended()
+    );
+    print_report("fixed", config, report, stats);
+
+    let pool: growable::Pool<Vec<u8>> = growable::Pool::with_capacity(config.buffers);
+    let report = run(config, || Buffer::Pooled(pool.checkout()));
+    let stats = format!(
+        "buffers: {} (started with {})",
+        pool.capacity(),
+        config.buffers
+    );
+    print_report("growable", config, report, stats);
+
+    let pool: fixed::Pool<Vec<u8>> = fixed::Pool::with_capacity(config.buffers);
+    let overflows = AtomicUsize::new(0);
+    let report = run(config, || match pool.try_checkout() {
+        Some(buf) => Buffer::Pooled(buf),
+        None => {
+            overflows.fetch_add(1, Ordering::Relaxed);
+            Buffer::Overflow(Vec::with_capacity(config.response_bytes))
+        }
+    });
+    let stats = format!(
+        "buffers: {}, overflow allocations: {}",
+        pool.capacity(),
+        overflows.load(Ordering::Relaxed)
+    );
+    print_report("overflow", config, report, stats);
+}
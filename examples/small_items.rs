@@ -0,0 +1,46 @@
+//! Measures checkout throughput for a pool of tiny, pointer-sized items.
+//!
+//! For items this small, the pool's per-slot bookkeeping is larger than the
+//! items themselves, so this is mostly a measure of that overhead.
+//!
+//! Run with `cargo run --release --example small_items`.
+use natatorium::{fixed::Pool, Clear};
+use std::time::Instant;
+
+/// A pointer-sized handle, such as an index into some other table.
+#[derive(Default)]
+struct Handle(u64);
+
+impl Clear for Handle {
+    fn clear(&mut self) {
+        self.0 = 0;
+    }
+}
+
+const ITEMS: usize = 1024;
+const ROUNDS: usize = 10_000;
+
+fn main() {
+    let pool: Pool<Handle> = Pool::with_capacity(ITEMS);
+    let mut held = Vec::with_capacity(ITEMS);
+
+    let start = Instant::now();
+    for round in 0..ROUNDS {
+        for _ in 0..ITEMS {
+            let mut handle = pool.checkout();
+            handle.0 = round as u64;
+            held.push(handle);
+        }
+        held.clear();
+    }
+    let elapsed = start.elapsed();
+
+    let ops = ITEMS * ROUNDS;
+    println!(
+        "{} checkouts of {}-byte items in {:?} ({:.1} ns/checkout)",
+        ops,
+        std::mem::size_of::<Handle>(),
+        elapsed,
+        elapsed.as_nanos() as f64 / ops as f64,
+    );
+}
@@ -0,0 +1,208 @@
+//! A minimal `tracing` subscriber that stores each span's data in a pooled
+//! item, in the style of `tracing-subscriber`'s `Registry`.
+//!
+//! Each span's ID is the [`Key`] of a shared checkout of its data, so looking
+//! a span up is a single `Pool::get`, and the buffer its fields are formatted
+//! into is reused once the span closes. The subscriber holds one reference to
+//! each span's checkout for every handle `tracing` holds to the span, as raw
+//! pointers from `Shared::into_raw`, so a span's data is released when its
+//! last handle is closed.
+//!
+//! Run with `cargo run --example tracing_buffers --features tracing`.
+use natatorium::{
+    fixed::{Pool, Shared},
+    Clear, Key,
+};
+use std::{
+    cell::RefCell,
+    fmt::{self, Write},
+    sync::Mutex,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// The data stored for each span.
+#[derive(Default)]
+struct SpanData {
+    name: &'static str,
+    parent: Option<span::Id>,
+    /// The span's formatted fields.
+    ///
+    /// Spans are only ever shared once they have been created, so fields that
+    /// are recorded later are written through a lock.
+    fields: Mutex<String>,
+}
+
+struct Registry {
+    spans: Pool<SpanData>,
+}
+
+/// Formats fields into a span's or event's buffer.
+struct FieldWriter<'a>(&'a mut String);
+
+thread_local! {
+    /// The spans that the current thread has entered, innermost last.
+    static STACK: RefCell<Vec<span::Id>> = const { RefCell::new(Vec::new()) };
+}
+
+impl Clear for SpanData {
+    fn clear(&mut self) {
+        self.name = "";
+        self.parent = None;
+        self.fields
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+impl Registry {
+    fn new(spans: Pool<SpanData>) -> Self {
+        Self { spans }
+    }
+
+    fn span(&self, id: &span::Id) -> Option<Shared<SpanData>> {
+        // Span IDs may not be zero, so keys are offset by one.
+        self.spans.get(Key::from_bits(id.into_u64() - 1))
+    }
+
+    fn current(&self) -> Option<span::Id> {
+        STACK.with(|stack| stack.borrow().last().cloned())
+    }
+
+    /// Formats the names and fields of `id` and its parents, outermost first.
+    fn format_scope(&self, id: Option<span::Id>, out: &mut String) {
+        let mut scope = Vec::new();
+        let mut next = id;
+        while let Some(span) = next.and_then(|id| self.span(&id)) {
+            next = span.parent.clone();
+            scope.push(span);
+        }
+        for span in scope.iter().rev() {
+            let fields = span.fields.lock().unwrap();
+            let _ = write!(out, "{}{{{}}}:", span.name, fields.trim_start());
+        }
+    }
+}
+
+impl Subscriber for Registry {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &span::Attributes<'_>) -> span::Id {
+        let mut span = self.spans.checkout();
+        span.name = attrs.metadata().name();
+        span.parent = if attrs.is_contextual() {
+            self.current()
+        } else {
+            attrs.parent().cloned()
+        };
+        attrs.record(&mut FieldWriter(span.fields.get_mut().unwrap()));
+
+        let span = span.downgrade();
+        let id = span::Id::from_u64(span.key().to_bits() + 1);
+        // `tracing` now holds a handle to the span, which keeps it checked out
+        // until the handle is closed.
+        let _ = Shared::into_raw(span);
+        if let Some(parent) = self.span(&id).and_then(|span| span.parent.clone()) {
+            // Children keep their parents open.
+            self.clone_span(&parent);
+        }
+        id
+    }
+
+    fn record(&self, id: &span::Id, values: &span::Record<'_>) {
+        if let Some(span) = self.span(id) {
+            values.record(&mut FieldWriter(&mut span.fields.lock().unwrap()));
+        }
+    }
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut line = String::new();
+        let parent = if event.is_contextual() {
+            self.current()
+        } else {
+            event.parent().cloned()
+        };
+        self.format_scope(parent, &mut line);
+        event.record(&mut FieldWriter(&mut line));
+        println!("{}", line);
+    }
+
+    fn enter(&self, id: &span::Id) {
+        STACK.with(|stack| stack.borrow_mut().push(id.clone()));
+    }
+
+    fn exit(&self, id: &span::Id) {
+        STACK.with(|stack| {
+            let mut stack = stack.borrow_mut();
+            if let Some(pos) = stack.iter().rposition(|entered| entered == id) {
+                stack.remove(pos);
+            }
+        });
+    }
+
+    fn clone_span(&self, id: &span::Id) -> span::Id {
+        let span = self
+            .span(id)
+            .expect("cloned a span that was already closed");
+        let _ = Shared::into_raw(span);
+        id.clone()
+    }
+
+    fn try_close(&self, id: span::Id) -> bool {
+        let span = match self.span(&id) {
+            Some(span) => span,
+            None => return false,
+        };
+        let parent = span.parent.clone();
+        // Release the reference that was leaked for the closed handle, along
+        // with the one `span` just took.
+        let handle = unsafe { Shared::from_raw(&self.spans, &*span as *const SpanData) };
+        drop((handle, span));
+
+        let closed = self.span(&id).is_none();
+        if closed {
+            if let Some(parent) = parent {
+                self.try_close(parent);
+            }
+        }
+        closed
+    }
+}
+
+impl Visit for FieldWriter<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = if field.name() == "message" {
+            write!(self.0, " {:?}", value)
+        } else {
+            write!(self.0, " {}={:?}", field.name(), value)
+        };
+    }
+}
+
+fn main() {
+    let spans: Pool<SpanData> = Pool::with_capacity(64);
+    let registry = Registry::new(spans.clone());
+
+    tracing::subscriber::with_default(registry, || {
+        let request = tracing::info_span!("request", method = "GET", path = "/pools");
+        let _request = request.enter();
+        for attempt in 0..2 {
+            let span = tracing::info_span!("attempt", attempt, status = tracing::field::Empty);
+            let _attempt = span.enter();
+            tracing::info!(bytes = 512, "sent response");
+            span.record("status", 200);
+            tracing::info!("finished attempt");
+        }
+        println!("{} spans open", spans.used());
+    });
+
+    // Every span's data has been released back to the pool.
+    println!("{} spans open", spans.used());
+}
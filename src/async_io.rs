@@ -0,0 +1,214 @@
+//! Integration with `tokio`'s I/O traits and the `bytes` buffer traits.
+//!
+//! Owned checkouts of types implementing these traits implement them as well,
+//! so pooled buffers can be passed directly to APIs such as
+//! `AsyncReadExt::read_buf`, without borrowing the pooled item first.
+use bytes::{buf::UninitSlice, Buf, BufMut};
+use std::{
+    io,
+    ops::{Deref, DerefMut},
+    pin::Pin,
+    task::{Context, Poll},
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+#[cfg(feature = "fixed")]
+use crate::fixed;
+#[cfg(feature = "growable")]
+use crate::growable;
+
+// === impl fixed::Owned ===
+
+#[cfg(feature = "fixed")]
+unsafe impl<T> BufMut for fixed::Owned<T>
+where
+    T: BufMut,
+{
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+        self.deref().remaining_mut()
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.deref_mut().advance_mut(cnt)
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        self.deref_mut().chunk_mut()
+    }
+}
+
+#[cfg(feature = "fixed")]
+impl<T> Buf for fixed::Owned<T>
+where
+    T: Buf,
+{
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.deref().remaining()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.deref().chunk()
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+        self.deref_mut().advance(cnt)
+    }
+}
+
+#[cfg(feature = "fixed")]
+impl<T> AsyncRead for fixed::Owned<T>
+where
+    T: AsyncRead + Unpin,
+{
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().deref_mut()).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "fixed")]
+impl<T> AsyncWrite for fixed::Owned<T>
+where
+    T: AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.get_mut().deref_mut()).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().deref_mut()).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().deref_mut()).poll_shutdown(cx)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.get_mut().deref_mut()).poll_write_vectored(cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.deref().is_write_vectored()
+    }
+}
+
+// === impl growable::Owned ===
+
+#[cfg(feature = "growable")]
+unsafe impl<T, N> BufMut for growable::Owned<T, N>
+where
+    T: BufMut,
+{
+    #[inline]
+    fn remaining_mut(&self) -> usize {
+        self.deref().remaining_mut()
+    }
+
+    #[inline]
+    unsafe fn advance_mut(&mut self, cnt: usize) {
+        self.deref_mut().advance_mut(cnt)
+    }
+
+    #[inline]
+    fn chunk_mut(&mut self) -> &mut UninitSlice {
+        self.deref_mut().chunk_mut()
+    }
+}
+
+#[cfg(feature = "growable")]
+impl<T, N> Buf for growable::Owned<T, N>
+where
+    T: Buf,
+{
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.deref().remaining()
+    }
+
+    #[inline]
+    fn chunk(&self) -> &[u8] {
+        self.deref().chunk()
+    }
+
+    #[inline]
+    fn advance(&mut self, cnt: usize) {
+        self.deref_mut().advance(cnt)
+    }
+}
+
+#[cfg(feature = "growable")]
+impl<T, N> AsyncRead for growable::Owned<T, N>
+where
+    T: AsyncRead + Unpin,
+{
+    #[inline]
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().deref_mut()).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "growable")]
+impl<T, N> AsyncWrite for growable::Owned<T, N>
+where
+    T: AsyncWrite + Unpin,
+{
+    #[inline]
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.get_mut().deref_mut()).poll_write(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().deref_mut()).poll_flush(cx)
+    }
+
+    #[inline]
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(self.get_mut().deref_mut()).poll_shutdown(cx)
+    }
+
+    #[inline]
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(self.get_mut().deref_mut()).poll_write_vectored(cx, bufs)
+    }
+
+    #[inline]
+    fn is_write_vectored(&self) -> bool {
+        self.deref().is_write_vectored()
+    }
+}
@@ -0,0 +1,67 @@
+//! Waiting for other threads to make progress.
+//!
+//! Every retry loop in the crate waits using this module, rather than calling
+//! `core::hint::spin_loop` or `std::thread::yield_now` itself. Under `loom`,
+//! both spinning and yielding yield to the model's scheduler, so that the
+//! threads being waited on can run, and every retry loop is modeled the same
+//! way.
+// Only pools retry, so without either pool flavor, nothing uses this.
+#![cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+
+/// The number of times to back off by spinning before yielding instead.
+const SPIN_LIMIT: u32 = 6;
+
+/// Adaptive backoff for retrying a contended checkout.
+///
+/// The first few retries spin for exponentially longer periods, which is
+/// cheapest when the contention is brief. After that, the thread yields to
+/// the scheduler on every retry, so that a thread waiting on an exhausted pool
+/// doesn't starve the threads that would release items back to it.
+///
+/// This never parks. Checkouts that should stop using the CPU while a pool
+/// stays exhausted park on the pool's queue of waiters instead (see
+/// `fixed::Pool::checkout_blocking`).
+#[derive(Debug, Default)]
+pub(crate) struct Backoff {
+    retries: usize,
+}
+
+impl Backoff {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Waits before the next retry.
+    pub(crate) fn wait(&mut self) {
+        if self.retries < SPIN_LIMIT as usize {
+            for _ in 0..1 << self.retries {
+                spin();
+            }
+        } else {
+            yield_now();
+        }
+        self.retries += 1;
+    }
+
+    /// Returns the number of times `wait` has been called.
+    pub(crate) fn retries(&self) -> usize {
+        self.retries
+    }
+}
+
+/// Hints that the current thread is briefly waiting for another thread.
+#[inline]
+pub(crate) fn spin() {
+    #[cfg(test)]
+    loom::yield_now();
+    #[cfg(not(test))]
+    core::hint::spin_loop();
+}
+
+/// Yields the rest of the current thread's time slice.
+pub(crate) fn yield_now() {
+    #[cfg(test)]
+    loom::yield_now();
+    #[cfg(not(test))]
+    std::thread::yield_now();
+}
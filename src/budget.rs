@@ -0,0 +1,151 @@
+//! A memory budget shared by several pools.
+#[cfg(feature = "growable")]
+use crate::GrowError;
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A limit on the memory used by the items of a group of growable pools.
+///
+/// A `Budget` is shared by every pool built with
+/// [`Builder::with_budget`]. Each pool charges the budget for the memory it
+/// allocates for every item: the box holding the item, and the item's slot in
+/// the pool's array of slots (see [`Pool::bytes_per_item`]). A pool that would
+/// exceed the budget doesn't grow: [`Pool::checkout`] waits for one of the
+/// pool's own items to be released instead, like a fixed pool does, and
+/// [`Pool::checkout_fallible`] returns an error. A pool's charges are
+/// returned to the budget when its storage is dropped.
+///
+/// Memory that an item owns (such as a `String`'s buffer) isn't charged, and
+/// neither is the old slot array that is briefly held while a pool grows. A
+/// pool's initial items are always charged, even if they exceed the budget.
+///
+/// Cloning a `Budget` returns another handle to the same budget.
+///
+/// [`Builder::with_budget`]: struct.Builder.html#method.with_budget
+/// [`Pool::bytes_per_item`]: growable/struct.Pool.html#method.bytes_per_item
+/// [`Pool::checkout`]: growable/struct.Pool.html#method.checkout
+/// [`Pool::checkout_fallible`]: growable/struct.Pool.html#method.checkout_fallible
+#[derive(Clone)]
+pub struct Budget {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    limit: usize,
+    used: AtomicUsize,
+}
+
+/// The bytes a pool has charged to its budget, which are returned when this is
+/// dropped.
+#[cfg(feature = "growable")]
+#[derive(Debug, Default)]
+pub(crate) struct Charge {
+    budget: Option<Budget>,
+    bytes: usize,
+}
+
+// === impl Budget ===
+
+impl Budget {
+    /// Returns a new budget of `limit` bytes.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                limit,
+                used: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Returns the number of bytes that the pools sharing this budget may
+    /// use.
+    pub fn limit(&self) -> usize {
+        self.inner.limit
+    }
+
+    /// Returns the number of bytes charged to this budget.
+    pub fn used(&self) -> usize {
+        self.inner.used.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of bytes left in this budget.
+    pub fn available(&self) -> usize {
+        self.limit().saturating_sub(self.used())
+    }
+
+    /// Charges `bytes` to the budget if they fit, returning `false` if not.
+    #[cfg(feature = "growable")]
+    fn try_charge(&self, bytes: usize) -> bool {
+        let mut used = self.inner.used.load(Ordering::Relaxed);
+        loop {
+            let next = match used.checked_add(bytes) {
+                Some(next) if next <= self.inner.limit => next,
+                _ => return false,
+            };
+            match self.inner.used.compare_exchange_weak(
+                used,
+                next,
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => used = actual,
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Budget")
+            .field("limit", &self.limit())
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
+// === impl Charge ===
+
+#[cfg(feature = "growable")]
+impl Charge {
+    /// Charges `bytes` to `budget`, even if they exceed it.
+    pub(crate) fn new(budget: Option<Budget>, bytes: usize) -> Self {
+        if let Some(ref budget) = budget {
+            budget.inner.used.fetch_add(bytes, Ordering::AcqRel);
+        }
+        Self { budget, bytes }
+    }
+
+    /// Charges `bytes` more to the budget, or returns an error if they don't
+    /// fit.
+    pub(crate) fn try_add(&mut self, bytes: usize) -> Result<(), GrowError> {
+        if let Some(ref budget) = self.budget {
+            if !budget.try_charge(bytes) {
+                return Err(GrowError::over_budget());
+            }
+        }
+        self.bytes += bytes;
+        Ok(())
+    }
+
+    /// Returns `bytes` that were charged with `try_add`.
+    pub(crate) fn refund(&mut self, bytes: usize) {
+        if let Some(ref budget) = self.budget {
+            budget.inner.used.fetch_sub(bytes, Ordering::AcqRel);
+        }
+        self.bytes -= bytes;
+    }
+}
+
+#[cfg(feature = "growable")]
+impl Drop for Charge {
+    fn drop(&mut self) {
+        let bytes = self.bytes;
+        self.refund(bytes);
+    }
+}
@@ -1,20 +1,101 @@
-use crate::{fixed, growable, slab};
-use std::marker::PhantomData;
+#[cfg(feature = "fixed")]
+use crate::fixed;
+#[cfg(feature = "growable")]
+use crate::growable;
+use crate::{
+    slab::{self, Pressure, Reuse},
+    traits::{Clear, ClearWith, PreTouch, WithCapacity},
+};
+use std::{alloc::GlobalAlloc, fmt, marker::PhantomData, sync::Arc, time::Duration};
+#[cfg(any(feature = "fixed", feature = "growable"))]
+use std::{cmp, mem};
 
 #[derive(Debug, Clone)]
 pub struct Builder<S, T, N = fn() -> T> {
     pub(crate) new: N,
     pub(crate) settings: S,
     capacity: usize,
+    lease: Option<Duration>,
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    trace_checkouts: Option<Duration>,
+    watermarks: slab::Watermarks,
+    on_release: slab::OnRelease,
+    /// Set by fixed pools' `with_recycler`.
+    recycle: slab::Recycle<T>,
+    pub(crate) template: Option<T>,
+    pub(crate) seed: Vec<T>,
+    item_alignment: usize,
+    allocator: slab::Allocator,
+    reuse: Reuse,
+    dirty_tracking: bool,
+    /// If set, the functions that clear an item, and a boxed item, when they
+    /// are released. These are recorded here since only the builder method
+    /// knows that `T: Clear`.
+    clear_on_release: Option<ClearFns<T>>,
+    pub(crate) ctors: Ctors<T>,
     item: PhantomData<fn() -> T>,
 }
 
+/// Functions that clear an item, and a boxed item.
+type ClearFns<T> = (fn(&mut T), fn(&mut Box<T>));
+
+/// The number of items a pool is built with if `with_elements` is not called.
+pub(crate) const DEFAULT_CAPACITY: usize = 256;
+
 impl<T> Builder<(), T, ()> {
+    /// Returns a builder with the default settings.
+    ///
+    /// Choose the kind of pool with [`fixed`] or [`growable`], and how its
+    /// items are constructed with [`with_default`] or [`with_fn`], then build
+    /// the pool with [`finish`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[cfg(feature = "growable")] {
+    /// use natatorium::{
+    ///     growable::{Growth, Pool},
+    ///     Builder, Pressure,
+    /// };
+    ///
+    /// let pool: Pool<Vec<u8>, _> = Builder::new()
+    ///     .growable()
+    ///     .with_fn(|| Vec::with_capacity(4096))
+    ///     .with_elements(8)
+    ///     .with_growth(Growth::Fixed(8))
+    ///     .with_watermarks(4, 6)
+    ///     .finish();
+    ///
+    /// assert_eq!(pool.capacity(), 8);
+    /// let buf = pool.checkout();
+    /// assert!(buf.capacity() >= 4096);
+    /// assert_eq!(pool.pressure(), Pressure::Low);
+    /// # }
+    /// ```
+    ///
+    /// [`fixed`]: #method.fixed
+    /// [`growable`]: #method.growable
+    /// [`with_default`]: #method.with_default
+    /// [`with_fn`]: #method.with_fn
+    /// [`finish`]: #method.finish
     pub fn new() -> Self {
         Self {
             new: (),
             settings: (),
-            capacity: 256,
+            capacity: DEFAULT_CAPACITY,
+            lease: None,
+            trace_checkouts: None,
+            watermarks: slab::Watermarks::default(),
+            on_release: slab::OnRelease::default(),
+            recycle: slab::Recycle::default(),
+            template: None,
+            seed: Vec::new(),
+            item_alignment: 1,
+            allocator: slab::Allocator::default(),
+            reuse: Reuse::Lifo,
+            dirty_tracking: false,
+            clear_on_release: None,
+            ctors: Ctors::new(),
             item: PhantomData,
         }
     }
@@ -25,6 +106,240 @@ impl<S, T, N> Builder<S, T, N> {
         Self { capacity, ..self }
     }
 
+    /// Enables lease mode, in which every checkout must be released within
+    /// `duration`.
+    ///
+    /// A checkout that outlives its lease may be marked as _lost_ by the pool's
+    /// `reclaim_expired` method. A lost slot is never handed out again, and
+    /// dropping the stuck checkout once it is finally released does nothing.
+    /// This allows a growable pool to replace capacity held by a consumer that
+    /// will never return it, at the cost of a little extra bookkeeping on every
+    /// release.
+    pub fn with_lease(self, duration: Duration) -> Self {
+        Self {
+            lease: Some(duration),
+            ..self
+        }
+    }
+
+    /// Traces [`Owned`] checkouts that are held for longer than `threshold`,
+    /// or that are released on a different thread than they were checked out
+    /// on.
+    ///
+    /// Either is a common symptom of a checkout being held across an `.await`
+    /// point, which can starve other tasks of the pool's items. When one of
+    /// these checkouts is released, a `WARN` event is emitted using
+    /// [`tracing`]. Tracing a checkout costs a timestamp and a thread ID
+    /// lookup each time an item is checked out and released.
+    ///
+    /// [`Owned`]: fixed/struct.Owned.html
+    /// [`tracing`]: https://docs.rs/tracing
+    #[cfg(feature = "tracing")]
+    pub fn trace_checkouts(self, threshold: Duration) -> Self {
+        Self {
+            trace_checkouts: Some(threshold),
+            ..self
+        }
+    }
+
+    /// Allocates the pool's storage with `alloc`, rather than the global
+    /// allocator.
+    ///
+    /// This allows a pool's memory to come from an arena, from huge pages, or
+    /// from an instrumented allocator. Only the pool's slots (which hold a
+    /// fixed pool's items inline) are allocated this way: the items'
+    /// own allocations, and the boxed items of a growable pool, still use the
+    /// global allocator.
+    ///
+    /// The allocator is kept alive for as long as the pool's storage is.
+    pub fn with_allocator<A>(self, alloc: A) -> Self
+    where
+        A: GlobalAlloc + Send + Sync + 'static,
+    {
+        Self {
+            allocator: slab::Allocator::new(alloc),
+            ..self
+        }
+    }
+
+    /// Sets the order in which the pool reuses its free items.
+    ///
+    /// By default, the most recently released item is checked out next
+    /// ([`Reuse::Lifo`]), which keeps the items in use warm in the CPU's
+    /// caches. See [`Reuse`] for the alternatives.
+    ///
+    /// [`Reuse::Lifo`]: enum.Reuse.html#variant.Lifo
+    /// [`Reuse`]: enum.Reuse.html
+    pub fn with_reuse(self, reuse: Reuse) -> Self {
+        Self { reuse, ..self }
+    }
+
+    /// Skips clearing items that weren't modified while they were checked
+    /// out.
+    ///
+    /// Normally, every item is cleared each time it is checked out. With dirty
+    /// tracking, a pool instead notes whenever an [`Owned`] checkout is
+    /// mutably dereferenced, and only clears items that were. This saves the
+    /// cost of clearing large items (such as maps) that are mostly checked
+    /// out to be read, such as checkouts that are immediately downgraded to
+    /// [`Shared`] checkouts.
+    ///
+    /// Items modified through interior mutability (such as a `Mutex` or a
+    /// `Cell`) behind a shared reference are not noticed, so this should not
+    /// be used with such items.
+    ///
+    /// [`Owned`]: fixed/struct.Owned.html
+    /// [`Shared`]: fixed/struct.Shared.html
+    pub fn with_dirty_tracking(self) -> Self {
+        Self {
+            dirty_tracking: true,
+            ..self
+        }
+    }
+
+    /// Clears items when they are released, rather than when they are next
+    /// checked out.
+    ///
+    /// By default, an item is cleared when it is checked out, so whatever a
+    /// checkout left in it stays in memory until the item is reused. Clearing
+    /// on release keeps the lifetime of sensitive data as short as possible,
+    /// and moves the cost of clearing off of the checkout path and onto the
+    /// thread that drops the last reference to the item.
+    ///
+    /// Items that were never checked out, or that were [repaired] after being
+    /// poisoned, are still cleared when they are checked out.
+    ///
+    /// [repaired]: fixed/struct.Pool.html#method.repair
+    pub fn clear_on_release(self) -> Self
+    where
+        T: Clear,
+    {
+        Self {
+            clear_on_release: Some((slab::clear::<T>, slab::clear::<Box<T>>)),
+            ..self
+        }
+    }
+
+    /// Sets low and high watermarks on the number of checked out items.
+    ///
+    /// The pool's `pressure` method compares the number of checked out items
+    /// to these watermarks, so that callers can begin shedding load _before_
+    /// the pool is exhausted. Without watermarks, the pool's pressure is
+    /// always [`Pressure::Low`].
+    ///
+    /// # Panics
+    ///
+    /// If `low` is greater than `high`.
+    ///
+    /// [`Pressure::Low`]: enum.Pressure.html#variant.Low
+    pub fn with_watermarks(self, low: usize, high: usize) -> Self {
+        Self {
+            watermarks: self.watermarks.levels(low, high),
+            ..self
+        }
+    }
+
+    /// Applies the settings in `config`.
+    ///
+    /// Settings that `config` leaves unset are unchanged. A [`Config`]'s
+    /// growth policy only applies to growable pools, so this should be called
+    /// once the kind of pool has been chosen.
+    ///
+    /// # Panics
+    ///
+    /// If `config`'s low watermark is greater than its high watermark.
+    ///
+    /// [`Config`]: struct.Config.html
+    pub fn with_config(mut self, config: &crate::Config) -> Self
+    where
+        S: settings::Configure,
+    {
+        self.settings.configure(config);
+        if let Some(capacity) = config.capacity {
+            self = self.with_elements(capacity);
+        }
+        if let Some(watermarks) = config.watermarks {
+            self = self.with_watermarks(watermarks.low, watermarks.high);
+        }
+        if let Some(reuse) = config.reuse {
+            self = self.with_reuse(reuse);
+        }
+        self
+    }
+
+    /// Sets a function to call whenever the pool's [pressure] changes.
+    ///
+    /// The function is called with the new pressure by the thread whose
+    /// checkout or release crossed a watermark, so it should be cheap, and it
+    /// must not check items out of (or return items to) the pool. It is never
+    /// called unless watermarks were set with [`with_watermarks`].
+    ///
+    /// [pressure]: enum.Pressure.html
+    /// [`with_watermarks`]: #method.with_watermarks
+    pub fn on_pressure_change<F>(self, f: F) -> Self
+    where
+        F: Fn(Pressure) + Send + Sync + 'static,
+    {
+        Self {
+            watermarks: self.watermarks.on_change(f),
+            ..self
+        }
+    }
+
+    /// Sets a function to call whenever a tagged checkout is released.
+    ///
+    /// Checkouts are tagged with `Owned::with_tag`, such as with the ID or
+    /// kind of the request they were checked out for, so that the pool's usage
+    /// can be attributed to each kind of request without wrapping the pooled
+    /// type. The function is called with the checkout's tag when it's dropped.
+    /// Releasing an untagged checkout doesn't call it.
+    ///
+    /// As with [`on_pressure_change`], the function is called by the thread
+    /// releasing the checkout, so it should be cheap, and it must not check
+    /// items out of (or return items to) the pool.
+    ///
+    /// [`on_pressure_change`]: #method.on_pressure_change
+    pub fn on_release<F>(self, f: F) -> Self
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        Self {
+            on_release: slab::OnRelease::new(f),
+            ..self
+        }
+    }
+
+    /// Seeds the pool with existing items.
+    ///
+    /// Seeded items are placed in the pool's slots before any new items are
+    /// constructed, so that (for example) buffers that were already allocated
+    /// can be reused. They count towards the pool's capacity; if there are
+    /// more seeded items than the configured capacity, the pool's initial
+    /// capacity is the number of seeded items. Like any other item, a seeded
+    /// item is cleared when it is checked out.
+    pub fn seeded(self, items: impl IntoIterator<Item = T>) -> Self {
+        let mut seed = self.seed;
+        seed.extend(items);
+        Self { seed, ..self }
+    }
+
+    /// Registers a constructor named `tag`, which initializes a cleared item
+    /// when it is checked out with `checkout_with_ctor(tag)`.
+    ///
+    /// This allows a single pool to serve several kinds of request, each of
+    /// which needs its items initialized differently, without clearing and
+    /// then re-initializing items in the caller. Registering a constructor
+    /// with the same tag as an existing one replaces it.
+    pub fn with_ctor<F>(self, tag: &'static str, init: F) -> Self
+    where
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        Self {
+            ctors: self.ctors.with(tag, init),
+            ..self
+        }
+    }
+
     pub fn with_default(self) -> Builder<S, T>
     where
         T: Default,
@@ -32,6 +347,19 @@ impl<S, T, N> Builder<S, T, N> {
         Builder {
             new: T::default,
             capacity: self.capacity,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            recycle: self.recycle,
+            template: self.template,
+            seed: self.seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            clear_on_release: self.clear_on_release,
+            ctors: self.ctors,
             settings: self.settings,
             item: PhantomData,
         }
@@ -44,24 +372,197 @@ impl<S, T, N> Builder<S, T, N> {
         Builder {
             new,
             capacity: self.capacity,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            recycle: self.recycle,
+            template: self.template,
+            seed: self.seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            clear_on_release: self.clear_on_release,
+            ctors: self.ctors,
+            settings: self.settings,
+            item: PhantomData,
+        }
+    }
+
+    /// Sizes the pool to use about `total` bytes, in items that are each
+    /// constructed with `T::with_capacity(each)`.
+    ///
+    /// The pool gets `total / each` items, rounded down, replacing any
+    /// element count or constructor set before. This is meant for pools of
+    /// byte buffers, such as `Vec<u8>` or `String`, whose capacity is
+    /// measured in bytes; for other items, `each` is in whatever units
+    /// [`WithCapacity`] uses, and only the items' buffers (not the items
+    /// themselves) are counted.
+    ///
+    /// # Panics
+    ///
+    /// If `each` is zero, or greater than `total`.
+    ///
+    /// [`WithCapacity`]: traits/trait.WithCapacity.html
+    pub fn with_total_bytes(self, total: usize, each: usize) -> Builder<S, T, impl FnMut() -> T>
+    where
+        T: WithCapacity,
+    {
+        assert!(each > 0, "pooled items must have a non-zero capacity");
+        assert!(
+            each <= total,
+            "a pool of {} bytes can't hold an item of {} bytes",
+            total,
+            each
+        );
+        self.with_elements(total / each)
+            .with_fn(move || T::with_capacity(each))
+    }
+
+    /// Wraps each item in a [`ClearWith`] that is cleared by calling `clear`.
+    ///
+    /// This allows types that don't implement [`Clear`] to be pooled. Each
+    /// item stores its own copy of `clear`, so this is best used with a
+    /// function or a closure that doesn't capture anything.
+    ///
+    /// Since this changes the type of the pool's items, it discards any
+    /// recycler set with [`with_recycler`]; set the recycler afterwards.
+    ///
+    /// [`ClearWith`]: struct.ClearWith.html
+    /// [`Clear`]: trait.Clear.html
+    /// [`with_recycler`]: #method.with_recycler
+    pub fn with_clear_fn<F>(
+        self,
+        clear: F,
+    ) -> Builder<S, ClearWith<T, F>, impl FnMut() -> ClearWith<T, F>>
+    where
+        N: FnMut() -> T,
+        T: 'static,
+        F: Fn(&mut T) + Clone + 'static,
+    {
+        let mut new = self.new;
+        let template = self
+            .template
+            .map(|item| ClearWith::new(item, clear.clone()));
+        let seed = self
+            .seed
+            .into_iter()
+            .map(|item| ClearWith::new(item, clear.clone()))
+            .collect();
+        Builder {
+            new: move || ClearWith::new(new(), clear.clone()),
+            capacity: self.capacity,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            // A recycler for `T` can't be handed the new items.
+            recycle: slab::Recycle::default(),
+            template,
+            seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            // The new items are cleared with `clear`, however they were
+            // going to be cleared before.
+            clear_on_release: self.clear_on_release.map(|_| {
+                (
+                    slab::clear::<ClearWith<T, F>> as fn(&mut _),
+                    slab::clear::<Box<ClearWith<T, F>>> as fn(&mut _),
+                )
+            }),
+            ctors: self.ctors.map(|item: &mut ClearWith<T, F>| &mut **item),
+            settings: self.settings,
+            item: PhantomData,
+        }
+    }
+
+    /// If `enabled`, touches every page of each item's allocated capacity as
+    /// soon as the item is constructed.
+    ///
+    /// This moves the cost of the page faults caused by first writing to a
+    /// freshly allocated buffer out of the checkout path, and into pool
+    /// construction (or growth, for growable pools). It only makes sense for
+    /// buffers that are allocated with a large capacity up front, such as
+    /// those created by [`with_fn`].
+    ///
+    /// [`with_fn`]: #method.with_fn
+    pub fn pre_touch(self, enabled: bool) -> Builder<S, T, impl FnMut() -> T>
+    where
+        N: FnMut() -> T,
+        T: PreTouch,
+    {
+        let mut new = self.new;
+        Builder {
+            new: move || {
+                let mut item = new();
+                if enabled {
+                    item.pre_touch();
+                }
+                item
+            },
+            capacity: self.capacity,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            recycle: self.recycle,
+            template: self.template,
+            seed: self.seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            clear_on_release: self.clear_on_release,
+            ctors: self.ctors,
             settings: self.settings,
             item: PhantomData,
         }
     }
 
+    #[cfg(feature = "growable")]
     pub fn growable(self) -> Builder<growable::Settings, T, N> {
         Builder {
             new: self.new,
             capacity: self.capacity,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            recycle: self.recycle,
+            template: self.template,
+            seed: self.seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            clear_on_release: self.clear_on_release,
+            ctors: self.ctors,
             settings: growable::Settings::default(),
             item: PhantomData,
         }
     }
 
+    #[cfg(feature = "fixed")]
     pub fn fixed(self) -> Builder<fixed::Settings, T, N> {
         Builder {
             new: self.new,
             capacity: self.capacity,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            recycle: self.recycle,
+            template: self.template,
+            seed: self.seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            clear_on_release: self.clear_on_release,
+            ctors: self.ctors,
             settings: fixed::Settings::default(),
             item: PhantomData,
         }
@@ -74,21 +575,228 @@ impl<S, T, N> Builder<S, T, N> {
         S::make(self)
     }
 
-    pub(crate) fn slab<I>(&mut self) -> slab::Slab<I>
+    /// Builds the pool's slab, consuming the builder.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn into_slab(mut self) -> slab::Slab<T>
+    where
+        N: FnMut() -> T,
+    {
+        let clear = self.clear_on_release.map(|(clear, _)| clear);
+        let mut slab = self.build_slab(|item| item, clear);
+        slab.set_recycle(self.recycle);
+        slab
+    }
+
+    /// Builds a slab of boxed items, leaving the builder's constructor and
+    /// settings in place so that the pool can construct more items as it
+    /// grows.
+    #[cfg(feature = "growable")]
+    pub(crate) fn boxed_slab(&mut self) -> slab::Slab<Box<T>>
     where
         N: FnMut() -> T,
-        T: Into<I>,
     {
-        slab::Slab::from_fn(self.capacity, &mut || (self.new)().into())
+        let clear = self.clear_on_release.map(|(_, clear)| clear);
+        self.build_slab(Box::new, clear)
+    }
+
+    #[cfg(any(feature = "fixed", feature = "growable"))]
+    fn build_slab<I>(
+        &mut self,
+        mut wrap: impl FnMut(T) -> I,
+        clear_on_release: Option<fn(&mut I)>,
+    ) -> slab::Slab<I>
+    where
+        N: FnMut() -> T,
+    {
+        // Seeded items count towards the pool's capacity, and the rest of the
+        // slots are filled with newly constructed items.
+        let capacity = cmp::max(self.capacity, self.seed.len());
+        let mut seed = mem::take(&mut self.seed).into_iter();
+        let new = &mut self.new;
+        let mut slab = slab::Slab::with_alignment(self.item_alignment);
+        slab.set_allocator(self.allocator.clone());
+        slab.set_reuse(self.reuse);
+        if self.dirty_tracking {
+            slab.set_dirty_tracking();
+        }
+        if let Some(clear) = clear_on_release {
+            slab.set_clear_on_release(clear);
+        }
+        slab.grow_by(capacity, &mut || {
+            wrap(seed.next().unwrap_or_else(&mut *new))
+        });
+        if let Some(lease) = self.lease {
+            slab.set_lease(lease);
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(threshold) = self.trace_checkouts {
+            slab.set_trace_checkouts(threshold);
+        }
+        slab.set_watermarks(self.watermarks.clone());
+        slab.set_on_release(self.on_release.clone());
+        slab
     }
 }
 
+#[cfg(feature = "fixed")]
+impl<T, N> Builder<fixed::Settings, T, N> {
+    /// Aligns each item in the pool to `align` bytes.
+    ///
+    /// Fixed pools store their items inline, so this may be used when items
+    /// must be over-aligned, such as page-aligned buffers for direct I/O or
+    /// items that should each occupy their own cache lines. Slots are padded
+    /// as needed, so a large alignment increases the pool's memory use. If
+    /// `align` is smaller than the item type's own alignment, it has no
+    /// effect.
+    ///
+    /// # Panics
+    ///
+    /// If `align` is not a power of two.
+    pub fn with_item_alignment(self, align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "item alignment must be a power of two, but was {}",
+            align
+        );
+        Self {
+            item_alignment: align,
+            ..self
+        }
+    }
+
+    /// Hands each item released by an [`Owned`] checkout to `recycler`,
+    /// rather than returning it to the pool straight away.
+    ///
+    /// The recycler gets the item as a [`Recycled`] handle, which it may
+    /// inspect or modify (such as to flush a write buffer, or log its
+    /// contents), and which returns the item to the pool when it's dropped or
+    /// [completed]. Until then, the item is still checked out. The recycler
+    /// may be a function, which is called by the thread releasing the item,
+    /// or the sending half of a channel, so that another thread or task can
+    /// finish recycling the item. See [`Recycler`] for details.
+    ///
+    /// An item is only recycled if an [`Owned`] checkout held its last
+    /// reference, so an item that was [split] is recycled once its last part
+    /// is dropped. Items released by [`Shared`], [`Scoped`], or [`OwnedSet`]
+    /// checkouts, or by a checkout that is dropped while its thread is
+    /// panicking, are returned to the pool directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::{fixed::Pool, Builder};
+    /// use std::{io::Write, sync::mpsc, thread};
+    ///
+    /// let (recycler, released) = mpsc::channel();
+    /// let pool: Pool<Vec<u8>> = Builder::new()
+    ///     .with_default()
+    ///     .fixed()
+    ///     .with_elements(4)
+    ///     .with_recycler(recycler)
+    ///     .finish();
+    ///
+    /// // Flush each released buffer on a background thread.
+    /// let flusher = thread::spawn(move || {
+    ///     let mut out = Vec::new();
+    ///     for buf in released {
+    ///         out.write_all(&buf).unwrap();
+    ///         buf.complete();
+    ///     }
+    ///     out
+    /// });
+    ///
+    /// let mut buf = pool.checkout();
+    /// buf.extend_from_slice(b"hello");
+    /// drop(buf);
+    ///
+    /// drop(pool);
+    /// assert_eq!(flusher.join().unwrap(), b"hello");
+    /// ```
+    ///
+    /// [`Owned`]: fixed/struct.Owned.html
+    /// [`Recycled`]: fixed/struct.Recycled.html
+    /// [completed]: fixed/struct.Recycled.html#method.complete
+    /// [`Recycler`]: fixed/trait.Recycler.html
+    /// [split]: fixed/struct.Owned.html#method.split
+    /// [`Shared`]: fixed/struct.Shared.html
+    /// [`Scoped`]: fixed/struct.Scoped.html
+    /// [`OwnedSet`]: fixed/struct.OwnedSet.html
+    pub fn with_recycler<R>(self, recycler: R) -> Self
+    where
+        R: fixed::Recycler<T>,
+    {
+        Self {
+            recycle: slab::Recycle::new(move |slab, slot, generation| {
+                recycler.recycle(fixed::Recycled::new(slab.clone(), slot, generation))
+            }),
+            ..self
+        }
+    }
+
+    /// Sets a read-only template item for the pool.
+    ///
+    /// When the pool has no free items, [`try_checkout_owned_or_shared`]
+    /// falls back to returning a [`Shared`] checkout of the template, rather
+    /// than failing. The template is stored separately from the pool's other
+    /// items, and is never cleared.
+    ///
+    /// [`try_checkout_owned_or_shared`]: fixed/struct.Pool.html#method.try_checkout_owned_or_shared
+    /// [`Shared`]: fixed/struct.Shared.html
+    pub fn with_template(self, template: T) -> Self {
+        Self {
+            template: Some(template),
+            ..self
+        }
+    }
+
+    /// Sets the pool's capacity to `CAP`, making it part of the pool's type.
+    ///
+    /// The resulting pool is a [`fixed::Pool<T, CAP>`]. `CAP` may not be
+    /// zero, which is the [`DYNAMIC`] marker for pools whose capacity is
+    /// chosen at runtime:
+    ///
+    /// ```compile_fail
+    /// use natatorium::fixed::Pool;
+    ///
+    /// let pool: Pool<String, 0> = Pool::builder()
+    ///     .with_default()
+    ///     .with_const_elements::<0>()
+    ///     .finish();
+    /// ```
+    ///
+    /// [`fixed::Pool<T, CAP>`]: fixed/struct.Pool.html
+    /// [`DYNAMIC`]: fixed/constant.DYNAMIC.html
+    pub fn with_const_elements<const CAP: usize>(self) -> Builder<fixed::ConstSettings<CAP>, T, N> {
+        let () = fixed::ConstSettings::<CAP>::NOT_DYNAMIC;
+        Builder {
+            new: self.new,
+            capacity: CAP,
+            lease: self.lease,
+            trace_checkouts: self.trace_checkouts,
+            watermarks: self.watermarks,
+            on_release: self.on_release,
+            recycle: self.recycle,
+            template: self.template,
+            seed: self.seed,
+            item_alignment: self.item_alignment,
+            allocator: self.allocator,
+            reuse: self.reuse,
+            dirty_tracking: self.dirty_tracking,
+            clear_on_release: self.clear_on_release,
+            ctors: self.ctors,
+            settings: fixed::ConstSettings::default(),
+            item: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "growable")]
 impl<T, N> Builder<growable::Settings, T, N> {
     pub fn grow_by(self, amount: usize) -> Self {
         Self {
             settings: growable::Settings {
                 growth: growable::Growth::Fixed(amount),
-                // ..self.settings
+                ..self.settings
             },
             ..self
         }
@@ -98,7 +806,60 @@ impl<T, N> Builder<growable::Settings, T, N> {
         Self {
             settings: growable::Settings {
                 growth: growable::Growth::Double,
-                // ..self.settings
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Sets the policy the pool uses to decide how much to grow by when it
+    /// runs out of items.
+    ///
+    /// Regardless of the policy, the pool always grows by at least one slot.
+    pub fn with_growth(self, growth: growable::Growth) -> Self {
+        Self {
+            settings: growable::Settings {
+                growth,
+                ..self.settings
+            },
+            ..self
+        }
+    }
+
+    /// Grows the pool by exactly `n` items whenever it runs out, rather than
+    /// by doubling.
+    ///
+    /// Each of a growable pool's items is allocated in its own box, so with a
+    /// constant block size, every growth makes `n` allocations of the item's
+    /// size, plus one reallocation of the pool's (small) array of slot
+    /// headers. This keeps the memory a pool adds at once predictable, at the
+    /// cost of growing more often than doubling would.
+    ///
+    /// This is equivalent to `with_growth(Growth::Fixed(n))`.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is zero.
+    pub fn with_elements_per_block(self, n: usize) -> Self {
+        assert!(
+            n > 0,
+            "a growable pool's blocks must hold at least one item"
+        );
+        self.with_growth(growable::Growth::Fixed(n))
+    }
+
+    /// Charges the pool's items to `budget`, which may be shared with other
+    /// pools.
+    ///
+    /// The pool won't grow past what the budget allows. See [`Budget`] for
+    /// details.
+    ///
+    /// [`Budget`]: struct.Budget.html
+    pub fn with_budget(self, budget: crate::Budget) -> Self {
+        Self {
+            settings: growable::Settings {
+                budget: Some(budget),
+                ..self.settings
             },
             ..self
         }
@@ -108,7 +869,7 @@ impl<T, N> Builder<growable::Settings, T, N> {
         Self {
             settings: growable::Settings {
                 growth: growable::Growth::Half,
-                // ..self.settings
+                ..self.settings
             },
             ..self
         }
@@ -128,4 +889,92 @@ pub(crate) mod settings {
         type Pool;
         fn make(builder: Builder<Self, T, N>) -> Self::Pool;
     }
+
+    /// Applies the parts of a `Config` that are specific to a kind of pool.
+    pub trait Configure {
+        fn configure(&mut self, _config: &crate::Config) {}
+    }
+
+    impl Configure for () {}
+}
+
+/// Named functions that initialize a cleared item when it is checked out.
+pub(crate) struct Ctors<T> {
+    ctors: Arc<Vec<Ctor<T>>>,
+}
+
+type Ctor<T> = (&'static str, Arc<dyn Fn(&mut T) + Send + Sync>);
+
+// === impl Ctors ===
+
+impl<T> Ctors<T> {
+    fn new() -> Self {
+        Self {
+            ctors: Arc::new(Vec::new()),
+        }
+    }
+
+    fn with<F>(mut self, tag: &'static str, init: F) -> Self
+    where
+        F: Fn(&mut T) + Send + Sync + 'static,
+    {
+        let init = Arc::new(init);
+        let ctors = Arc::make_mut(&mut self.ctors);
+        match ctors.iter_mut().find(|(t, _)| *t == tag) {
+            Some(ctor) => ctor.1 = init,
+            None => ctors.push((tag, init)),
+        }
+        self
+    }
+
+    /// Returns the constructor named `tag`.
+    ///
+    /// # Panics
+    ///
+    /// If no constructor named `tag` was registered.
+    #[cfg(any(feature = "fixed", feature = "growable"))]
+    pub(crate) fn get(&self, tag: &str) -> &Arc<dyn Fn(&mut T) + Send + Sync> {
+        match self.ctors.iter().find(|(t, _)| *t == tag) {
+            Some((_, init)) => init,
+            None => panic!("no constructor named {:?} was registered", tag),
+        }
+    }
+
+    /// Converts constructors for `T` into constructors for a type that wraps
+    /// `T`.
+    fn map<U>(self, inner: fn(&mut U) -> &mut T) -> Ctors<U>
+    where
+        T: 'static,
+        U: 'static,
+    {
+        let ctors = self
+            .ctors
+            .iter()
+            .map(|(tag, init)| {
+                let init = init.clone();
+                let init: Arc<dyn Fn(&mut U) + Send + Sync> =
+                    Arc::new(move |item: &mut U| init(inner(item)));
+                (*tag, init)
+            })
+            .collect();
+        Ctors {
+            ctors: Arc::new(ctors),
+        }
+    }
+}
+
+impl<T> Clone for Ctors<T> {
+    fn clone(&self) -> Self {
+        Self {
+            ctors: self.ctors.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Ctors<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.ctors.iter().map(|(tag, _)| tag))
+            .finish()
+    }
 }
@@ -0,0 +1,297 @@
+//! A pool of items sorted into size classes by their capacity.
+//!
+//! When items such as buffers are needed in very different sizes, a single
+//! pool either wastes memory (if every item is as large as the largest
+//! request) or reallocates (if items are grown on demand). A classified
+//! [`Pool`] instead holds a fixed pool of items for each of a list of
+//! capacities, and checks each request out of the smallest class whose items
+//! are large enough for it.
+//!
+//! [`Pool`]: struct.Pool.html
+use crate::{
+    fixed::{self, Owned},
+    sync::atomic::{AtomicUsize, Ordering},
+    traits::{Clear, WithCapacity},
+    RetryHistogram,
+};
+use std::{fmt, sync::Arc};
+
+/// A pool that checks items out of one of several size classes.
+///
+/// Each class is a fixed pool of items constructed with the class's capacity.
+/// [`checkout`] takes the smallest capacity the caller needs, and checks out
+/// an item from the smallest class whose capacity is at least that large. If
+/// that class is exhausted, the next larger class is tried, and so on; the
+/// checkout is counted as [spilled] from the class it was meant for.
+///
+/// Items are returned to the class they were checked out of, regardless of
+/// how their capacity changed while they were in use.
+///
+/// [`checkout`]: #method.checkout
+/// [spilled]: struct.Class.html#method.spilled
+pub struct Pool<T> {
+    classes: Arc<[Class<T>]>,
+}
+
+/// One of a classified [`Pool`]'s size classes.
+///
+/// [`Pool`]: struct.Pool.html
+pub struct Class<T> {
+    capacity: usize,
+    pool: fixed::Pool<T>,
+    spilled: AtomicUsize,
+}
+
+/// How many of a size class's items are checked out.
+///
+/// Returned by [`Pool::occupancy`].
+///
+/// [`Pool::occupancy`]: struct.Pool.html#method.occupancy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Occupancy {
+    /// The capacity that the class's items are constructed with.
+    pub capacity: usize,
+    /// The number of items in the class.
+    pub size: usize,
+    /// The number of the class's items that are checked out.
+    pub used: usize,
+}
+
+// === impl Pool ===
+
+impl<T> Pool<T>
+where
+    T: WithCapacity,
+{
+    /// Returns a new pool with the given size classes.
+    ///
+    /// Each class is a pair of the capacity that its items are constructed
+    /// with, and the number of items in the class. Classes may be given in
+    /// any order.
+    ///
+    /// # Panics
+    ///
+    /// If no classes are given, or if two classes have the same capacity.
+    pub fn new(classes: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        let mut classes = classes
+            .into_iter()
+            .map(|(capacity, count)| Class {
+                capacity,
+                pool: fixed::Pool::with_capacity_and_fn(count, move || T::with_capacity(capacity)),
+                spilled: AtomicUsize::new(0),
+            })
+            .collect::<Vec<_>>();
+        assert!(
+            !classes.is_empty(),
+            "a classified pool needs at least one class"
+        );
+        classes.sort_by_key(Class::capacity);
+        for pair in classes.windows(2) {
+            assert_ne!(
+                pair[0].capacity, pair[1].capacity,
+                "size classes must have distinct capacities"
+            );
+        }
+        Self {
+            classes: classes.into(),
+        }
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns the pool's size classes, from smallest to largest.
+    pub fn classes(&self) -> &[Class<T>] {
+        &self.classes
+    }
+
+    /// Returns the smallest class whose capacity is at least `min_capacity`,
+    /// which requests for `min_capacity` are checked out of unless it is
+    /// exhausted.
+    pub fn class_for(&self, min_capacity: usize) -> Option<&Class<T>> {
+        self.classes
+            .iter()
+            .find(|class| class.capacity >= min_capacity)
+    }
+
+    /// Returns the total number of items in every class.
+    pub fn capacity(&self) -> usize {
+        self.classes.iter().map(|class| class.pool.capacity()).sum()
+    }
+
+    /// Returns the total number of items checked out of every class.
+    pub fn used(&self) -> usize {
+        self.classes.iter().map(|class| class.pool.used()).sum()
+    }
+
+    /// Returns how many items are checked out of each class, from smallest to
+    /// largest.
+    ///
+    /// The entry for each class is at the same index as the class in
+    /// [`classes`]. Classes are read one at a time, so if items are checked
+    /// out or released meanwhile, the entries may not all be from the same
+    /// instant.
+    ///
+    /// [`classes`]: #method.classes
+    pub fn occupancy(&self) -> Vec<Occupancy> {
+        self.classes
+            .iter()
+            .map(|class| Occupancy {
+                capacity: class.capacity,
+                size: class.pool.capacity(),
+                used: class.pool.used(),
+            })
+            .collect()
+    }
+
+    fn fitting(&self, min_capacity: usize) -> &[Class<T>] {
+        let first = self
+            .classes
+            .iter()
+            .position(|class| class.capacity >= min_capacity)
+            .unwrap_or(self.classes.len());
+        &self.classes[first..]
+    }
+}
+
+impl<T> Pool<T>
+where
+    T: Clear,
+{
+    /// Attempts to check out an item with a capacity of at least
+    /// `min_capacity`, without blocking.
+    ///
+    /// Returns `None` if every class that is large enough is exhausted, or if
+    /// no class is large enough.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn try_checkout(&self, min_capacity: usize) -> Option<Owned<T>> {
+        let fitting = self.fitting(min_capacity);
+        let (idx, checkout) = fitting
+            .iter()
+            .enumerate()
+            .find_map(|(idx, class)| Some((idx, class.pool.try_checkout()?)))?;
+        if idx > 0 {
+            fitting[0].spilled.fetch_add(1, Ordering::Relaxed);
+        }
+        Some(checkout)
+    }
+
+    /// Attempts to check out an item from the class at index `idx` in
+    /// [`classes`], without blocking.
+    ///
+    /// Unlike [`try_checkout`], this never spills into a larger class, and
+    /// doesn't count towards any class's [spilled] checkouts. It's meant for
+    /// callers that route checkouts to classes themselves, such as a runtime
+    /// that assigns each worker thread a class of its own. Returns `None` if
+    /// the class is exhausted.
+    ///
+    /// # Panics
+    ///
+    /// If there is no class at index `idx`.
+    ///
+    /// [`classes`]: #method.classes
+    /// [`try_checkout`]: #method.try_checkout
+    /// [spilled]: struct.Class.html#method.spilled
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn try_checkout_from_class(&self, idx: usize) -> Option<Owned<T>> {
+        let class = self.classes.get(idx).unwrap_or_else(|| {
+            panic!(
+                "no size class at index {} (the pool has {} classes)",
+                idx,
+                self.classes.len(),
+            )
+        });
+        class.pool.try_checkout()
+    }
+
+    /// Checks out an item with a capacity of at least `min_capacity`.
+    ///
+    /// If every class that is large enough is exhausted, this blocks until an
+    /// item in the smallest of them is released.
+    ///
+    /// # Panics
+    ///
+    /// If no class is large enough.
+    pub fn checkout(&self, min_capacity: usize) -> Owned<T> {
+        if let Some(checkout) = self.try_checkout(min_capacity) {
+            return checkout;
+        }
+        match self.fitting(min_capacity).first() {
+            Some(class) => class.pool.checkout(),
+            None => panic!(
+                "no size class has a capacity of at least {} (the largest is {})",
+                min_capacity,
+                self.classes[self.classes.len() - 1].capacity,
+            ),
+        }
+    }
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            classes: self.classes.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("classes", &self.classes)
+            .finish()
+    }
+}
+
+// === impl Class ===
+
+impl<T> Class<T> {
+    /// Returns the capacity that this class's items are constructed with.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the pool of items in this class.
+    ///
+    /// This may be used to read the class's statistics, or to check items out
+    /// of this class directly.
+    pub fn pool(&self) -> &fixed::Pool<T> {
+        &self.pool
+    }
+
+    /// Returns the number of items checked out of this class.
+    pub fn used(&self) -> usize {
+        self.pool.used()
+    }
+
+    /// Returns the number of items in this class that may be checked out
+    /// before it is exhausted.
+    pub fn available(&self) -> usize {
+        self.pool.available()
+    }
+
+    /// Returns the number of checkouts that were meant for this class, but
+    /// were served by a larger class because this one was exhausted.
+    ///
+    /// A class that spills often may need more items.
+    pub fn spilled(&self) -> usize {
+        self.spilled.load(Ordering::Relaxed)
+    }
+
+    /// Returns a histogram of how many times blocking checkouts from this
+    /// class have had to retry.
+    pub fn retry_histogram(&self) -> RetryHistogram {
+        self.pool.retry_histogram()
+    }
+}
+
+impl<T> fmt::Debug for Class<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Class")
+            .field("capacity", &self.capacity)
+            .field("size", &self.pool.capacity())
+            .field("used", &self.pool.used())
+            .field("spilled", &self.spilled())
+            .finish()
+    }
+}
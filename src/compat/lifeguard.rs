@@ -0,0 +1,144 @@
+//! An adapter with the API of the [`lifeguard`] crate's `Pool`.
+//!
+//! `lifeguard` pools hold items implementing its `Recycleable` trait. Here,
+//! the equivalent is `Default` (to construct items) and [`Clear`] (to reset
+//! them).
+//!
+//! [`lifeguard`]: https://docs.rs/lifeguard
+//! [`Clear`]: ../../trait.Clear.html
+use crate::{growable, Clear};
+use std::{
+    fmt, mem,
+    ops::{Deref, DerefMut},
+};
+
+/// A pool with the API of `lifeguard::Pool`.
+///
+/// Unlike a `lifeguard` pool, this pool may be shared between threads. It
+/// grows whenever every item is checked out, and never shrinks.
+pub struct Pool<T> {
+    pool: growable::Pool<T>,
+}
+
+/// A checkout from an adapted [`Pool`], like `lifeguard::Recycled`.
+///
+/// [`Pool`]: struct.Pool.html
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct Recycled<'a, T> {
+    checkout: growable::Owned<T>,
+    pool: &'a Pool<T>,
+}
+
+// === impl Pool ===
+
+impl<T> Pool<T>
+where
+    T: Default + Clear,
+{
+    /// Returns a pool of `size` items.
+    pub fn with_size(size: usize) -> Self {
+        Self {
+            pool: growable::Pool::with_capacity(size),
+        }
+    }
+
+    /// Checks out an item, growing the pool if every item is in use.
+    #[allow(clippy::new_ret_no_self, clippy::should_implement_trait)]
+    pub fn new(&self) -> Recycled<'_, T> {
+        Recycled {
+            checkout: self.pool.checkout(),
+            pool: self,
+        }
+    }
+
+    /// Checks out an item, and replaces it with `value`.
+    ///
+    /// `lifeguard` adds `value` to the pool when the checkout is dropped.
+    /// Here, the checked out item is dropped instead, so that `value` takes
+    /// its slot and the pool keeps its size.
+    pub fn attach(&self, value: T) -> Recycled<'_, T> {
+        let mut checkout = self.new();
+        drop(mem::replace(&mut *checkout, value));
+        checkout
+    }
+
+    /// Takes an item out of the pool, which is not returned to it when
+    /// dropped.
+    ///
+    /// The item keeps whatever capacity it had in the pool, and its slot is
+    /// given a new, default item.
+    pub fn detached(&self) -> T {
+        self.new().detach()
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns the number of items that are not checked out.
+    pub fn size(&self) -> usize {
+        self.pool.available()
+    }
+
+    /// Returns the number of items in the pool, including those that are
+    /// checked out.
+    pub fn max_size(&self) -> usize {
+        self.pool.capacity()
+    }
+
+    /// Returns the underlying growable pool.
+    pub fn into_inner(self) -> growable::Pool<T> {
+        self.pool
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("capacity", &self.pool.capacity())
+            .field("used", &self.pool.used())
+            .finish()
+    }
+}
+
+// === impl Recycled ===
+
+impl<'a, T> Recycled<'a, T>
+where
+    T: Default,
+{
+    /// Takes the item out of the pool.
+    ///
+    /// The item's slot is given a new, default item, so the pool keeps its
+    /// size.
+    pub fn detach(mut self) -> T {
+        self.checkout.detach()
+    }
+}
+
+impl<'a, T> Recycled<'a, T> {
+    /// Returns the pool this item was checked out of.
+    pub fn pool(&self) -> &'a Pool<T> {
+        self.pool
+    }
+}
+
+impl<T> Deref for Recycled<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.checkout
+    }
+}
+
+impl<T> DerefMut for Recycled<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.checkout
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Recycled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
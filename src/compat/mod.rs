@@ -0,0 +1,17 @@
+//! Adapters that mimic the APIs of other object pool crates.
+//!
+//! These are meant to ease migrating to natatorium: code written against one
+//! of these crates can switch to the matching adapter by changing its
+//! imports, and then move to natatorium's own APIs one call site at a time.
+//! Each adapter wraps a [growable pool], which [`into_inner`] returns.
+//!
+//! Unlike the crates they imitate, the adapters are built on natatorium's
+//! pools, so items are [cleared] when they are checked out, and must
+//! implement [`Clear`].
+//!
+//! [growable pool]: ../growable/struct.Pool.html
+//! [`into_inner`]: object_pool/struct.Pool.html#method.into_inner
+//! [cleared]: ../trait.Clear.html
+//! [`Clear`]: ../trait.Clear.html
+pub mod lifeguard;
+pub mod object_pool;
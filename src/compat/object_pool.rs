@@ -0,0 +1,136 @@
+//! An adapter with the API of the [`object-pool`] crate's `Pool`.
+//!
+//! [`object-pool`]: https://docs.rs/object-pool
+use crate::{growable, Clear};
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+/// The constructor an adapted pool replaces detached items with.
+type Init<T> = Box<dyn FnMut() -> T + Send + Sync>;
+
+/// A pool with the API of `object_pool::Pool`.
+pub struct Pool<T> {
+    pool: growable::Pool<T, Init<T>>,
+}
+
+/// A checkout from an adapted [`Pool`], like `object_pool::Reusable`.
+///
+/// [`Pool`]: struct.Pool.html
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct Reusable<'a, T> {
+    pool: &'a Pool<T>,
+    checkout: growable::Owned<T, Init<T>>,
+}
+
+// === impl Pool ===
+
+impl<T> Pool<T>
+where
+    T: Clear + 'static,
+{
+    /// Returns a pool of `cap` items constructed by `init`.
+    ///
+    /// `init` is also used to replace items that are [detached] from the
+    /// pool.
+    ///
+    /// [detached]: struct.Reusable.html#method.detach
+    pub fn new<F>(cap: usize, init: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Self {
+            pool: growable::Pool::with_capacity_and_fn(cap, Box::new(init)),
+        }
+    }
+
+    /// Checks out an item, or returns `None` if every item is in use.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn try_pull(&self) -> Option<Reusable<'_, T>> {
+        let checkout = self.pool.try_checkout()?;
+        Some(Reusable {
+            pool: self,
+            checkout,
+        })
+    }
+
+    /// Checks out an item, adding one constructed by `fallback` to the pool
+    /// if every item is in use.
+    pub fn pull(&self, fallback: impl Fn() -> T) -> Reusable<'_, T> {
+        loop {
+            if let Some(checkout) = self.try_pull() {
+                return checkout;
+            }
+            // Another thread may check out the new item first, in which case
+            // this adds another.
+            self.attach(fallback());
+        }
+    }
+
+    /// Adds `item` to the pool.
+    pub fn attach(&self, item: T) {
+        self.pool.attach(item)
+    }
+}
+
+impl<T> Pool<T> {
+    /// Returns the number of items that are not checked out.
+    pub fn len(&self) -> usize {
+        self.pool.available()
+    }
+
+    /// Returns `true` if every item is checked out.
+    pub fn is_empty(&self) -> bool {
+        self.pool.is_exhausted()
+    }
+
+    /// Returns the underlying growable pool.
+    pub fn into_inner(self) -> growable::Pool<T, Init<T>> {
+        self.pool
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("capacity", &self.pool.capacity())
+            .field("used", &self.pool.used())
+            .finish()
+    }
+}
+
+// === impl Reusable ===
+
+impl<'a, T> Reusable<'a, T> {
+    /// Takes the item out of the pool, returning the pool and the item.
+    ///
+    /// The item's slot is given a new item, constructed by the pool's `init`
+    /// function, so the pool keeps its size.
+    pub fn detach(mut self) -> (&'a Pool<T>, T) {
+        let item = self.checkout.detach();
+        (self.pool, item)
+    }
+}
+
+impl<T> Deref for Reusable<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.checkout
+    }
+}
+
+impl<T> DerefMut for Reusable<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.checkout
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Reusable<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
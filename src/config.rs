@@ -0,0 +1,141 @@
+//! Declarative pool settings, which may be loaded from a configuration file.
+use crate::Reuse;
+
+/// A pool's settings, as plain data.
+///
+/// A `Config` holds the subset of a [`Builder`]'s settings that can be
+/// written down in a configuration file. With the `serde` feature enabled, it
+/// implements `Serialize` and `Deserialize`, so that services can tune their
+/// pools without code changes. Every field is optional: settings that are
+/// left unset keep the builder's defaults (or whatever the builder was
+/// already configured with).
+///
+/// Apply a `Config` with [`Builder::with_config`]. New settings may be added
+/// to `Config` in the future, so one can't be built with a struct
+/// expression outside this crate; start from [`Config::new`] (or a
+/// deserialized `Config`) and use its `with_` methods instead.
+///
+/// [`Config::new`]: #method.new
+/// [`Builder`]: struct.Builder.html
+/// [`Builder::with_config`]: struct.Builder.html#method.with_config
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+#[non_exhaustive]
+pub struct Config {
+    /// A name for the pool.
+    ///
+    /// The builder doesn't use this itself; pass it to the pool's `register`
+    /// method to include the pool in [`dump_all`]'s diagnostics.
+    ///
+    /// [`dump_all`]: fn.dump_all.html
+    pub name: Option<String>,
+    /// The number of items the pool is built with.
+    pub capacity: Option<usize>,
+    /// How a growable pool grows when it runs out of items. Fixed pools
+    /// ignore this.
+    pub growth: Option<GrowthConfig>,
+    /// Low and high watermarks on the number of checked out items.
+    pub watermarks: Option<WatermarksConfig>,
+    /// The order in which the pool reuses its free items.
+    pub reuse: Option<Reuse>,
+}
+
+/// How a growable pool grows, in a [`Config`].
+///
+/// These are the variants of [`growable::Growth`] that can be written down as
+/// data.
+///
+/// [`Config`]: struct.Config.html
+/// [`growable::Growth`]: growable/enum.Growth.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum GrowthConfig {
+    /// Double the size of the pool.
+    Double,
+    /// Grow the pool by half its current size.
+    Half,
+    /// Grow the pool by a fixed number of slots.
+    Fixed(usize),
+}
+
+/// Watermarks on the number of checked out items, in a [`Config`].
+///
+/// See [`Builder::with_watermarks`].
+///
+/// [`Config`]: struct.Config.html
+/// [`Builder::with_watermarks`]: struct.Builder.html#method.with_watermarks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct WatermarksConfig {
+    pub low: usize,
+    pub high: usize,
+}
+
+// === impl Config ===
+
+impl Config {
+    /// Returns a config that leaves every setting unset.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_name(self, name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            ..self
+        }
+    }
+
+    pub fn with_capacity(self, capacity: usize) -> Self {
+        Self {
+            capacity: Some(capacity),
+            ..self
+        }
+    }
+
+    pub fn with_growth(self, growth: GrowthConfig) -> Self {
+        Self {
+            growth: Some(growth),
+            ..self
+        }
+    }
+
+    pub fn with_watermarks(self, low: usize, high: usize) -> Self {
+        Self {
+            watermarks: Some(WatermarksConfig::new(low, high)),
+            ..self
+        }
+    }
+
+    pub fn with_reuse(self, reuse: Reuse) -> Self {
+        Self {
+            reuse: Some(reuse),
+            ..self
+        }
+    }
+}
+
+// === impl WatermarksConfig ===
+
+impl WatermarksConfig {
+    pub fn new(low: usize, high: usize) -> Self {
+        Self { low, high }
+    }
+}
+
+// === impl GrowthConfig ===
+
+#[cfg(feature = "growable")]
+impl From<GrowthConfig> for crate::growable::Growth {
+    fn from(growth: GrowthConfig) -> Self {
+        match growth {
+            GrowthConfig::Double => Self::Double,
+            GrowthConfig::Half => Self::Half,
+            GrowthConfig::Fixed(amount) => Self::Fixed(amount),
+        }
+    }
+}
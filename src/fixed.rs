@@ -1,44 +1,363 @@
 use crate::{
-    builder::{settings, Builder},
+    backoff::{self, Backoff},
+    builder::{settings, Builder, Ctors},
+    latency::Timer,
     slab::{self, Slab},
     sync::{atomic, Arc},
+    trace,
     traits::Clear,
+    traits::{AnyClear, AnyItem, Split},
+    wakers, AuditReport, Key, Pressure, RetryHistogram, Stats, StatsCursor, VerifyReport,
 };
 
 use std::{
+    borrow::{Borrow, BorrowMut},
+    cmp, error, fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    io,
+    marker::PhantomData,
     mem,
     ops::{Deref, DerefMut},
-    ptr,
+    pin::Pin,
+    ptr, slice,
+    sync::mpsc,
+    task::{Context, Poll},
+    thread,
+    time::{Duration, Instant},
 };
 
-#[derive(Debug, Clone)]
-pub struct Pool<T> {
+/// A pool with a fixed number of slots.
+///
+/// If `CAP` is not [`DYNAMIC`], the pool's capacity is part of its type, and
+/// was set using [`Builder::with_const_elements`].
+///
+/// Checkouts keep the pool's storage alive, like clones of the pool do. If
+/// every clone of a pool is dropped while items are still checked out, the
+/// checkouts remain valid, and the storage (and every item in it) is dropped
+/// once the last of them is released. See [`strong_count`].
+///
+/// [`DYNAMIC`]: constant.DYNAMIC.html
+/// [`Builder::with_const_elements`]: ../struct.Builder.html#method.with_const_elements
+/// [`strong_count`]: #method.strong_count
+pub struct Pool<T, const CAP: usize = DYNAMIC> {
     slab: Arc<Slab<T>>,
+    template: Option<Template<T>>,
+    ctors: Ctors<T>,
 }
 
-#[derive(Debug)]
+/// The `CAP` parameter of a [`Pool`] whose capacity is chosen at runtime.
+///
+/// [`Pool`]: struct.Pool.html
+pub const DYNAMIC: usize = 0;
+
+#[must_use = "dropping a checkout immediately returns it to the pool"]
 pub struct Owned<T> {
     slot: ptr::NonNull<slab::Slot<T>>,
     slab: Arc<Slab<T>>,
+    generation: usize,
+    trace: trace::Checkout,
 }
 
+/// A shared, atomically reference-counted checkout of an item in a fixed
+/// pool.
+///
+/// Reading the item through a `Shared` checkout (with `Deref` or
+/// [`get_ref`]) is wait-free, as it only follows a pointer. Cloning a
+/// checkout is also wait-free, as it increments the item's reference count.
+/// Dropping the last reference to an item releases it to the pool, which is
+/// lock-free: it may retry if other threads are concurrently releasing items.
+/// If tasks are waiting for an item in [`Pool::checkout_async`], though, the
+/// release takes the lock on the pool's queue of waiters to wake one of them.
+/// On hot read paths, borrow items from checkouts that are already held,
+/// rather than cloning them; [`iter_refs`] does this for a slice of
+/// checkouts.
+///
+/// [`get_ref`]: #method.get_ref
+/// [`iter_refs`]: #method.iter_refs
+/// [`Pool::checkout_async`]: struct.Pool.html#method.checkout_async
+#[must_use = "dropping a checkout immediately returns it to the pool"]
 pub struct Shared<T> {
     slot: ptr::NonNull<slab::Slot<T>>,
     slab: Arc<Slab<T>>,
+    generation: usize,
+}
+
+/// An iterator over the items of a slice of [`Shared`] checkouts, returned by
+/// [`Shared::iter_refs`].
+///
+/// Like [`Shared::get_ref`], this borrows each item, rather than cloning its
+/// checkout.
+///
+/// [`Shared`]: struct.Shared.html
+/// [`Shared::iter_refs`]: struct.Shared.html#method.iter_refs
+/// [`Shared::get_ref`]: struct.Shared.html#method.get_ref
+#[derive(Debug)]
+pub struct SharedRefIter<'a, T> {
+    shared: slice::Iter<'a, Shared<T>>,
+}
+
+/// A group of [`Owned`] checkouts from the same pool, released all at once.
+///
+/// When an `OwnedSet` is dropped, every checkout in it is returned to the pool
+/// together, with a single update to the pool's free list, rather than one
+/// update per checkout. This is useful when many checkouts with the same
+/// lifetime are held at once, such as the buffers for a vectored I/O
+/// operation.
+///
+/// [`Owned`]: struct.Owned.html
+#[derive(Debug)]
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct OwnedSet<T> {
+    slots: Vec<(ptr::NonNull<slab::Slot<T>>, usize)>,
+    slab: Option<Arc<Slab<T>>>,
+}
+
+/// Either an [`Owned`] or a [`Shared`] checkout.
+///
+/// Returned by [`Pool::try_checkout_owned_or_shared`].
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Shared`]: struct.Shared.html
+/// [`Pool::try_checkout_owned_or_shared`]: struct.Pool.html#method.try_checkout_owned_or_shared
+#[derive(Debug)]
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub enum OwnedOrShared<T> {
+    /// A free item, checked out for exclusive use.
+    Owned(Owned<T>),
+    /// A shared checkout of the pool's template.
+    Shared(Shared<T>),
+}
+
+/// Either a caller's scratch item or an [`Owned`] checkout from a pool.
+///
+/// Returned by [`Pool::checkout_or`].
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Pool::checkout_or`]: struct.Pool.html#method.checkout_or
+#[derive(Debug)]
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub enum CheckoutOr<'a, T> {
+    /// The caller's scratch item, which was free.
+    Scratch(&'a mut T),
+    /// An item checked out of the pool, because the scratch item was taken.
+    Owned(Owned<T>),
+}
+
+/// An item that was released by an [`Owned`] checkout, and handed to the
+/// pool's [`Recycler`] before being returned to the pool.
+///
+/// A `Recycled` item is still checked out, and it may be read and modified
+/// like an [`Owned`] checkout. Dropping it, or calling [`complete`], returns
+/// the item to the pool.
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Recycler`]: trait.Recycler.html
+/// [`complete`]: #method.complete
+pub struct Recycled<T> {
+    slot: ptr::NonNull<slab::Slot<T>>,
+    slab: Arc<Slab<T>>,
+    generation: usize,
+}
+
+/// Receives items released by a fixed pool's [`Owned`] checkouts, before
+/// they are returned to the pool.
+///
+/// A recycler is set with [`Builder::with_recycler`]. This is implemented
+/// for functions and closures that take a [`Recycled`] item, which are
+/// called by the thread releasing the item, and for the sending halves of
+/// `std::sync::mpsc` channels of [`Recycled`] items, so that another thread
+/// can finish recycling them. Other channels (such as asynchronous ones) can
+/// be used by sending to them from a closure.
+///
+/// Whatever the recycler is, it must eventually drop each item it's given,
+/// or the item is never returned to the pool.
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Builder::with_recycler`]: ../struct.Builder.html#method.with_recycler
+/// [`Recycled`]: struct.Recycled.html
+pub trait Recycler<T>: Send + Sync + 'static {
+    /// Recycles a released item.
+    fn recycle(&self, item: Recycled<T>);
+}
+
+/// A future that checks out an item from a fixed pool, waiting for one to be
+/// released if the pool is exhausted.
+///
+/// Returned by [`Pool::checkout_async`].
+///
+/// [`Pool::checkout_async`]: struct.Pool.html#method.checkout_async
+#[must_use = "futures do nothing unless polled"]
+pub struct Checkout<'a, T, const CAP: usize = DYNAMIC> {
+    pool: &'a Pool<T, CAP>,
+    /// The future's place in the queue of tasks waiting for an item, once it
+    /// has had to wait.
+    waiting: Option<usize>,
+}
+
+/// Checks items out of a pool for the duration of a call to [`Pool::scope`].
+///
+/// [`Pool::scope`]: struct.Pool.html#method.scope
+pub struct Scope<'scope, T> {
+    slab: &'scope Slab<T>,
+    /// Makes `'scope` invariant, so that checkouts can't escape the scope.
+    scope: PhantomData<&'scope mut &'scope ()>,
+}
+
+/// A uniquely owned checkout that may not outlive the [`Scope`] it was
+/// checked out in.
+///
+/// A `Scoped` checkout is like an [`Owned`] checkout, except that it borrows
+/// the pool rather than holding a reference count, so checking it out and
+/// releasing it never touches the pool's reference count.
+///
+/// [`Scope`]: struct.Scope.html
+/// [`Owned`]: struct.Owned.html
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct Scoped<'scope, T> {
+    slot: ptr::NonNull<slab::Slot<T>>,
+    slab: &'scope Slab<T>,
+    generation: usize,
+    scope: PhantomData<&'scope mut &'scope ()>,
+}
+
+/// An [`Owned`] checkout of an [`AnyItem`] that is known to hold a `U`.
+///
+/// Returned by [`Owned::downcast`]. The item stays checked out until the
+/// `OwnedDowncast` is dropped, or converted back with [`into_inner`].
+///
+/// [`Owned`]: struct.Owned.html
+/// [`AnyItem`]: ../traits/type.AnyItem.html
+/// [`Owned::downcast`]: struct.Owned.html#method.downcast
+/// [`into_inner`]: #method.into_inner
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct OwnedDowncast<U> {
+    owned: Owned<AnyItem>,
+    _type: PhantomData<fn() -> U>,
+}
+
+/// One of the two disjoint parts of an [`Owned`] checkout, returned by
+/// [`Owned::split`].
+///
+/// The item stays checked out until both parts have been dropped.
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Owned::split`]: struct.Owned.html#method.split
+#[must_use = "dropping both parts of a checkout returns it to the pool"]
+pub struct OwnedPart<T: Split> {
+    owned: Owned<T>,
+    part: ptr::NonNull<T::Part>,
+}
+
+/// An [`Owned`] checkout with a tag, returned by [`Owned::with_tag`].
+///
+/// When a `Tagged` checkout is dropped, its tag is passed to the function set
+/// with [`Builder::on_release`]. The tag is stored in the checkout, not the
+/// pool, so tagging a checkout doesn't touch any shared state.
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Owned::with_tag`]: struct.Owned.html#method.with_tag
+/// [`Builder::on_release`]: ../struct.Builder.html#method.on_release
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct Tagged<T> {
+    owned: Owned<T>,
+    tag: u64,
+}
+
+/// A read-only handle to a set of shared items, such as reference data.
+///
+/// A `ReadHandle` only hands out [`Shared`] checkouts of the items it was
+/// created with, so the items can never be modified through it, and checking
+/// one out never fails because a pool is exhausted. Cloning a handle is cheap,
+/// and clones share the same items.
+///
+/// Create one from a pool's [template] with [`Pool::read_handle`], or from
+/// any set of shared checkouts with [`ReadHandle::new`].
+///
+/// [`Shared`]: struct.Shared.html
+/// [template]: ../struct.Builder.html#method.with_template
+/// [`Pool::read_handle`]: struct.Pool.html#method.read_handle
+/// [`ReadHandle::new`]: #method.new
+pub struct ReadHandle<T> {
+    items: std::sync::Arc<[Shared<T>]>,
+}
+
+/// A pool's read-only template item, which lives in a slab of its own.
+///
+/// The template's slot is kept checked out for as long as any clone of the
+/// pool exists, so it is never cleared or handed out for exclusive use.
+struct Template<T> {
+    slab: Arc<Slab<T>>,
+    generation: usize,
 }
 
+/// A pool whose storage has been intentionally leaked.
+///
+/// Because a leaked pool's storage is never freed, [`Shared`] checkouts from it
+/// may be [promoted] to `&'static` references. This is intended for
+/// configuration-like objects that are checked out once at startup and read
+/// for the rest of the program's lifetime.
+///
+/// A `Leaked` pool dereferences to the underlying [`Pool`], so items may be
+/// checked out of it as usual.
+///
+/// [`Shared`]: struct.Shared.html
+/// [promoted]: #method.promote
+/// [`Pool`]: struct.Pool.html
 #[derive(Debug, Clone)]
+pub struct Leaked<T, const CAP: usize = DYNAMIC> {
+    pool: Pool<T, CAP>,
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct Settings {
     _p: (),
 }
 
-impl<T: Default> Default for Pool<T> {
+/// Settings for a fixed pool whose capacity is part of its type.
+#[derive(Debug, Clone, Default)]
+pub struct ConstSettings<const CAP: usize> {
+    _p: (),
+}
+
+impl<const CAP: usize> ConstSettings<CAP> {
+    /// Fails to compile if `CAP` is `DYNAMIC`, since a pool of that type
+    /// couldn't be told apart from one whose capacity is chosen at runtime.
+    pub(crate) const NOT_DYNAMIC: () = assert!(
+        CAP != DYNAMIC,
+        "a pool's const capacity may not be zero, which is `fixed::DYNAMIC`"
+    );
+}
+
+impl<T: Default, const CAP: usize> Default for Pool<T, CAP> {
+    /// Returns a pool of `CAP` default items, or of [`DEFAULT_CAPACITY`] items
+    /// if `CAP` is [`DYNAMIC`].
+    ///
+    /// [`DEFAULT_CAPACITY`]: #associatedconstant.DEFAULT_CAPACITY
+    /// [`DYNAMIC`]: constant.DYNAMIC.html
     fn default() -> Self {
-        Self::new()
+        let cap = if CAP == DYNAMIC {
+            Pool::<T>::DEFAULT_CAPACITY
+        } else {
+            CAP
+        };
+        let Pool {
+            slab,
+            template,
+            ctors,
+        } = Pool::<T>::with_capacity(cap);
+        Self {
+            slab,
+            template,
+            ctors,
+        }
     }
 }
 
 impl<T: Default> Pool<T> {
+    /// Returns a new pool of [`DEFAULT_CAPACITY`] default items.
+    ///
+    /// [`DEFAULT_CAPACITY`]: #associatedconstant.DEFAULT_CAPACITY
     pub fn new() -> Self {
         Builder::default().fixed().finish()
     }
@@ -49,21 +368,350 @@ impl<T: Default> Pool<T> {
 }
 
 impl<T> Pool<T> {
+    /// The number of items in a pool created by [`new`] or [`Default`], or by
+    /// a builder whose capacity was not set.
+    ///
+    /// [`new`]: #method.new
+    /// [`Default`]: #impl-Default
+    pub const DEFAULT_CAPACITY: usize = crate::builder::DEFAULT_CAPACITY;
+
     pub fn builder() -> Builder<Settings, T, ()> {
         Builder::new().fixed()
     }
 
-    pub fn size(&self) -> usize {
+    /// Returns a new pool with the default capacity, which constructs new
+    /// items by calling `new`.
+    pub fn with_fn(new: impl FnMut() -> T) -> Self {
+        Self::builder().with_fn(new).finish()
+    }
+
+    /// Returns a new pool with `cap` items, which are constructed by calling
+    /// `new`.
+    pub fn with_capacity_and_fn(cap: usize, new: impl FnMut() -> T) -> Self {
+        Self::builder().with_fn(new).with_elements(cap).finish()
+    }
+}
+
+impl<T, const CAP: usize> Pool<T, CAP> {
+    /// The capacity of the pool, if it is part of the pool's type, or
+    /// [`DYNAMIC`] otherwise.
+    ///
+    /// [`DYNAMIC`]: constant.DYNAMIC.html
+    pub const CAPACITY: usize = CAP;
+
+    /// Returns a number identifying this pool, which is unique within the
+    /// process.
+    ///
+    /// Clones of a pool share its id. Checkouts report the id of the pool
+    /// they came from with their `pool_id` methods, which may be used to
+    /// correlate them in logs.
+    pub fn id(&self) -> usize {
+        self.slab.id()
+    }
+
+    /// Returns the number of items the pool currently has room for, whether
+    /// or not they are checked out.
+    pub fn capacity(&self) -> usize {
         self.slab.size()
     }
 
+    /// Returns the number of items that are currently checked out.
     pub fn used(&self) -> usize {
         self.slab.used()
     }
 
-    pub fn remaining(&self) -> usize {
+    /// Returns the number of items that may be checked out before the pool is
+    /// exhausted.
+    pub fn available(&self) -> usize {
         self.slab.remaining()
     }
+
+    /// Returns `true` if every item in the pool is checked out.
+    pub fn is_exhausted(&self) -> bool {
+        self.available() == 0
+    }
+
+    #[deprecated(note = "renamed to `capacity`")]
+    pub fn size(&self) -> usize {
+        self.capacity()
+    }
+
+    #[deprecated(note = "renamed to `available`")]
+    pub fn remaining(&self) -> usize {
+        self.available()
+    }
+
+    /// Audits the pool, reporting where each of its slots is accounted for.
+    ///
+    /// This is intended for tests and diagnostics: it walks the pool's free
+    /// list, so it takes time proportional to the pool's size. See
+    /// [`AuditReport`] for details.
+    ///
+    /// [`AuditReport`]: ../struct.AuditReport.html
+    pub fn audit(&self) -> AuditReport {
+        self.slab.audit()
+    }
+
+    /// Returns the pool's most recent events, oldest first.
+    ///
+    /// Only a small, fixed number of events are retained. This is intended
+    /// for debugging, such as when a slot is unexpectedly lost or released
+    /// twice, and is only available when the `debug-history` feature is
+    /// enabled.
+    #[cfg(feature = "debug-history")]
+    pub fn recent_events(&self) -> Vec<crate::Event> {
+        self.slab.recent_events()
+    }
+
+    /// Returns the approximate number of checkouts currently blocked waiting
+    /// for an item to be released.
+    ///
+    /// Unlike the number of items in use, this shows how much demand is
+    /// queued up behind an exhausted pool.
+    pub fn waiters(&self) -> usize {
+        self.slab.waiters()
+    }
+
+    /// Returns a histogram of how many times blocking checkouts from this
+    /// pool have had to retry.
+    ///
+    /// See [`RetryHistogram`] for details.
+    ///
+    /// [`RetryHistogram`]: ../struct.RetryHistogram.html
+    pub fn retry_histogram(&self) -> RetryHistogram {
+        self.slab.retry_histogram()
+    }
+
+    /// Returns histograms of how long blocking checkouts from this pool have
+    /// taken.
+    ///
+    /// See [`LatencyStats`] for details.
+    ///
+    /// [`LatencyStats`]: ../struct.LatencyStats.html
+    #[cfg(feature = "timing-stats")]
+    pub fn latency_stats(&self) -> crate::LatencyStats {
+        self.slab.latency_stats()
+    }
+
+    /// Adds this pool to the process-wide registry under `name`, so that it
+    /// is included in [`dump_all`]'s diagnostics.
+    ///
+    /// The registry doesn't keep the pool alive: it is removed once every
+    /// clone of the pool has been dropped.
+    ///
+    /// [`dump_all`]: ../fn.dump_all.html
+    pub fn register(&self, name: impl Into<String>)
+    where
+        T: Send + Sync + 'static,
+    {
+        crate::registry::register(name.into(), Box::new(Arc::downgrade(&self.slab)));
+    }
+
+    /// Returns the number of clones of this pool and checkouts from it that
+    /// are keeping its storage alive.
+    ///
+    /// Once every clone of the pool has been dropped, this is the number of
+    /// checkouts that have yet to be released.
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.slab)
+    }
+
+    /// Returns the largest number of items that have been checked out of
+    /// this pool at once.
+    pub fn high_water(&self) -> usize {
+        self.slab.high_water()
+    }
+
+    /// Returns counts of this pool's activity since it was created.
+    ///
+    /// See [`Stats`] for details.
+    ///
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn stats(&self) -> Stats {
+        self.slab.stats()
+    }
+
+    /// Returns counts of this pool's activity since the last call with
+    /// `cursor`.
+    ///
+    /// This lets a scraper that periodically reports a pool's metrics get
+    /// the counts for each period without storing and diffing the previous
+    /// totals itself. Several scrapers may each keep their own cursor.
+    pub fn stats_delta(&self, cursor: &mut StatsCursor) -> Stats {
+        self.slab.stats_delta(cursor)
+    }
+
+    /// Returns the number of slots that have been poisoned because an
+    /// [`Owned`] checkout was dropped while panicking.
+    ///
+    /// Poisoned slots are skipped by `checkout` until they are [repaired].
+    ///
+    /// [repaired]: #method.repair
+    pub fn poisoned(&self) -> usize {
+        self.slab.poisoned()
+    }
+
+    /// Returns the indices of all currently poisoned slots.
+    pub fn poisoned_slots(&self) -> Vec<usize> {
+        self.slab.poisoned_slots()
+    }
+
+    /// Returns how close the pool is to running out of items, relative to the
+    /// watermarks set with [`Builder::with_watermarks`].
+    ///
+    /// [`Builder::with_watermarks`]: ../struct.Builder.html#method.with_watermarks
+    pub fn pressure(&self) -> Pressure {
+        self.slab.pressure()
+    }
+
+    /// Returns `true` if `item` belongs to this pool.
+    ///
+    /// This may be used to assert that a checkout (or a reference derived
+    /// from one) came from the expected pool, such as when several pools of
+    /// the same type are in use.
+    pub fn contains(&self, item: &T) -> bool {
+        self.slab.contains(item)
+    }
+
+    /// Returns a new [`Shared`] checkout of the item that `key` refers to.
+    ///
+    /// Returns `None` if the checkout the key was taken from has since been
+    /// released, or if it has not been downgraded to a shared checkout (so
+    /// that it is still exclusively owned).
+    ///
+    /// [`Shared`]: struct.Shared.html
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn get(&self, key: Key) -> Option<Shared<T>> {
+        match self.slab.get(key) {
+            Ok(generation) => Some(Shared {
+                slot: ptr::NonNull::from(self.slab.slot(key.index())),
+                slab: self.slab.clone(),
+                generation,
+            }),
+            Err(idle) => {
+                if idle {
+                    self.slab.idle().notify();
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the poisoned slot at index `idx` to the pool.
+    ///
+    /// The slot's item will be cleared as usual when it is next checked out.
+    /// Returns `false` if the slot is not poisoned.
+    pub fn repair(&self, idx: usize) -> bool {
+        self.slab.repair(idx)
+    }
+
+    /// Returns the number of slots that have been marked as lost because a
+    /// checkout outlived its [lease].
+    ///
+    /// [lease]: ../struct.Builder.html#method.with_lease
+    pub fn lost(&self) -> usize {
+        self.slab.lost()
+    }
+
+    /// Marks every checkout that has outlived its [lease] as lost.
+    ///
+    /// Lost slots are never reused, and dropping a checkout of a lost slot does
+    /// nothing. Returns the number of slots marked as lost by this call. If the
+    /// pool was not built with a lease, this always returns 0.
+    ///
+    /// When the `tracing` feature is enabled, each slot marked as lost is
+    /// recorded as an event, with its index and how long its lease had
+    /// expired.
+    ///
+    /// [lease]: ../struct.Builder.html#method.with_lease
+    pub fn reclaim_expired(&self) -> usize {
+        let reclaimed = self.slab.reclaim_expired();
+        if reclaimed > 0 {
+            self.slab.idle().notify();
+        }
+        reclaimed
+    }
+
+    /// Consumes the pool, returning all of its items.
+    ///
+    /// This succeeds only if this is the last handle to the pool and no items
+    /// are checked out; otherwise, the pool is returned unchanged. It is
+    /// intended for orderly teardown, when pooled resources (such as
+    /// connections) must be closed explicitly rather than dropped.
+    ///
+    /// Items in poisoned slots are returned as well, in whatever state they
+    /// were left in. Slots whose items were taken out with
+    /// [`Owned::take_and_release`], and which haven't been checked out since,
+    /// have no item to return.
+    ///
+    /// [`Owned::take_and_release`]: struct.Owned.html#method.take_and_release
+    pub fn try_into_items(self) -> Result<Vec<T>, Self> {
+        let Pool {
+            slab,
+            template,
+            ctors,
+        } = self;
+        match Arc::try_unwrap(slab) {
+            Ok(slab) => Ok(slab.into_items()),
+            Err(slab) => Err(Self {
+                slab,
+                template,
+                ctors,
+            }),
+        }
+    }
+
+    /// Returns a [`ReadHandle`] to the pool's [template], or `None` if the
+    /// pool has no template.
+    ///
+    /// [`ReadHandle`]: struct.ReadHandle.html
+    /// [template]: ../struct.Builder.html#method.with_template
+    pub fn read_handle(&self) -> Option<ReadHandle<T>> {
+        self.template
+            .as_ref()
+            .map(|template| ReadHandle::new(Some(template.checkout())))
+    }
+
+    /// Leaks the pool's storage, so that it will never be freed.
+    ///
+    /// See [`Leaked`] for details.
+    ///
+    /// [`Leaked`]: struct.Leaked.html
+    pub fn leak(self) -> Leaked<T, CAP> {
+        mem::forget(self.slab.clone());
+        Leaked { pool: self }
+    }
+
+    /// Blocks the current thread until every checkout from this pool has been
+    /// released.
+    ///
+    /// This is intended for graceful shutdown: once an application has stopped
+    /// checking out new items, `drain_when_idle` waits for all outstanding
+    /// [`Owned`] and [`Shared`] checkouts to be dropped. If other threads keep
+    /// checking out items, this may never return.
+    pub fn drain_when_idle(&self) {
+        self.slab.idle().wait_until(|| self.slab.is_idle())
+    }
+}
+
+impl<T, const CAP: usize> Clone for Pool<T, CAP> {
+    fn clone(&self) -> Self {
+        Self {
+            slab: self.slab.clone(),
+            template: self.template.clone(),
+            ctors: self.ctors.clone(),
+        }
+    }
+}
+
+impl<T, const CAP: usize> fmt::Debug for Pool<T, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("size", &self.capacity())
+            .field("used", &self.used())
+            .field("waiters", &self.waiters())
+            .finish()
+    }
 }
 
 impl<T, N> From<Builder<Settings, T, N>> for Pool<T>
@@ -75,6 +723,15 @@ where
     }
 }
 
+impl<T, N, const CAP: usize> From<Builder<ConstSettings<CAP>, T, N>> for Pool<T, CAP>
+where
+    N: FnMut() -> T,
+{
+    fn from(builder: Builder<ConstSettings<CAP>, T, N>) -> Self {
+        builder.finish()
+    }
+}
+
 impl<T, N> From<N> for Pool<T>
 where
     N: FnMut() -> T,
@@ -84,11 +741,67 @@ where
     }
 }
 
-impl<T> Pool<T>
+impl<T, const CAP: usize> Pool<T, CAP>
 where
     T: Clear,
 {
+    /// Checks out the item in the slot at index `idx`, if it's free.
+    ///
+    /// This is meant for tests that need to exercise a particular slot (such
+    /// as the first or last slot of a block). It works by checking out free
+    /// items until slot `idx` comes up, and then releasing the others in the
+    /// reverse order, so it takes time proportional to the number of free
+    /// items, and other threads' checkouts may find the pool exhausted while
+    /// it runs.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn checkout_nth(&self, idx: usize) -> Option<Owned<T>> {
+        let mut skipped = Vec::new();
+        let found = loop {
+            match self.try_checkout() {
+                Some(item) if item.key().index() == idx => break Some(item),
+                Some(item) => skipped.push(item),
+                None => break None,
+            }
+        };
+        // Put the skipped items back in the order they were found in.
+        while let Some(item) = skipped.pop() {
+            drop(item);
+        }
+        found
+    }
+
+    /// Checks out every free item once, checking that the pool and the
+    /// items' [`Clear`] implementation behave, and returns a report of what
+    /// was found.
+    ///
+    /// This is meant to be run once a large pool has been built (e.g. at
+    /// startup), to fail fast if it was misconfigured. The report is only
+    /// meaningful if no other thread uses the pool while this runs. See
+    /// [`VerifyReport`] for details.
+    ///
+    /// [`Clear`]: ../traits/trait.Clear.html
+    /// [`VerifyReport`]: ../struct.VerifyReport.html
+    pub fn verify(&self) -> VerifyReport {
+        let items = std::iter::from_fn(|| self.try_checkout())
+            .map(|item: Owned<T>| (item.key().index(), item));
+        VerifyReport::run(self.capacity(), items, || self.audit())
+    }
+
     /// Attempt to check out a pooled resource _without_ growing the slab.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    ///
+    /// let pool: Pool<String> = Pool::with_capacity(1);
+    /// let first = pool.try_checkout().expect("the pool has a free item");
+    /// assert!(pool.try_checkout().is_none());
+    ///
+    /// drop(first);
+    /// assert!(pool.try_checkout().is_some());
+    /// ```
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
     pub fn try_checkout(&self) -> Option<Owned<T>> {
         loop {
             match self.slab.try_checkout() {
@@ -96,6 +809,8 @@ where
                     let checkout = Owned {
                         slot,
                         slab: self.slab.clone(),
+                        generation: unsafe { slot.as_ref() }.generation(),
+                        trace: self.slab.trace_checkout(),
                     };
 
                     #[cfg(debug_assertions)]
@@ -106,25 +821,352 @@ where
                 Err(slab::Error::AtCapacity) => return None,
                 Err(slab::Error::ShouldRetry) => {}
             }
-            atomic::spin_loop_hint();
+            backoff::spin();
         }
     }
 
+    /// Attempts to check out an item without blocking or allocating.
+    ///
+    /// This is suitable for contexts that must never block, such as real-time
+    /// audio threads. Checking out an item is lock-free: it only retries if
+    /// another thread made progress on the same pool in the meantime. It never
+    /// acquires a lock, parks the thread, or allocates memory.
+    ///
+    /// The same is true of dropping the returned checkout, with two
+    /// exceptions. If other tasks or threads are waiting for an item in
+    /// [`checkout_async`], [`checkout_blocking`] or [`checkout_timeout`],
+    /// releasing an item briefly acquires the lock on the pool's queue of
+    /// waiters to wake the first of them. If another thread is blocked in
+    /// [`drain_when_idle`], the last release wakes it. Code that must never
+    /// acquire a lock should not share a pool with code that waits for
+    /// items.
+    ///
+    /// These guarantees cover the pool itself. The item's [`Clear`]
+    /// implementation (which runs when the item is checked out) and any
+    /// [pressure callback] must uphold them as well.
+    ///
+    /// For a fixed pool, this is the same as [`try_checkout`]; it exists so that
+    /// callers can depend on the contract explicitly.
+    ///
+    /// [`checkout_async`]: #method.checkout_async
+    /// [`checkout_blocking`]: #method.checkout_blocking
+    /// [`checkout_timeout`]: #method.checkout_timeout
+    /// [`drain_when_idle`]: #method.drain_when_idle
+    /// [`Clear`]: ../trait.Clear.html
+    /// [pressure callback]: ../struct.Builder.html#method.on_pressure_change
+    /// [`try_checkout`]: #method.try_checkout
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    #[inline]
+    pub fn try_checkout_nonblocking(&self) -> Option<Owned<T>> {
+        self.try_checkout()
+    }
+
+    /// Checks out an item, and initializes it with the constructor registered
+    /// as `tag` using [`Builder::with_ctor`].
+    ///
+    /// # Panics
+    ///
+    /// If no constructor named `tag` was registered.
+    ///
+    /// [`Builder::with_ctor`]: ../struct.Builder.html#method.with_ctor
+    pub fn checkout_with_ctor(&self, tag: &str) -> Owned<T> {
+        // Look up the constructor first, so that an unknown tag doesn't
+        // poison the slot.
+        let init = self.ctors.get(tag);
+        let mut checkout = self.checkout();
+        init(&mut checkout);
+        checkout
+    }
+
+    /// Checks out a free item for exclusive use if there is one, or else a
+    /// shared checkout of the pool's [template].
+    ///
+    /// This allows read-mostly users to degrade gracefully to the template
+    /// when the pool is exhausted. Returns `None` only if there are no free
+    /// items and the pool has no template.
+    ///
+    /// [template]: ../struct.Builder.html#method.with_template
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn try_checkout_owned_or_shared(&self) -> Option<OwnedOrShared<T>> {
+        if let Some(owned) = self.try_checkout() {
+            return Some(OwnedOrShared::Owned(owned));
+        }
+        self.template
+            .as_ref()
+            .map(|template| OwnedOrShared::Shared(template.checkout()))
+    }
+
+    /// Checks out the item in `scratch` if there is one, or else an item from
+    /// the pool.
+    ///
+    /// This lets code that keeps a scratch item of its own (such as a
+    /// per-task buffer) share its call sites with code that uses the pool: the
+    /// scratch item is cleared and used whenever the caller has one, and the
+    /// pool is only used otherwise. The scratch item is never returned to the
+    /// pool.
+    pub fn checkout_or<'a>(&self, scratch: &'a mut Option<T>) -> CheckoutOr<'a, T> {
+        match scratch {
+            Some(item) => {
+                slab::clear(item);
+                CheckoutOr::Scratch(item)
+            }
+            None => CheckoutOr::Owned(self.checkout()),
+        }
+    }
+
+    /// Calls `f` with a [`Scope`] that checks items out of this pool, and
+    /// returns its result.
+    ///
+    /// Checkouts made with the scope borrow it, so the compiler ensures that
+    /// every one of them is released before `scope` returns: none can be
+    /// returned from `f`, or stored anywhere that outlives it. This makes it
+    /// impossible to leak a checkout out of a processing phase by accident
+    /// (though one may still be leaked on purpose, with `mem::forget`).
+    ///
+    /// Since scoped checkouts borrow the pool, they are also slightly cheaper
+    /// than [`Owned`] checkouts, which each hold a reference count.
+    ///
+    /// [`Scope`]: struct.Scope.html
+    /// [`Owned`]: struct.Owned.html
+    pub fn scope<R>(&self, f: impl for<'scope> FnOnce(&Scope<'scope, T>) -> R) -> R {
+        f(&Scope {
+            slab: &self.slab,
+            scope: PhantomData,
+        })
+    }
+
+    /// Checks out an item, waiting for one to be released if the pool is
+    /// exhausted.
+    ///
+    /// The item is returned to the pool when the checkout is dropped, and
+    /// cleared before it's checked out again.
+    ///
+    /// While the pool is exhausted, this spins, and then repeatedly yields,
+    /// until an item is released. That's the fastest way to get an item that
+    /// is about to be released, but it keeps a core busy for as long as the
+    /// pool stays exhausted. Use [`checkout_blocking`] (or
+    /// [`checkout_timeout`]) to park the thread instead.
+    ///
+    /// # Fairness
+    ///
+    /// Waiting checkouts aren't queued, so there's no guarantee about which
+    /// waiting thread gets the next item that's released, or that a thread
+    /// gets an item within any particular time. What the pool does guarantee
+    /// is that a blocking checkout doesn't barge ahead of threads that are
+    /// already waiting: if any are, it yields to them before trying to check
+    /// out an item. So a thread that releases an item and immediately checks
+    /// one out again gives the waiting threads a chance to take it, rather
+    /// than starving them. [`try_checkout`] never waits, and doesn't yield.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    ///
+    /// let pool: Pool<String> = Pool::with_capacity(2);
+    /// let mut greeting = pool.checkout();
+    /// greeting.push_str("hello");
+    /// drop(greeting);
+    ///
+    /// // The released item was cleared, but kept its allocation.
+    /// let reused = pool.checkout();
+    /// assert_eq!(*reused, "");
+    /// assert!(reused.capacity() >= 5);
+    /// ```
+    ///
+    /// [`checkout_blocking`]: #method.checkout_blocking
+    /// [`checkout_timeout`]: #method.checkout_timeout
+    /// [`try_checkout`]: #method.try_checkout
     pub fn checkout(&self) -> Owned<T> {
+        let timer = Timer::start();
+        let mut backoff = Backoff::new();
+        let mut waiting = None;
+        self.slab.yield_to_waiters();
         loop {
             if let Some(checkout) = self.try_checkout() {
+                self.slab.record_checkout(backoff.retries(), &timer);
                 return checkout;
             }
 
-            // If the snapshot got stale, or our attempt to grow the slab
-            // failed, spin and retry.
-            atomic::spin_loop_hint();
+            // The pool is exhausted; back off until an item is released.
+            waiting.get_or_insert_with(|| self.slab.start_waiting());
+            backoff.wait();
+        }
+    }
+
+    /// Returns a future that checks out an item, waiting for one to be
+    /// released if the pool is exhausted.
+    ///
+    /// This is the asynchronous counterpart to [`checkout`]: rather than
+    /// spinning, a task that finds the pool exhausted is woken when an
+    /// [`Owned`] or [`Shared`] checkout is released. Tasks are woken in the
+    /// order in which they started waiting, one per released item, although
+    /// a task that isn't waiting (or a blocking [`checkout`]) may still take
+    /// an item before the task that was woken for it polls again. A woken
+    /// task that loses an item this way keeps its place at the front of the
+    /// queue.
+    ///
+    /// Dropping the future stops waiting. If it was already woken for an
+    /// item, the next waiting task is woken in its place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    ///
+    /// # let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+    /// # rt.block_on(async {
+    /// let pool: Pool<String> = Pool::with_capacity(1);
+    /// let mut first = pool.checkout_async().await;
+    /// first.push_str("hello");
+    ///
+    /// // The pool is exhausted, so this task waits until `first` is released.
+    /// let waiting = tokio::spawn({
+    ///     let pool = pool.clone();
+    ///     async move { pool.checkout_async().await.capacity() }
+    /// });
+    /// tokio::task::yield_now().await;
+    /// drop(first);
+    /// assert!(waiting.await.unwrap() >= 5);
+    /// # });
+    /// ```
+    ///
+    /// [`checkout`]: #method.checkout
+    /// [`Owned`]: struct.Owned.html
+    /// [`Shared`]: struct.Shared.html
+    pub fn checkout_async(&self) -> Checkout<'_, T, CAP> {
+        Checkout {
+            pool: self,
+            waiting: None,
+        }
+    }
+
+    /// Checks out an item, parking the thread until one is released if the
+    /// pool is exhausted.
+    ///
+    /// Unlike [`checkout`], which spins and yields while it waits, this
+    /// doesn't use any CPU while the pool stays exhausted, at the cost of a
+    /// slower wakeup once an item is released. Parked threads wait in the same
+    /// queue as [`checkout_async`]'s tasks, so they're woken in the order in
+    /// which they started waiting, one per released item.
+    ///
+    /// [`checkout`]: #method.checkout
+    /// [`checkout_async`]: #method.checkout_async
+    pub fn checkout_blocking(&self) -> Owned<T> {
+        self.checkout_parked(None)
+            .expect("a checkout without a deadline can't time out")
+    }
+
+    /// Checks out an item, parking the thread for up to `timeout` until one
+    /// is released if the pool is exhausted.
+    ///
+    /// Returns `None` if no item could be checked out before `timeout`
+    /// elapsed. Otherwise, this is like [`checkout_blocking`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    /// use std::time::Duration;
+    ///
+    /// let pool: Pool<String> = Pool::with_capacity(1);
+    /// let held = pool.checkout();
+    /// assert!(pool.checkout_timeout(Duration::from_millis(10)).is_none());
+    ///
+    /// drop(held);
+    /// assert!(pool.checkout_timeout(Duration::from_millis(10)).is_some());
+    /// ```
+    ///
+    /// [`checkout_blocking`]: #method.checkout_blocking
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn checkout_timeout(&self, timeout: Duration) -> Option<Owned<T>> {
+        // A deadline too far in the future to represent is never reached.
+        self.checkout_parked(Instant::now().checked_add(timeout))
+    }
+
+    fn checkout_parked(&self, deadline: Option<Instant>) -> Option<Owned<T>> {
+        let timer = Timer::start();
+        self.slab.yield_to_waiters();
+        if let Some(checkout) = self.try_checkout() {
+            self.slab.record_checkout(0, &timer);
+            return Some(checkout);
+        }
+
+        let _waiting = self.slab.start_waiting();
+        let waker = wakers::unpark_current();
+        let mut cx = Context::from_waker(&waker);
+        let mut checkout = self.checkout_async();
+        let mut parks = 0;
+        loop {
+            if let Poll::Ready(checkout) = Pin::new(&mut checkout).poll(&mut cx) {
+                self.slab.record_checkout(parks, &timer);
+                return Some(checkout);
+            }
+            // Wakeups may be spurious, so poll again whenever one happens.
+            match deadline {
+                None => thread::park(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return None;
+                    }
+                    thread::park_timeout(deadline - now);
+                }
+            }
+            parks += 1;
+        }
+    }
+
+    /// Checks out an item and immediately returns it to the pool.
+    ///
+    /// Checkouts are `#[must_use]`, since a checkout that is dropped as soon
+    /// as it's made doesn't reserve anything, which is usually a bug. This
+    /// is for the rare cases where that's intended, such as to wait until the
+    /// pool has a free item.
+    pub fn checkout_discard(&self) {
+        drop(self.checkout());
+    }
+
+    /// Moves up to `n` of `from`'s free items into this pool, returning the
+    /// number of items moved.
+    ///
+    /// Pools can't trade slots, so each item taken from `from` is exchanged
+    /// for one of this pool's free items, and neither pool's capacity
+    /// changes. This lets a stage of a pipeline that is temporarily idle lend
+    /// its warmed up items (such as buffers that have grown to their working
+    /// size) to a busy stage, and take them back later by stealing from it in
+    /// turn. No more items are moved than both pools have free.
+    pub fn steal_free<const FROM: usize>(&self, from: &Pool<T, FROM>, n: usize) -> usize {
+        if self.id() == from.id() {
+            return 0;
+        }
+        // Hold on to every checkout until the items have been exchanged, so
+        // that the same free items aren't checked out again.
+        let mut exchanged = Vec::new();
+        while exchanged.len() < n {
+            let theirs = match from.try_checkout() {
+                Some(theirs) => theirs,
+                None => break,
+            };
+            let ours = match self.try_checkout() {
+                Some(ours) => ours,
+                None => break,
+            };
+            exchanged.push((ours, theirs));
         }
+        for (ours, theirs) in &mut exchanged {
+            mem::swap::<T>(ours, theirs);
+        }
+        exchanged.len()
     }
 }
 
 // == impl Owned ===
 
+// Like a `Box`, an `Owned` checkout has exclusive access to its item.
+unsafe impl<T: Send> Send for Owned<T> {}
+unsafe impl<T: Sync> Sync for Owned<T> {}
+
 impl<T> Deref for Owned<T> {
     type Target = T;
 
@@ -153,103 +1195,1744 @@ impl<T> DerefMut for Owned<T> {
 impl<T> Drop for Owned<T> {
     fn drop(&mut self) {
         let slot = unsafe { self.slot.as_ref() };
-        slot.drop_ref(&self.slab);
+        self.trace.finish(self.slab.index_of(slot));
+        // If we're panicking, the item may have been left in an inconsistent
+        // state, so don't hand it out again.
+        let idle = if thread::panicking() {
+            unsafe { slot.poison(&self.slab, self.generation) }
+        } else if Slab::recycle(&self.slab, self.slot, self.generation) {
+            // The recycler returns the item to the pool once it's done.
+            return;
+        } else {
+            unsafe { slot.drop_ref(&self.slab, self.generation) }
+        };
+        if idle {
+            self.slab.idle().notify();
+        }
     }
 }
 
-impl<T> Owned<T> {
-    pub fn downgrade(self) -> Shared<T> {
-        // TODO: cloning the slot and slab will cause two ref-count bumps (one
-        // for the slot's ref count, and one for the Arc), but we can't move out
-        // of `self` since `Owned` implements `Drop`. This may not be a big deal
-        // but it would be nice to fix.
-        Shared::new(self.slot, self.slab.clone())
-    }
-
-    pub fn detach(&mut self) -> T
-    where
-        T: Default,
-    {
-        self.detach_with(T::default)
-    }
-
-    pub fn detach_with(&mut self, new: impl FnOnce() -> T) -> T {
-        unsafe { mem::replace(self.slot.as_mut().item_mut(), new()) }
+impl<T> AsRef<T> for Owned<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
     }
+}
 
-    /// Asserts that the invariants enforced by the pool are currently valid for
-    /// this `Owned` reference.
-    pub fn assert_valid(&self) {
-        let slot = unsafe { self.slot.as_ref() };
-        assert_eq!(
-            slot.ref_count(atomic::Ordering::SeqCst),
-            1,
-            "invariant violated: owned checkout must have exactly one reference"
-        );
-        slot.assert_valid();
-        self.slab.assert_valid();
+impl<T> AsMut<T> for Owned<T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self.deref_mut()
     }
 }
 
-// === impl Shared ===
-
-impl<T> Shared<T> {
-    fn new(slot: ptr::NonNull<slab::Slot<T>>, slab: Arc<Slab<T>>) -> Self {
-        unsafe {
-            slot.as_ref().clone_ref();
-        }
-        Self {
-            slot,
-            slab,
-        }
+impl<T> Borrow<T> for Owned<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.deref()
     }
+}
 
-    pub fn try_upgrade(self) -> Result<Owned<T>, Self> {
-        unimplemented!()
+impl<T> BorrowMut<T> for Owned<T> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut T {
+        self.deref_mut()
     }
 }
 
-impl<T> Clone for Shared<T> {
-    fn clone(&self) -> Self {
-        Self::new(self.slot, self.slab.clone())
+impl<T> PartialEq for Owned<T>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
     }
 }
 
-impl<T> Deref for Shared<T> {
-    type Target = T;
+impl<T> Eq for Owned<T> where T: Eq {}
 
+impl<T> PartialOrd for Owned<T>
+where
+    T: PartialOrd,
+{
     #[inline]
-    fn deref(&self) -> &Self::Target {
-        unsafe {
-            // A `Shared` checkout implies that the slot may not be mutated.
-            self.slot.as_ref().item()
-        }
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
     }
 }
 
-impl<T> Drop for Shared<T> {
-    fn drop(&mut self) {
-        let slot = unsafe { self.slot.as_ref() };
-        slot.drop_ref(&self.slab);
+impl<T> Ord for Owned<T>
+where
+    T: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.deref().cmp(other.deref())
     }
 }
 
-// === impl Settings ===
-
-impl Default for Settings {
-    fn default() -> Self {
-        Self { _p: () }
+impl<T> Hash for Owned<T>
+where
+    T: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
     }
 }
 
-impl<T, N> settings::Make<T, N> for Settings
+impl<T> fmt::Debug for Owned<T>
 where
-    N: FnMut() -> T,
+    T: fmt::Debug,
 {
-    type Pool = Pool<T>;
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T> fmt::Display for Owned<T>
+where
+    T: fmt::Display,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T> error::Error for Owned<T>
+where
+    T: error::Error,
+{
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.deref().source()
+    }
+}
+
+impl<T> io::Read for Owned<T>
+where
+    T: io::Read,
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.deref_mut().read(buf)
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.deref_mut().read_vectored(bufs)
+    }
+
+    #[inline]
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.deref_mut().read_to_end(buf)
+    }
+
+    #[inline]
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.deref_mut().read_to_string(buf)
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.deref_mut().read_exact(buf)
+    }
+}
+
+impl<T> io::Write for Owned<T>
+where
+    T: io::Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.deref_mut().write(buf)
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.deref_mut().write_vectored(bufs)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.deref_mut().flush()
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.deref_mut().write_all(buf)
+    }
+
+    #[inline]
+    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> io::Result<()> {
+        self.deref_mut().write_fmt(fmt)
+    }
+}
+
+impl<T> fmt::Write for Owned<T>
+where
+    T: fmt::Write,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.deref_mut().write_str(s)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.deref_mut().write_char(c)
+    }
+
+    #[inline]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        self.deref_mut().write_fmt(args)
+    }
+}
+
+impl<T> Owned<T> {
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.slab.id()
+    }
+
+    /// Tags the checkout with `tag`, such as the ID of the request it was
+    /// checked out for.
+    ///
+    /// The tag is passed to the function set with [`Builder::on_release`]
+    /// when the returned checkout is released.
+    ///
+    /// [`Builder::on_release`]: ../struct.Builder.html#method.on_release
+    pub fn with_tag(self, tag: u64) -> Tagged<T> {
+        Tagged { owned: self, tag }
+    }
+
+    /// Converts the checkout into a [`Shared`] checkout, which may be cloned,
+    /// and looked up by its [key].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    ///
+    /// let pool: Pool<String> = Pool::with_capacity(1);
+    /// let mut config = pool.checkout();
+    /// config.push_str("verbose = true");
+    ///
+    /// let config = config.downgrade();
+    /// let copy = config.clone();
+    /// let found = pool.get(config.key()).expect("shared items can be found");
+    /// assert_eq!(*found, "verbose = true");
+    /// assert_eq!(found.version(), copy.version());
+    /// ```
+    ///
+    /// [`Shared`]: struct.Shared.html
+    /// [key]: #method.key
+    pub fn downgrade(self) -> Shared<T> {
+        // TODO: cloning the slot and slab will cause two ref-count bumps (one
+        // for the slot's ref count, and one for the Arc), but we can't move out
+        // of `self` since `Owned` implements `Drop`. This may not be a big deal
+        // but it would be nice to fix.
+        Shared::new(self.slot, self.slab.clone(), self.generation)
+    }
+
+    /// Returns the version of this item.
+    ///
+    /// Every mutable checkout of an item has a different version, which is
+    /// kept by the [`Shared`] checkouts it's downgraded to. See
+    /// [`Shared::version`] for details.
+    ///
+    /// [`Shared`]: struct.Shared.html
+    /// [`Shared::version`]: struct.Shared.html#method.version
+    pub fn version(&self) -> usize {
+        slab::version(self.generation)
+    }
+
+    /// Returns a [`Key`] for this checkout.
+    ///
+    /// The key can only be used to look the item up with [`Pool::get`] once
+    /// this checkout has been [downgraded] to a [`Shared`] checkout.
+    ///
+    /// [`Key`]: ../struct.Key.html
+    /// [`Pool::get`]: struct.Pool.html#method.get
+    /// [downgraded]: #method.downgrade
+    /// [`Shared`]: struct.Shared.html
+    pub fn key(&self) -> Key {
+        slab::key(
+            self.slab.index_of(unsafe { self.slot.as_ref() }),
+            self.generation,
+        )
+    }
+
+    /// Consumes the checkout, returning a pointer to its item.
+    ///
+    /// Like [`Arc::into_raw`], this does not release the item: it stays
+    /// checked out, and the pool's storage is kept alive, until the pointer is
+    /// turned back into an `Owned` checkout with [`Owned::from_raw`] and that
+    /// checkout is dropped. This allows checkouts to be stored in intrusive
+    /// data structures, or behind an atomic pointer, without boxing them.
+    ///
+    /// [`Arc::into_raw`]: https://doc.rust-lang.org/std/sync/struct.Arc.html#method.into_raw
+    /// [`Owned::from_raw`]: #method.from_raw
+    pub fn into_raw(this: Self) -> *mut T {
+        // Don't drop the checkout, so that its slot stays checked out and its
+        // reference to the slab stays alive until `from_raw` takes them back.
+        let mut this = mem::ManuallyDrop::new(this);
+        unsafe { this.slot.as_mut().item_mut() }
+    }
+
+    /// Turns a pointer returned by [`Owned::into_raw`] back into a checkout.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Owned::into_raw` for a checkout from
+    /// `pool` (or a clone of it), and each such pointer may only be passed to
+    /// `from_raw` once. Until then, the item may be accessed through `ptr`,
+    /// but only by one thread at a time.
+    ///
+    /// # Panics
+    ///
+    /// If `ptr` does not point to one of `pool`'s items.
+    ///
+    /// [`Owned::into_raw`]: #method.into_raw
+    pub unsafe fn from_raw<const CAP: usize>(pool: &Pool<T, CAP>, ptr: *mut T) -> Self {
+        let slot = pool
+            .slab
+            .slot_of(ptr)
+            .expect("pointer does not point to an item in this pool");
+        let checkout = Self {
+            slot: ptr::NonNull::from(slot),
+            // Take back the reference to the slab that `into_raw` leaked.
+            slab: Arc::from_raw(Arc::as_ptr(&pool.slab)),
+            // The slot can't have been released since `into_raw`, so its
+            // generation is unchanged (unless it has been reclaimed, in which
+            // case the new checkout is lost as well).
+            generation: slot.generation(),
+            trace: pool.slab.trace_checkout(),
+        };
+
+        #[cfg(debug_assertions)]
+        checkout.assert_valid();
+
+        checkout
+    }
+
+    /// Takes the item out of the pool, replacing it with `T::default()`.
+    ///
+    /// The checkout holds on to the new item, which is returned to the pool
+    /// in place of the detached one. Detached items are counted in the pool's
+    /// [`Stats`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    ///
+    /// let pool: Pool<Vec<u8>> = Pool::with_capacity(1);
+    /// let mut buf = pool.checkout();
+    /// buf.extend_from_slice(b"response body");
+    ///
+    /// // The body outlives the checkout.
+    /// let body: Vec<u8> = buf.detach();
+    /// assert!(buf.is_empty());
+    /// drop(buf);
+    ///
+    /// assert_eq!(body, b"response body");
+    /// assert_eq!(pool.stats().detached, 1);
+    /// ```
+    ///
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn detach(&mut self) -> T
+    where
+        T: Default,
+    {
+        self.detach_with(T::default)
+    }
+
+    pub fn detach_with(&mut self, new: impl FnOnce() -> T) -> T {
+        self.slab.record_detach();
+        unsafe { mem::replace(self.slot.as_mut().item_mut(), new()) }
+    }
+
+    /// Takes the item out of the pool, and releases its slot.
+    ///
+    /// Unlike [`detach`], this doesn't construct a replacement item. The slot
+    /// is left empty, and is only refilled when it is next checked out, so a
+    /// pool whose items are mostly taken pays for each replacement once, and
+    /// only if it's used. Fixed pools don't keep the constructor they were
+    /// built with, so the slot is refilled with `T::default()`. This ends the
+    /// checkout, so the slot is immediately available again.
+    ///
+    /// Detached items are counted in the pool's [`Stats`], so that a pool
+    /// whose items are mostly detached (and which is therefore acting as an
+    /// allocator) can be spotted.
+    ///
+    /// [`detach`]: #method.detach
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn take_and_release(mut self) -> T
+    where
+        T: Default,
+    {
+        self.slab.record_detach();
+        self.slab.rebuild_with(T::default);
+        // This is the slot's only reference, and releasing an empty slot
+        // doesn't touch its item.
+        unsafe { self.slot.as_mut().take() }
+    }
+
+    /// Asserts that the invariants enforced by the pool are currently valid for
+    /// this `Owned` reference.
+    pub fn assert_valid(&self) {
+        let slot = unsafe { self.slot.as_ref() };
+        assert_eq!(
+            slot.ref_count(atomic::Ordering::SeqCst),
+            1,
+            "invariant violated: owned checkout must have exactly one reference"
+        );
+        slot.assert_valid(self.slab.index_of(slot));
+        self.slab.assert_valid();
+    }
+}
+
+impl<T: Split> Owned<T> {
+    /// Splits the checkout into two parts that can be used independently,
+    /// such as a buffer's header and body, which are processed by different
+    /// tasks.
+    ///
+    /// The first part is the item before `at`, and the second is the rest
+    /// (see [`Split::split_at_mut`]). The item stays checked out until both
+    /// parts have been dropped. The item can't be looked up by its key while
+    /// it is split, as it isn't shared.
+    ///
+    /// # Panics
+    ///
+    /// If the item can't be split at `at`.
+    ///
+    /// [`Split::split_at_mut`]: ../traits/trait.Split.html#tymethod.split_at_mut
+    pub fn split(mut self, at: usize) -> (OwnedPart<T>, OwnedPart<T>) {
+        let (head, tail) = self.deref_mut().split_at_mut(at);
+        let (head, tail) = (ptr::NonNull::from(head), ptr::NonNull::from(tail));
+        // The second part holds a reference of its own, so that the slot is
+        // released only once both have been dropped. The slot isn't marked as
+        // shared, so `Pool::get` still won't hand out references to it.
+        unsafe { self.slot.as_ref().clone_ref() };
+        let other = Owned {
+            slot: self.slot,
+            slab: self.slab.clone(),
+            generation: self.generation,
+            trace: self.slab.trace_checkout(),
+        };
+        (
+            OwnedPart {
+                owned: self,
+                part: head,
+            },
+            OwnedPart {
+                owned: other,
+                part: tail,
+            },
+        )
+    }
+}
+
+impl Owned<AnyItem> {
+    /// Returns a checkout of the item as a `U`, or this checkout if the item
+    /// is not a `U`.
+    ///
+    /// The item stays checked out either way.
+    pub fn downcast<U: AnyClear>(self) -> Result<OwnedDowncast<U>, Self> {
+        // `Box<dyn AnyClear>` is itself `AnyClear`, so be sure to ask the
+        // boxed item.
+        if (**self).as_any().is::<U>() {
+            Ok(OwnedDowncast {
+                owned: self,
+                _type: PhantomData,
+            })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+// === impl OwnedSet ===
+
+impl<T> OwnedSet<T> {
+    /// Returns a new, empty `OwnedSet`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Returns a new, empty `OwnedSet` with space for `cap` checkouts.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(cap),
+            slab: None,
+        }
+    }
+
+    /// Adds a checkout to the set.
+    ///
+    /// # Panics
+    ///
+    /// If `checkout` was not checked out of the same pool as the checkouts
+    /// already in the set.
+    pub fn push(&mut self, checkout: Owned<T>) {
+        if let Some(ref slab) = self.slab {
+            assert!(
+                Arc::ptr_eq(slab, &checkout.slab),
+                "checkouts in an `OwnedSet` must all come from the same pool (expected pool {}, got pool {})",
+                slab.id(),
+                checkout.pool_id(),
+            );
+        }
+
+        // Take ownership of the checkout's reference without releasing it.
+        let checkout = mem::ManuallyDrop::new(checkout);
+        let slab = unsafe { ptr::read(&checkout.slab) };
+        if self.slab.is_none() {
+            self.slab = Some(slab);
+        }
+        self.slots.push((checkout.slot, checkout.generation));
+    }
+
+    /// Returns the number of checkouts in the set.
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns `true` if the set contains no checkouts.
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+
+    /// Returns an iterator over the checked out items.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.slots
+            .iter()
+            .map(|(slot, _)| unsafe { slot.as_ref().item() })
+    }
+
+    /// Returns an iterator that allows modifying each checked out item.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.slots.iter_mut().map(|(slot, _)| unsafe {
+            // Like an `Owned` checkout, the set has unique access to each of
+            // its slots.
+            slot.as_mut().item_mut()
+        })
+    }
+}
+
+impl<T> Default for OwnedSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for OwnedSet<T> {
+    fn drop(&mut self) {
+        let slab = match self.slab.take() {
+            Some(slab) => slab,
+            None => return,
+        };
+        let slots = self
+            .slots
+            .iter()
+            .map(|&(slot, generation)| (unsafe { slot.as_ref() }, generation));
+        let idle = if thread::panicking() {
+            // As with `Owned`, don't hand out items that may have been left in
+            // an inconsistent state.
+            slots.fold(false, |idle, (slot, generation)| unsafe {
+                slot.poison(&slab, generation) || idle
+            })
+        } else {
+            slab.release_all(slots)
+        };
+        if idle {
+            slab.idle().notify();
+        }
+    }
+}
+
+// === impl OwnedOrShared ===
+
+impl<T> OwnedOrShared<T> {
+    /// Returns `true` if this is a shared checkout of the pool's template.
+    pub fn is_shared(&self) -> bool {
+        matches!(self, OwnedOrShared::Shared(_))
+    }
+
+    /// Returns the exclusive checkout, if this is one.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn into_owned(self) -> Option<Owned<T>> {
+        match self {
+            OwnedOrShared::Owned(owned) => Some(owned),
+            OwnedOrShared::Shared(_) => None,
+        }
+    }
+}
+
+impl<T> Deref for OwnedOrShared<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            OwnedOrShared::Owned(owned) => owned,
+            OwnedOrShared::Shared(shared) => shared,
+        }
+    }
+}
+
+impl<T> AsRef<T> for OwnedOrShared<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<T> fmt::Display for OwnedOrShared<T>
+where
+    T: fmt::Display,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T> error::Error for OwnedOrShared<T>
+where
+    T: error::Error,
+{
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.deref().source()
+    }
+}
+
+// === impl CheckoutOr ===
+
+impl<'a, T> Deref for CheckoutOr<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            CheckoutOr::Scratch(item) => item,
+            CheckoutOr::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<'a, T> DerefMut for CheckoutOr<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            CheckoutOr::Scratch(item) => item,
+            CheckoutOr::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<'a, T> AsRef<T> for CheckoutOr<'a, T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<'a, T> AsMut<T> for CheckoutOr<'a, T> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self.deref_mut()
+    }
+}
+
+// === impl Recycled ===
+
+// Like an `Owned` checkout, a `Recycled` item has exclusive access to its item.
+unsafe impl<T: Send> Send for Recycled<T> {}
+unsafe impl<T: Sync> Sync for Recycled<T> {}
+
+impl<T> Recycled<T> {
+    pub(crate) fn new(
+        slab: Arc<Slab<T>>,
+        slot: ptr::NonNull<slab::Slot<T>>,
+        generation: usize,
+    ) -> Self {
+        Self {
+            slot,
+            slab,
+            generation,
+        }
+    }
+
+    /// Returns the item to the pool.
+    ///
+    /// This is the same as dropping it, but makes the end of recycling
+    /// explicit.
+    pub fn complete(self) {
+        drop(self);
+    }
+
+    /// Returns the key that the item was checked out with.
+    pub fn key(&self) -> Key {
+        slab::key(
+            self.slab.index_of(unsafe { self.slot.as_ref() }),
+            self.generation,
+        )
+    }
+
+    /// Returns the [ID] of the pool that the item belongs to.
+    ///
+    /// [ID]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.slab.id()
+    }
+}
+
+impl<T> Deref for Recycled<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.slot.as_ref().item() }
+    }
+}
+
+impl<T> DerefMut for Recycled<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.slot.as_mut().item_mut() }
+    }
+}
+
+impl<T> Drop for Recycled<T> {
+    fn drop(&mut self) {
+        let slot = unsafe { self.slot.as_ref() };
+        // If the recycler panicked, don't hand the item out again.
+        let idle = if thread::panicking() {
+            unsafe { slot.poison(&self.slab, self.generation) }
+        } else {
+            unsafe { slot.drop_ref(&self.slab, self.generation) }
+        };
+        if idle {
+            self.slab.idle().notify();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Recycled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Recycled")
+            .field("item", self.deref())
+            .field("key", &self.key())
+            .finish()
+    }
+}
+
+// === impl Recycler ===
+
+impl<T, F> Recycler<T> for F
+where
+    F: Fn(Recycled<T>) + Send + Sync + 'static,
+{
+    fn recycle(&self, item: Recycled<T>) {
+        self(item)
+    }
+}
+
+/// Sends each item to the channel's receiver. If the receiver was dropped,
+/// the item is returned to the pool straight away.
+impl<T: Send + 'static> Recycler<T> for mpsc::Sender<Recycled<T>> {
+    fn recycle(&self, item: Recycled<T>) {
+        let _ = self.send(item);
+    }
+}
+
+/// Sends each item to the channel's receiver, waiting for room in the
+/// channel if it's full. If the receiver was dropped, the item is returned
+/// to the pool straight away.
+impl<T: Send + 'static> Recycler<T> for mpsc::SyncSender<Recycled<T>> {
+    fn recycle(&self, item: Recycled<T>) {
+        let _ = self.send(item);
+    }
+}
+
+// === impl Checkout ===
+
+impl<T, const CAP: usize> Checkout<'_, T, CAP>
+where
+    T: Clear,
+{
+    fn try_checkout(&mut self) -> Option<Owned<T>> {
+        let checkout = self.pool.try_checkout()?;
+        if let Some(id) = self.waiting.take() {
+            self.pool.slab.wakers().remove(id);
+        }
+        Some(checkout)
+    }
+}
+
+impl<T, const CAP: usize> Future for Checkout<'_, T, CAP>
+where
+    T: Clear,
+{
+    type Output = Owned<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        if let Some(checkout) = this.try_checkout() {
+            return Poll::Ready(checkout);
+        }
+        this.pool
+            .slab
+            .wakers()
+            .register(&mut this.waiting, cx.waker());
+        // An item may have been released before the waker was registered.
+        match this.try_checkout() {
+            Some(checkout) => Poll::Ready(checkout),
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T, const CAP: usize> Drop for Checkout<'_, T, CAP> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiting {
+            self.pool.slab.wakers().cancel(id);
+        }
+    }
+}
+
+impl<T, const CAP: usize> fmt::Debug for Checkout<'_, T, CAP> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Checkout")
+            .field("pool", &self.pool.id())
+            .field("waiting", &self.waiting.is_some())
+            .finish()
+    }
+}
+
+// === impl Scope ===
+
+impl<'scope, T> Scope<'scope, T>
+where
+    T: Clear,
+{
+    /// Attempts to check out an item without blocking, returning `None` if
+    /// the pool is exhausted.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn try_checkout(&self) -> Option<Scoped<'scope, T>> {
+        loop {
+            match self.slab.try_checkout() {
+                Ok(slot) => {
+                    return Some(Scoped {
+                        slot,
+                        slab: self.slab,
+                        generation: unsafe { slot.as_ref() }.generation(),
+                        scope: PhantomData,
+                    })
+                }
+                Err(slab::Error::AtCapacity) => return None,
+                Err(slab::Error::ShouldRetry) => backoff::spin(),
+            }
+        }
+    }
+
+    /// Checks out an item, blocking until one is released if the pool is
+    /// exhausted.
+    pub fn checkout(&self) -> Scoped<'scope, T> {
+        let timer = Timer::start();
+        let mut backoff = Backoff::new();
+        let mut waiting = None;
+        self.slab.yield_to_waiters();
+        loop {
+            if let Some(checkout) = self.try_checkout() {
+                self.slab.record_checkout(backoff.retries(), &timer);
+                return checkout;
+            }
+
+            // The pool is exhausted; back off until an item is released.
+            waiting.get_or_insert_with(|| self.slab.start_waiting());
+            backoff.wait();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Scope<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("pool", &self.slab.id())
+            .finish()
+    }
+}
+
+// === impl Scoped ===
+
+// Like an `Owned` checkout, a `Scoped` checkout has exclusive access to its
+// item.
+unsafe impl<T: Send> Send for Scoped<'_, T> {}
+unsafe impl<T: Sync> Sync for Scoped<'_, T> {}
+
+impl<T> Deref for Scoped<'_, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { self.slot.as_ref().item() }
+    }
+}
+
+impl<T> DerefMut for Scoped<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.slot.as_mut().item_mut() }
+    }
+}
+
+impl<T> Drop for Scoped<'_, T> {
+    fn drop(&mut self) {
+        let slot = unsafe { self.slot.as_ref() };
+        // As with `Owned`, don't hand out an item that may have been left in
+        // an inconsistent state.
+        let idle = if thread::panicking() {
+            unsafe { slot.poison(self.slab, self.generation) }
+        } else {
+            unsafe { slot.drop_ref(self.slab, self.generation) }
+        };
+        if idle {
+            self.slab.idle().notify();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Scoped<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// === impl OwnedDowncast ===
+
+impl<U: AnyClear> OwnedDowncast<U> {
+    /// Converts this back into a checkout of the type-erased item.
+    pub fn into_inner(self) -> Owned<AnyItem> {
+        self.owned
+    }
+}
+
+impl<U: AnyClear> Deref for OwnedDowncast<U> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        (**self.owned)
+            .as_any()
+            .downcast_ref()
+            .expect("item was checked to be a `U` when it was downcast")
+    }
+}
+
+impl<U: AnyClear> DerefMut for OwnedDowncast<U> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        (**self.owned)
+            .as_any_mut()
+            .downcast_mut()
+            .expect("item was checked to be a `U` when it was downcast")
+    }
+}
+
+impl<U: AnyClear + fmt::Debug> fmt::Debug for OwnedDowncast<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+// === impl Tagged ===
+
+impl<T> Tagged<T> {
+    /// Returns the checkout's tag.
+    pub fn tag(&self) -> u64 {
+        self.tag
+    }
+
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.owned.pool_id()
+    }
+
+    /// Removes the checkout's tag, so that releasing it isn't reported.
+    pub fn into_owned(self) -> Owned<T> {
+        let this = mem::ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.owned) }
+    }
+}
+
+impl<T> Deref for Tagged<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.owned.deref()
+    }
+}
+
+impl<T> DerefMut for Tagged<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.owned.deref_mut()
+    }
+}
+
+impl<T> Drop for Tagged<T> {
+    fn drop(&mut self) {
+        self.owned.slab.notify_release(self.tag);
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Tagged<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tagged")
+            .field("item", self.deref())
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+// === impl OwnedPart ===
+
+// Each part has exclusive access to its half of the item. Dropping a part may
+// drop the pool's storage, and the items in it.
+unsafe impl<T: Split + Send> Send for OwnedPart<T> where T::Part: Send {}
+unsafe impl<T: Split + Sync> Sync for OwnedPart<T> where T::Part: Sync {}
+
+impl<T: Split> OwnedPart<T> {
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.owned.pool_id()
+    }
+}
+
+impl<T: Split> Deref for OwnedPart<T> {
+    type Target = T::Part;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            // The parts of a split item are disjoint, and the item can't be
+            // accessed any other way until both parts are dropped.
+            self.part.as_ref()
+        }
+    }
+}
+
+impl<T: Split> DerefMut for OwnedPart<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.part.as_mut() }
+    }
+}
+
+impl<T: Split> fmt::Debug for OwnedPart<T>
+where
+    T::Part: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+// === impl ReadHandle ===
+
+impl<T> ReadHandle<T> {
+    /// Returns a handle to `items`.
+    pub fn new(items: impl IntoIterator<Item = Shared<T>>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+        }
+    }
+
+    /// Returns a shared checkout of the item at `idx`, or `None` if `idx` is
+    /// out of bounds.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn checkout_shared(&self, idx: usize) -> Option<Shared<T>> {
+        self.items.get(idx).cloned()
+    }
+
+    /// Returns a reference to the item at `idx`, or `None` if `idx` is out
+    /// of bounds.
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        self.items.get(idx).map(Deref::deref)
+    }
+
+    /// Returns the number of items in the handle.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the handle has no items.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns an iterator over the handle's items.
+    pub fn iter(&self) -> SharedRefIter<'_, T> {
+        Shared::iter_refs(&self.items)
+    }
+}
+
+impl<T> Clone for ReadHandle<T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for ReadHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// === impl Template ===
+
+impl<T> Template<T> {
+    fn new(item: T) -> Self {
+        let slab = Slab::pinned(item);
+        let generation = slab.slot(0).generation();
+        Self {
+            slab: Arc::new(slab),
+            generation,
+        }
+    }
+
+    fn checkout(&self) -> Shared<T> {
+        let slot = ptr::NonNull::from(self.slab.slot(0));
+        Shared::new(slot, self.slab.clone(), self.generation)
+    }
+}
+
+impl<T> Clone for Template<T> {
+    fn clone(&self) -> Self {
+        // The template's own reference keeps the slot checked out.
+        unsafe { self.slab.slot(0).clone_ref() };
+        Self {
+            slab: self.slab.clone(),
+            generation: self.generation,
+        }
+    }
+}
+
+impl<T> Drop for Template<T> {
+    fn drop(&mut self) {
+        if unsafe { self.slab.slot(0).drop_ref(&self.slab, self.generation) } {
+            self.slab.idle().notify();
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Template<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Template")
+            .field("item", unsafe { self.slab.slot(0).item() })
+            .finish()
+    }
+}
+
+// === impl Shared ===
+
+impl<T> Shared<T> {
+    fn new(slot: ptr::NonNull<slab::Slot<T>>, slab: Arc<Slab<T>>, generation: usize) -> Self {
+        unsafe {
+            slot.as_ref().clone_ref();
+            slot.as_ref().mark_shared();
+        }
+        Self {
+            slot,
+            slab,
+            generation,
+        }
+    }
+
+    /// Returns a reference to the item.
+    ///
+    /// This is the same as dereferencing the checkout. It doesn't take another
+    /// reference to the checkout, so it only reads the item's address and
+    /// never touches the pool's shared state: it is wait-free. Prefer
+    /// borrowing the item from a `Shared` checkout that's already held to
+    /// cloning the checkout for each access.
+    #[inline]
+    pub fn get_ref(&self) -> &T {
+        self.deref()
+    }
+
+    /// Returns an iterator over the items of `shared`, without taking another
+    /// reference to any of their checkouts.
+    pub fn iter_refs(shared: &[Self]) -> SharedRefIter<'_, T> {
+        SharedRefIter {
+            shared: shared.iter(),
+        }
+    }
+
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// A checkout of a pool's [template] is not checked out of the pool's
+    /// storage, so it reports an id of its own.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    /// [template]: ../struct.Builder.html#method.with_template
+    pub fn pool_id(&self) -> usize {
+        self.slab.id()
+    }
+
+    /// Returns the version of this item.
+    ///
+    /// An item's version changes every time it's checked out mutably, and
+    /// stays the same for as long as it's shared. A reader that copies data
+    /// out of a shared item can later look the item up again (e.g. with
+    /// [`Pool::get`]) and compare versions to check that the data it copied
+    /// is still current.
+    ///
+    /// [`Pool::get`]: struct.Pool.html#method.get
+    pub fn version(&self) -> usize {
+        slab::version(self.generation)
+    }
+
+    /// Returns a [`Key`] that may be used to look this item up again with
+    /// [`Pool::get`].
+    ///
+    /// [`Key`]: ../struct.Key.html
+    /// [`Pool::get`]: struct.Pool.html#method.get
+    pub fn key(&self) -> Key {
+        slab::key(
+            self.slab.index_of(unsafe { self.slot.as_ref() }),
+            self.generation,
+        )
+    }
+
+    /// Consumes the checkout, returning a pointer to its item.
+    ///
+    /// Like [`Arc::into_raw`], this does not drop the checkout's reference to
+    /// the item: the item stays checked out, and the pool's storage is kept
+    /// alive, until the pointer is turned back into a `Shared` checkout with
+    /// [`Shared::from_raw`] and that checkout is dropped.
+    ///
+    /// [`Arc::into_raw`]: https://doc.rust-lang.org/std/sync/struct.Arc.html#method.into_raw
+    /// [`Shared::from_raw`]: #method.from_raw
+    pub fn into_raw(this: Self) -> *const T {
+        let this = mem::ManuallyDrop::new(this);
+        unsafe { this.slot.as_ref().item() }
+    }
+
+    /// Turns a pointer returned by [`Shared::into_raw`] back into a checkout.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Shared::into_raw` for a checkout from
+    /// `pool` (or a clone of it), and each pointer returned by `into_raw` may
+    /// only be passed to `from_raw` once.
+    ///
+    /// # Panics
+    ///
+    /// If `ptr` does not point to one of `pool`'s items, or to its template.
+    ///
+    /// [`Shared::into_raw`]: #method.into_raw
+    pub unsafe fn from_raw<const CAP: usize>(pool: &Pool<T, CAP>, ptr: *const T) -> Self {
+        // A checkout of the pool's template refers to the template's own slab.
+        let slab = match pool.template {
+            Some(ref template) if template.slab.contains(ptr) => &template.slab,
+            _ => &pool.slab,
+        };
+        let slot = slab
+            .slot_of(ptr)
+            .expect("pointer does not point to an item in this pool");
+        Self {
+            slot: ptr::NonNull::from(slot),
+            // Take back the reference to the slab that `into_raw` leaked.
+            slab: Arc::from_raw(Arc::as_ptr(slab)),
+            generation: slot.generation(),
+        }
+    }
+
+    /// Upgrades the checkout to an [`Owned`] checkout, if it's the only
+    /// reference to the item, or returns it unchanged if it isn't.
+    ///
+    /// Since the item may be modified once it's upgraded, it's given a new
+    /// [version], and can no longer be looked up with the keys of its shared
+    /// checkouts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::fixed::Pool;
+    ///
+    /// let pool: Pool<String> = Pool::with_capacity(1);
+    /// let mut name = pool.checkout();
+    /// name.push_str("pool");
+    /// let name = name.downgrade();
+    /// let key = name.key();
+    ///
+    /// // There's another reference, so the item can't be modified yet.
+    /// let other = pool.get(key).unwrap();
+    /// let name = name.try_upgrade().unwrap_err();
+    /// drop(other);
+    ///
+    /// let mut name = name.try_upgrade().expect("the only reference");
+    /// name.push_str("-1");
+    /// // The item has a new version, so it's no longer found by its old key.
+    /// assert!(pool.get(key).is_none());
+    /// ```
+    ///
+    /// [`Owned`]: struct.Owned.html
+    /// [version]: #method.version
+    pub fn try_upgrade(self) -> Result<Owned<T>, Self> {
+        let generation = match unsafe { self.slot.as_ref() }.try_unshare(self.generation) {
+            Some(generation) => generation,
+            None => return Err(self),
+        };
+        // Hand this checkout's reference to the slot, and to the slab, over
+        // to the owned checkout.
+        let this = mem::ManuallyDrop::new(self);
+        Ok(Owned {
+            slot: this.slot,
+            trace: this.slab.trace_checkout(),
+            slab: unsafe { ptr::read(&this.slab) },
+            generation,
+        })
+    }
+}
+
+// Like an `Arc`, a `Shared` checkout may be sent to (or shared with) another
+// thread if the item may be shared between threads, since the last reference
+// may be dropped on any of them.
+unsafe impl<T: Send + Sync> Send for Shared<T> {}
+unsafe impl<T: Send + Sync> Sync for Shared<T> {}
+
+// === impl SharedRefIter ===
+
+impl<'a, T> Iterator for SharedRefIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.shared.next().map(Shared::get_ref)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shared.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for SharedRefIter<'a, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.shared.next_back().map(Shared::get_ref)
+    }
+}
+
+impl<'a, T> ExactSizeIterator for SharedRefIter<'a, T> {}
+
+impl<'a, T> Clone for SharedRefIter<'a, T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.slot, self.slab.clone(), self.generation)
+    }
+}
+
+impl<T> Deref for Shared<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            // A `Shared` checkout implies that the slot may not be mutated.
+            self.slot.as_ref().item()
+        }
+    }
+}
+
+impl<T> Drop for Shared<T> {
+    fn drop(&mut self) {
+        let slot = unsafe { self.slot.as_ref() };
+        if unsafe { slot.drop_ref(&self.slab, self.generation) } {
+            self.slab.idle().notify();
+        }
+    }
+}
+
+impl<T> AsRef<T> for Shared<T> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<T> Borrow<T> for Shared<T> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<T> PartialEq for Shared<T>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
+    }
+}
+
+impl<T> Eq for Shared<T> where T: Eq {}
+
+impl<T> PartialOrd for Shared<T>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<T> Ord for Shared<T>
+where
+    T: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.deref().cmp(other.deref())
+    }
+}
+
+impl<T> Hash for Shared<T>
+where
+    T: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
+
+impl<T> fmt::Debug for Shared<T>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T> fmt::Display for Shared<T>
+where
+    T: fmt::Display,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T> error::Error for Shared<T>
+where
+    T: error::Error,
+{
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.deref().source()
+    }
+}
+
+// === impl Leaked ===
+
+impl<T, const CAP: usize> Leaked<T, CAP> {
+    /// Converts a [`Shared`] checkout from this pool into a `&'static`
+    /// reference to the pooled item.
+    ///
+    /// The checkout's slot is never released, so the item will not be reused
+    /// or mutated again. If the checkout did not come from this pool, it is
+    /// returned unchanged.
+    ///
+    /// [`Shared`]: struct.Shared.html
+    pub fn promote(&self, shared: Shared<T>) -> Result<&'static T, Shared<T>>
+    where
+        T: 'static,
+    {
+        if !Arc::ptr_eq(&shared.slab, &self.pool.slab) {
+            return Err(shared);
+        }
+
+        let slot = shared.slot;
+        // Leak the checkout's reference to the slot, so that it is never
+        // released.
+        mem::forget(shared);
+        unsafe {
+            // The slab is never freed (we leaked a reference to it), and the
+            // slot is never released, so it may never be mutably accessed
+            // again.
+            Ok(slot.as_ref().item())
+        }
+    }
+}
+
+impl<T, const CAP: usize> Deref for Leaked<T, CAP> {
+    type Target = Pool<T, CAP>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.pool
+    }
+}
+
+// === impl Settings ===
+
+impl<T, N> settings::Make<T, N> for Settings
+where
+    N: FnMut() -> T,
+{
+    type Pool = Pool<T>;
+    fn make(mut builder: Builder<Self, T, N>) -> Self::Pool {
+        let template = builder.template.take().map(Template::new);
+        let ctors = builder.ctors.clone();
+        Pool {
+            slab: Arc::new(builder.into_slab()),
+            template,
+            ctors,
+        }
+    }
+}
+
+impl<T, N, const CAP: usize> settings::Make<T, N> for ConstSettings<CAP>
+where
+    N: FnMut() -> T,
+{
+    type Pool = Pool<T, CAP>;
     fn make(mut builder: Builder<Self, T, N>) -> Self::Pool {
+        assert!(
+            builder.seed.len() <= CAP,
+            "cannot seed a pool of capacity {} with {} items",
+            CAP,
+            builder.seed.len()
+        );
+        let template = builder.template.take().map(Template::new);
+        let ctors = builder.ctors.clone();
         Pool {
-            slab: Arc::new(builder.slab()),
+            slab: Arc::new(builder.with_elements(CAP).into_slab()),
+            template,
+            ctors,
+        }
+    }
+}
+
+impl settings::Configure for Settings {}
+
+impl<const CAP: usize> settings::Configure for ConstSettings<CAP> {}
+
+#[cfg(test)]
+mod tests {
+    //! These tests live in the crate (rather than in `tests/fixed_fuzz.rs`) so
+    //! that the slab's own atomics are the ones modeled by `loom`.
+    use super::*;
+    use loom::thread;
+
+    #[test]
+    fn shared_clone_and_drop_on_separate_threads() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+            let mut owned = pool.checkout();
+            owned.push_str("hello");
+            let shared = owned.downgrade();
+
+            let s1 = shared.clone();
+            let t1 = thread::spawn(move || {
+                let s2 = s1.clone();
+                assert_eq!(&*s2, "hello");
+                drop(s1);
+                drop(s2);
+            });
+
+            let s3 = shared.clone();
+            let t2 = thread::spawn(move || {
+                assert_eq!(&*s3, "hello");
+            });
+
+            drop(shared);
+            t1.join().expect("thread 1 panicked");
+            t2.join().expect("thread 2 panicked");
+
+            // Every reference has been dropped, so the slot must be free.
+            assert_eq!(pool.used(), 0);
+            let c = pool.try_checkout().expect("slot must have been released");
+            assert_eq!(&*c, "");
+        });
+    }
+
+    #[test]
+    fn downgrade_races_with_drop() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+            let owned = pool.checkout();
+
+            let p = pool.clone();
+            let t = thread::spawn(move || {
+                let shared = owned.downgrade();
+                let clone = shared.clone();
+                drop(shared);
+                clone
+            });
+
+            // Meanwhile, this thread polls the (single) slot.
+            let polled = pool.try_checkout();
+            let clone = t.join().expect("thread panicked");
+            if polled.is_some() {
+                panic!("slot was checked out while a shared reference existed");
+            }
+            drop(clone);
+
+            assert_eq!(p.used(), 0);
+            assert!(pool.try_checkout().is_some());
+        });
+    }
+
+    #[test]
+    fn get_races_with_drop() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+            let mut owned = pool.checkout();
+            owned.push_str("hello");
+            let shared = owned.downgrade();
+            let key = shared.key();
+
+            let t = thread::spawn(move || drop(shared));
+
+            // Looking the item up either finds it before the last reference
+            // is dropped, or finds nothing.
+            if let Some(found) = pool.get(key) {
+                assert_eq!(&*found, "hello");
+            }
+            t.join().expect("thread panicked");
+
+            assert_eq!(pool.used(), 0);
+            assert!(pool.get(key).is_none());
+            assert!(pool.try_checkout().is_some());
+        });
+    }
+
+    #[test]
+    fn upgrade_races_with_get() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+            let mut owned = pool.checkout();
+            owned.push_str("hello");
+            let shared = owned.downgrade();
+            let key = shared.key();
+
+            let p = pool.clone();
+            let t = thread::spawn(move || p.get(key).map(|found| found.version()));
+
+            // Either the lookup finds the shared item, or the upgrade wins and
+            // the lookup finds nothing; the two never alias.
+            let upgraded = shared.try_upgrade();
+            let found = t.join().expect("thread panicked");
+            if let Ok(ref upgraded) = upgraded {
+                assert_ne!(found, Some(upgraded.version()));
+            }
+            drop(upgraded);
+
+            assert_eq!(pool.used(), 0);
+            assert!(pool.try_checkout().is_some());
+        });
+    }
+
+    #[test]
+    fn upgrade_races_with_checkout() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+
+            // This checkout may find the slot that's upgraded below at the
+            // head of the free list, and try to acquire it, after it's been
+            // checked out.
+            let p = pool.clone();
+            let t = thread::spawn(move || drop(p.try_checkout()));
+
+            let shared = pool.checkout().downgrade();
+            let upgraded = shared
+                .try_upgrade()
+                .unwrap_or_else(|_| panic!("the only reference"));
+            drop(upgraded);
+            t.join().expect("thread panicked");
+
+            assert_eq!(pool.used(), 0);
+            assert!(pool.try_checkout().is_some());
+        });
+    }
+
+    #[test]
+    fn round_robin_checkout_races_with_release() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::builder()
+                .with_elements(2)
+                .with_reuse(crate::Reuse::RoundRobin)
+                .with_default()
+                .fixed()
+                .finish();
+            let owned = pool.checkout();
+
+            let p = pool.clone();
+            let t = thread::spawn(move || p.try_checkout().map(|c| c.key()));
+            drop(owned);
+
+            let mine = pool.try_checkout().map(|c| c.key());
+            let theirs = t.join().expect("thread panicked");
+            assert!(mine.is_some() || theirs.is_some());
+
+            assert_eq!(pool.used(), 0);
+            assert_eq!(pool.audit().free, 2);
+        });
+    }
+
+    #[test]
+    fn registered_pool_is_dumped_until_dropped() {
+        fn dump() -> String {
+            let mut out = Vec::new();
+            crate::dump_all(&mut out).expect("writing to a Vec cannot fail");
+            String::from_utf8(out).expect("the dump must be UTF-8")
         }
+
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+            pool.register("registered-fixed");
+            let owned = pool.checkout();
+            assert!(dump().contains("registered-fixed"));
+
+            // The registry mustn't keep the pool's storage alive, and the
+            // checkout still does.
+            drop(pool);
+            assert!(dump().contains("registered-fixed"));
+            drop(owned);
+            assert!(!dump().contains("registered-fixed"));
+        });
     }
 }
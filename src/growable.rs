@@ -1,16 +1,41 @@
 use crate::{
-    builder::{settings, Builder},
+    backoff::{self, Backoff},
+    budget::Charge,
+    builder::{settings, Builder, Ctors},
+    latency::Timer,
     slab::{self, Slab},
-    sync::{atomic, Arc, RwLock, RwLockReadGuard},
-    Clear,
+    sync::{atomic, Arc, Mutex, RwLock, RwLockReadGuard, TryLockError},
+    trace,
+    traits::{AnyClear, AnyItem, Split},
+    AuditReport, Budget, Clear, GrowError, Key, Pressure, RetryHistogram, Stats, StatsCursor,
+    VerifyReport,
 };
 use std::{
+    alloc::{self, Layout},
+    borrow::{Borrow, BorrowMut},
+    cell::Cell,
+    cmp, error, fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    io,
+    marker::PhantomData,
     mem,
     ops::{Deref, DerefMut},
-    ptr,
+    pin::Pin,
+    ptr, slice,
+    sync::PoisonError,
+    task::{Context, Poll},
+    thread,
 };
 
-#[derive(Clone)]
+/// A pool that allocates more items when it runs out.
+///
+/// Checkouts keep the pool's storage alive, like clones of the pool do. If
+/// every clone of a pool is dropped while items are still checked out, the
+/// checkouts remain valid, and the storage (and every item in it) is dropped
+/// once the last of them is released. See [`strong_count`].
+///
+/// [`strong_count`]: #method.strong_count
 pub struct Pool<T, N = fn() -> T> {
     inner: Arc<RwLock<Inner<T, N>>>,
 }
@@ -24,13 +49,25 @@ pub struct Pool<T, N = fn() -> T> {
 /// When an `Owned` checkout is dropped, the underlying object is cleared and
 /// released back to the pool.
 ///
+/// Each of a growable pool's items is boxed, so growing the pool never moves
+/// a checked out item: references to it, and the checkout's [key], stay valid
+/// for as long as the checkout (or a [`Shared`] checkout downgraded from it)
+/// is held, no matter how many times the pool grows in the meantime.
+///
 /// [growable pool]: ../struct.Pool.html
 /// [downgraded]: #method.downgrade
+/// [key]: #method.key
 /// [`Shared`]: ../struct.Shared.html
+#[must_use = "dropping a checkout immediately returns it to the pool"]
 pub struct Owned<T, N = fn() -> T> {
     item: ptr::NonNull<T>,
     idx: usize,
+    generation: usize,
     slab: Arc<RwLock<Inner<T, N>>>,
+    trace: trace::Checkout,
+    /// Set once the item has been mutably dereferenced, so that the slot is
+    /// marked dirty when it's released.
+    dirty: bool,
 }
 
 /// A shared, atomically reference-counted checkout of an object in a [growable pool].
@@ -45,31 +82,241 @@ pub struct Owned<T, N = fn() -> T> {
 /// If the shared count is 0, the underlying object is cleared and released back
 /// to the pool.
 ///
+/// Reading the object through a `Shared` checkout (with `Deref` or
+/// [`get_ref`]) is wait-free, as it only follows a pointer. Cloning and
+/// dropping a checkout both take a read lock on the pool, so they may block
+/// while the pool is growing. On hot read paths, borrow objects from
+/// checkouts that are already held, rather than cloning them; [`iter_refs`]
+/// does this for a slice of checkouts.
+///
 /// [growable pool]: ../struct.Pool.html
+/// [`get_ref`]: #method.get_ref
+/// [`iter_refs`]: #method.iter_refs
 /// [upgraded]: #method.try_upgrade
 /// [`Owned`]: ../struct.Owned.html
+#[must_use = "dropping a checkout immediately returns it to the pool"]
 pub struct Shared<T, N = fn() -> T> {
     item: ptr::NonNull<T>,
     idx: usize,
+    generation: usize,
     slab: Arc<RwLock<Inner<T, N>>>,
 }
 
+/// An iterator over the items of a slice of [`Shared`] checkouts, returned by
+/// [`Shared::iter_refs`].
+///
+/// Like [`Shared::get_ref`], this borrows each item, rather than cloning its
+/// checkout.
+///
+/// [`Shared`]: struct.Shared.html
+/// [`Shared::iter_refs`]: struct.Shared.html#method.iter_refs
+/// [`Shared::get_ref`]: struct.Shared.html#method.get_ref
+#[derive(Debug)]
+pub struct SharedRefIter<'a, T, N = fn() -> T> {
+    shared: slice::Iter<'a, Shared<T, N>>,
+}
+
+/// An [`Owned`] checkout of an [`AnyItem`] that is known to hold a `U`.
+///
+/// Returned by [`Owned::downcast`]. The item stays checked out until the
+/// `OwnedDowncast` is dropped, or converted back with [`into_inner`].
+///
+/// [`Owned`]: struct.Owned.html
+/// [`AnyItem`]: ../traits/type.AnyItem.html
+/// [`Owned::downcast`]: struct.Owned.html#method.downcast
+/// [`into_inner`]: #method.into_inner
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct OwnedDowncast<U, N = fn() -> AnyItem> {
+    owned: Owned<AnyItem, N>,
+    _type: PhantomData<fn() -> U>,
+}
+
+/// One of the two disjoint parts of an [`Owned`] checkout, returned by
+/// [`Owned::split`].
+///
+/// The item stays checked out until both parts have been dropped.
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Owned::split`]: struct.Owned.html#method.split
+#[must_use = "dropping both parts of a checkout returns it to the pool"]
+pub struct OwnedPart<T: Split, N = fn() -> T> {
+    owned: Owned<T, N>,
+    part: ptr::NonNull<T::Part>,
+}
+
+/// An [`Owned`] checkout with a tag, returned by [`Owned::with_tag`].
+///
+/// When a `Tagged` checkout is dropped, its tag is passed to the function set
+/// with [`Builder::on_release`]. The tag is stored in the checkout, not the
+/// pool, so tagging a checkout doesn't touch any shared state.
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Owned::with_tag`]: struct.Owned.html#method.with_tag
+/// [`Builder::on_release`]: ../struct.Builder.html#method.on_release
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct Tagged<T, N = fn() -> T> {
+    owned: Owned<T, N>,
+    tag: u64,
+}
+
+/// A group of [`Owned`] checkouts from the same pool, released all at once.
+///
+/// When an `OwnedSet` is dropped, every checkout in it is returned to the pool
+/// together, acquiring the pool's lock once and updating its free list once,
+/// rather than once per checkout.
+///
+/// [`Owned`]: struct.Owned.html
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub struct OwnedSet<T, N = fn() -> T> {
+    items: Vec<(ptr::NonNull<T>, usize, usize)>,
+    slab: Option<Arc<RwLock<Inner<T, N>>>>,
+    /// Set if any of the items may have been modified.
+    dirty: bool,
+}
+
+/// Either a caller's scratch item or an [`Owned`] checkout from a pool.
+///
+/// Returned by [`Pool::checkout_or`].
+///
+/// [`Owned`]: struct.Owned.html
+/// [`Pool::checkout_or`]: struct.Pool.html#method.checkout_or
+#[must_use = "dropping a checkout immediately returns it to the pool"]
+pub enum CheckoutOr<'a, T, N = fn() -> T> {
+    /// The caller's scratch item, which was free.
+    Scratch(&'a mut T),
+    /// An item checked out of the pool, because the scratch item was taken.
+    Owned(Owned<T, N>),
+}
+
+/// A future that checks out an item from a growable pool, waiting for one to
+/// be released if the pool is exhausted and can't grow.
+///
+/// Returned by [`Pool::checkout_async`].
+///
+/// [`Pool::checkout_async`]: struct.Pool.html#method.checkout_async
+#[must_use = "futures do nothing unless polled"]
+pub struct Checkout<'a, T, N = fn() -> T> {
+    pool: &'a Pool<T, N>,
+    /// The future's place in the queue of tasks waiting for an item, once it
+    /// has had to wait.
+    waiting: Option<usize>,
+}
+
+/// A pool whose storage has been intentionally leaked.
+///
+/// Because a leaked pool's storage is never freed, [`Shared`] checkouts from it
+/// may be [promoted] to `&'static` references. This is intended for
+/// configuration-like objects that are checked out once at startup and read
+/// for the rest of the program's lifetime.
+///
+/// A `Leaked` pool dereferences to the underlying [`Pool`], so items may be
+/// checked out of it as usual.
+///
+/// [`Shared`]: struct.Shared.html
+/// [promoted]: #method.promote
+/// [`Pool`]: struct.Pool.html
+pub struct Leaked<T, N = fn() -> T> {
+    pool: Pool<T, N>,
+}
+
 #[derive(Debug, Clone)]
 pub struct Settings {
     pub(crate) growth: Growth,
+    pub(crate) budget: Option<Budget>,
 }
 
-#[derive(Debug, Clone)]
-pub(crate) enum Growth {
+/// Determines how many slots a growable pool adds when it runs out of items.
+#[derive(Clone)]
+#[non_exhaustive]
+pub enum Growth {
+    /// Double the size of the pool (the default).
     Double,
+    /// Grow the pool by half its current size.
     Half,
+    /// Grow the pool by a fixed number of slots.
     Fixed(usize),
+    /// Grow the pool by the number of slots returned by the provided function.
+    ///
+    /// The function is passed the pool's current size, and returns the number
+    /// of slots to add. This may be used to implement growth policies that
+    /// aren't provided by the other variants, such as doubling up to a cap.
+    ///
+    /// The function is shared by clones of the policy, so that a builder's
+    /// settings can still be cloned.
+    Custom(std::sync::Arc<dyn Fn(usize) -> usize + Send + Sync>),
+}
+
+/// An error returned by [`Pool::checkout_fallible`].
+///
+/// [`Pool::checkout_fallible`]: struct.Pool.html#method.checkout_fallible
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CheckoutError {
+    /// The pool was exhausted, and growing it failed because memory for its
+    /// new items could not be allocated.
+    AllocFailed(GrowError),
+    /// The pool was exhausted, and growing it would have exceeded its
+    /// [`Budget`].
+    ///
+    /// [`Budget`]: ../struct.Budget.html
+    OverBudget,
+    /// The checkout was made by the thread that is growing the pool, such as
+    /// from the pool's item constructor or [allocator]. Waiting for growth to
+    /// finish would deadlock.
+    ///
+    /// [allocator]: ../struct.Builder.html#method.with_allocator
+    Reentrant,
+}
+
+thread_local! {
+    /// The innermost pool that the current thread is growing, if any.
+    static GROWING: Cell<*const Growing> = const { Cell::new(ptr::null()) };
+}
+
+/// A pool that the current thread is growing, identified by the address of
+/// its lock.
+///
+/// Growing a pool may grow others (from within its item constructor, say),
+/// so these form a list on the growing thread's stack, innermost first.
+/// Keeping the list on the stack means that checking for reentrancy never
+/// allocates, even while growth is failing for lack of memory.
+struct Growing {
+    id: usize,
+    outer: *const Growing,
+}
+
+/// Pops a pool off the current thread's list of pools it is growing, even if
+/// growing it panicked.
+struct PopGrowing(*const Growing);
+
+/// Counts a blocking checkout as waiting for an item while it exists.
+///
+/// Unlike a fixed pool's, this doesn't borrow the slab, since a waiting
+/// checkout must not hold the pool's lock.
+struct Waiting<'a, T, N> {
+    pool: &'a Pool<T, N>,
 }
 
 struct Inner<T, N> {
+    /// The pool's slots.
+    ///
+    /// Growing may move the slot array, freeing the old one, so it only
+    /// happens while the pool's lock is held for writing. Everything else that
+    /// touches a slot, including releasing a checkout, holds the lock for
+    /// reading, so no one can still be using a retired array. Items are boxed,
+    /// so checkouts never point into the array itself. Anything that shrinks
+    /// or compacts the slab must keep to the same rule.
     slab: Slab<Box<T>>,
-    new: N,
+    /// Constructs new items. Checkouts call this while holding the lock for
+    /// reading, to refill slots whose items were taken out (see
+    /// `Owned::take_and_release`), so it has a lock of its own.
+    new: Mutex<N>,
     settings: Settings,
+    ctors: Ctors<T>,
+    /// Non-zero while a thread has claimed the right to grow the slab.
+    growing: atomic::AtomicUsize,
+    /// The bytes charged to the pool's budget for its items.
+    charge: Charge,
 }
 
 // === impl Pool ===
@@ -78,10 +325,20 @@ impl<T> Pool<T>
 where
     T: Default,
 {
+    /// Returns a new pool of [`DEFAULT_CAPACITY`] items, which grows as
+    /// needed.
+    ///
+    /// [`DEFAULT_CAPACITY`]: #associatedconstant.DEFAULT_CAPACITY
     pub fn new() -> Self {
-        Pool::builder().with_default().with_elements(0).finish()
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
     }
 
+    /// Returns a new pool with exactly `cap` items.
+    ///
+    /// The capacity is not rounded up; the pool's [`capacity`] will be `cap`
+    /// until it first grows.
+    ///
+    /// [`capacity`]: #method.capacity
     pub fn with_capacity(cap: usize) -> Self {
         Pool::builder().with_default().with_elements(cap).finish()
     }
@@ -93,22 +350,353 @@ impl<T> Pool<T, ()> {
     }
 }
 
+impl<T, N> Pool<T, N>
+where
+    N: FnMut() -> T,
+{
+    /// Returns a new, empty pool which constructs new items by calling `new`.
+    pub fn with_fn(new: N) -> Self {
+        Self::with_capacity_and_fn(0, new)
+    }
+
+    /// Returns a new pool with `cap` items, which constructs new items by
+    /// calling `new`.
+    pub fn with_capacity_and_fn(cap: usize, new: N) -> Self {
+        Pool::builder().with_fn(new).with_elements(cap).finish()
+    }
+}
+
 impl<T, N> Pool<T, N> {
+    /// The number of items in a pool created by [`new`] or [`Default`].
+    ///
+    /// Growable pools start out empty and allocate items on demand. Unlike
+    /// [`new`], a builder whose capacity was not set creates a pool of 256
+    /// items, like a fixed pool.
+    ///
+    /// [`new`]: #method.new
+    /// [`Default`]: #impl-Default
+    pub const DEFAULT_CAPACITY: usize = 0;
+
     fn read<'a>(&'a self) -> RwLockReadGuard<'a, Inner<T, N>> {
         self.inner.read().expect("pool poisoned")
     }
 
-    pub fn size(&self) -> usize {
+    /// Returns a number identifying this pool, which is unique within the
+    /// process.
+    ///
+    /// Clones of a pool share its id, which does not change when the pool
+    /// grows. Checkouts report the id of the pool they came from with their
+    /// `pool_id` methods, which may be used to correlate them in logs.
+    pub fn id(&self) -> usize {
+        self.read().slab.id()
+    }
+
+    /// Returns the number of items the pool currently has room for, whether
+    /// or not they are checked out. It grows as items are checked out of an
+    /// exhausted pool.
+    pub fn capacity(&self) -> usize {
         self.read().slab.size()
     }
 
+    /// Returns the number of bytes the pool charges to its [budget] for each
+    /// of its items.
+    ///
+    /// This is the size of the item's box, plus the size of its slot in the
+    /// pool's array of slots (which depends on the item alignment the pool
+    /// was built with).
+    ///
+    /// [budget]: ../struct.Budget.html
+    pub fn bytes_per_item(&self) -> usize {
+        item_bytes(&self.read().slab)
+    }
+
+    /// Returns the number of items that are currently checked out.
     pub fn used(&self) -> usize {
         self.read().slab.used()
     }
 
-    pub fn remaining(&self) -> usize {
+    /// Returns the number of items that may be checked out before the pool is
+    /// exhausted.
+    pub fn available(&self) -> usize {
         self.read().slab.remaining()
     }
+
+    /// Returns `true` if every item in the pool is checked out.
+    pub fn is_exhausted(&self) -> bool {
+        self.available() == 0
+    }
+
+    #[deprecated(note = "renamed to `capacity`")]
+    pub fn size(&self) -> usize {
+        self.capacity()
+    }
+
+    #[deprecated(note = "renamed to `available`")]
+    pub fn remaining(&self) -> usize {
+        self.available()
+    }
+
+    /// Audits the pool, reporting where each of its slots is accounted for.
+    ///
+    /// This is intended for tests and diagnostics: it walks the pool's free
+    /// list, so it takes time proportional to the pool's size. See
+    /// [`AuditReport`] for details.
+    ///
+    /// [`AuditReport`]: ../struct.AuditReport.html
+    pub fn audit(&self) -> AuditReport {
+        self.read().slab.audit()
+    }
+
+    /// Returns the pool's most recent events, oldest first.
+    ///
+    /// Only a small, fixed number of events are retained. This is intended
+    /// for debugging, such as when a slot is unexpectedly lost or released
+    /// twice, and is only available when the `debug-history` feature is
+    /// enabled.
+    #[cfg(feature = "debug-history")]
+    pub fn recent_events(&self) -> Vec<crate::Event> {
+        self.read().slab.recent_events()
+    }
+
+    /// Returns a histogram of how many times blocking checkouts from this
+    /// pool have had to retry.
+    ///
+    /// See [`RetryHistogram`] for details.
+    ///
+    /// [`RetryHistogram`]: ../struct.RetryHistogram.html
+    pub fn retry_histogram(&self) -> RetryHistogram {
+        self.read().slab.retry_histogram()
+    }
+
+    /// Returns histograms of how long blocking checkouts from this pool, and
+    /// growing it, have taken.
+    ///
+    /// See [`LatencyStats`] for details.
+    ///
+    /// [`LatencyStats`]: ../struct.LatencyStats.html
+    #[cfg(feature = "timing-stats")]
+    pub fn latency_stats(&self) -> crate::LatencyStats {
+        self.read().slab.latency_stats()
+    }
+
+    /// Adds this pool to the process-wide registry under `name`, so that it
+    /// is included in [`dump_all`]'s diagnostics.
+    ///
+    /// The registry doesn't keep the pool alive: it is removed once every
+    /// clone of the pool has been dropped.
+    ///
+    /// [`dump_all`]: ../fn.dump_all.html
+    pub fn register(&self, name: impl Into<String>)
+    where
+        T: Send + Sync + 'static,
+        N: Send + Sync + 'static,
+    {
+        crate::registry::register(name.into(), Box::new(Arc::downgrade(&self.inner)));
+    }
+
+    /// Returns the number of clones of this pool and checkouts from it that
+    /// are keeping its storage alive.
+    ///
+    /// Once every clone of the pool has been dropped, this is the number of
+    /// checkouts that have yet to be released.
+    pub fn strong_count(&self) -> usize {
+        Arc::strong_count(&self.inner)
+    }
+
+    /// Returns the largest number of items that have been checked out of
+    /// this pool at once.
+    pub fn high_water(&self) -> usize {
+        self.read().slab.high_water()
+    }
+
+    /// Returns counts of this pool's activity since it was created.
+    ///
+    /// See [`Stats`] for details.
+    ///
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn stats(&self) -> Stats {
+        self.read().slab.stats()
+    }
+
+    /// Returns counts of this pool's activity since the last call with
+    /// `cursor`.
+    ///
+    /// This lets a scraper that periodically reports a pool's metrics get
+    /// the counts for each period without storing and diffing the previous
+    /// totals itself. Several scrapers may each keep their own cursor.
+    pub fn stats_delta(&self, cursor: &mut StatsCursor) -> Stats {
+        self.read().slab.stats_delta(cursor)
+    }
+
+    /// Returns the number of slots that have been poisoned because an
+    /// [`Owned`] checkout was dropped while panicking.
+    ///
+    /// Poisoned slots are skipped by `checkout` until they are [repaired].
+    ///
+    /// [repaired]: #method.repair
+    pub fn poisoned(&self) -> usize {
+        self.read().slab.poisoned()
+    }
+
+    /// Returns the indices of all currently poisoned slots.
+    pub fn poisoned_slots(&self) -> Vec<usize> {
+        self.read().slab.poisoned_slots()
+    }
+
+    /// Returns the block that the slot at `idx` belongs to, or `None` if the
+    /// pool has no slot at `idx`.
+    ///
+    /// A pool's items are allocated in blocks: the items it is built with
+    /// are block 0, and each time the pool grows, the items it adds form the
+    /// next block. Items in the same block were allocated together, so they
+    /// are likely to be near each other in memory, and to have been first
+    /// touched by the same thread. Schedulers that care about cache or NUMA
+    /// locality may group work on items by their block.
+    ///
+    /// A slot's block never changes.
+    pub fn block_of(&self, idx: usize) -> Option<usize> {
+        self.read().slab.block_of(idx)
+    }
+
+    /// Returns how close the pool is to running out of items, relative to the
+    /// watermarks set with [`Builder::with_watermarks`].
+    ///
+    /// [`Builder::with_watermarks`]: ../struct.Builder.html#method.with_watermarks
+    ///
+    /// Watermarks do not grow with the pool.
+    pub fn pressure(&self) -> Pressure {
+        self.read().slab.pressure()
+    }
+
+    /// Returns `true` if `item` belongs to this pool.
+    ///
+    /// This may be used to assert that a checkout (or a reference derived
+    /// from one) came from the expected pool, such as when several pools of
+    /// the same type are in use.
+    ///
+    /// Because a growable pool's items are boxed, this takes time linear in
+    /// the size of the pool.
+    pub fn contains(&self, item: &T) -> bool {
+        self.read().slab.contains_boxed(item)
+    }
+
+    /// Returns a new [`Shared`] checkout of the item that `key` refers to.
+    ///
+    /// Returns `None` if the checkout the key was taken from has since been
+    /// released, or if it has not been downgraded to a shared checkout (so
+    /// that it is still exclusively owned).
+    ///
+    /// [`Shared`]: struct.Shared.html
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn get(&self, key: Key) -> Option<Shared<T, N>> {
+        let inner = self.read();
+        match inner.slab.get(key) {
+            Ok(generation) => Some(Shared {
+                item: ptr::NonNull::from(unsafe { &**inner.slab.slot(key.index()).item() }),
+                idx: key.index(),
+                generation,
+                slab: self.inner.clone(),
+            }),
+            Err(idle) => {
+                // As with dropping a `Shared`, only notify waiters once the
+                // lock has been released.
+                let waiters = inner.slab.idle().clone();
+                drop(inner);
+                if idle {
+                    waiters.notify();
+                }
+                None
+            }
+        }
+    }
+
+    /// Returns the poisoned slot at index `idx` to the pool.
+    ///
+    /// The slot's item will be cleared as usual when it is next checked out.
+    /// Returns `false` if the slot is not poisoned.
+    pub fn repair(&self, idx: usize) -> bool {
+        self.read().slab.repair(idx)
+    }
+
+    /// Returns the number of slots that have been marked as lost because a
+    /// checkout outlived its [lease].
+    ///
+    /// [lease]: ../struct.Builder.html#method.with_lease
+    pub fn lost(&self) -> usize {
+        self.read().slab.lost()
+    }
+
+    /// Marks every checkout that has outlived its [lease] as lost.
+    ///
+    /// Lost slots are never reused, and dropping a checkout of a lost slot does
+    /// nothing. Returns the number of slots marked as lost by this call. If the
+    /// pool was not built with a lease, this always returns 0.
+    ///
+    /// When the `tracing` feature is enabled, each slot marked as lost is
+    /// recorded as an event, with its index and how long its lease had
+    /// expired.
+    ///
+    /// [lease]: ../struct.Builder.html#method.with_lease
+    pub fn reclaim_expired(&self) -> usize {
+        let (reclaimed, idle) = {
+            let inner = self.read();
+            (inner.slab.reclaim_expired(), inner.slab.idle().clone())
+        };
+        // As when releasing a checkout, notify waiters only once our read lock
+        // has been released.
+        if reclaimed > 0 {
+            idle.notify();
+        }
+        reclaimed
+    }
+
+    /// Consumes the pool, returning all of its items.
+    ///
+    /// This succeeds only if this is the last handle to the pool and no items
+    /// are checked out; otherwise, the pool is returned unchanged. It is
+    /// intended for orderly teardown, when pooled resources (such as
+    /// connections) must be closed explicitly rather than dropped.
+    ///
+    /// Items in poisoned slots are returned as well, in whatever state they
+    /// were left in. Slots whose items were taken out with
+    /// [`Owned::take_and_release`], and which haven't been checked out since,
+    /// have no item to return.
+    ///
+    /// [`Owned::take_and_release`]: struct.Owned.html#method.take_and_release
+    pub fn try_into_items(self) -> Result<Vec<T>, Self> {
+        match Arc::try_unwrap(self.inner) {
+            Ok(inner) => {
+                // A thread panicked while holding the lock, but no one else
+                // can observe the pool anymore, so hand its items back as the
+                // docs promise rather than panicking here too.
+                let inner = inner.into_inner().unwrap_or_else(PoisonError::into_inner);
+                Ok(inner.slab.into_items().into_iter().map(|b| *b).collect())
+            }
+            Err(inner) => Err(Self { inner }),
+        }
+    }
+
+    /// Leaks the pool's storage, so that it will never be freed.
+    ///
+    /// See [`Leaked`] for details.
+    ///
+    /// [`Leaked`]: struct.Leaked.html
+    pub fn leak(self) -> Leaked<T, N> {
+        mem::forget(self.inner.clone());
+        Leaked { pool: self }
+    }
+
+    /// Blocks the current thread until every checkout from this pool has been
+    /// released.
+    ///
+    /// This is intended for graceful shutdown: once an application has stopped
+    /// checking out new items, `drain_when_idle` waits for all outstanding
+    /// [`Owned`] and [`Shared`] checkouts to be dropped. If other threads keep
+    /// checking out items, this may never return.
+    pub fn drain_when_idle(&self) {
+        let idle = self.read().slab.idle().clone();
+        idle.wait_until(|| self.read().slab.is_idle())
+    }
 }
 
 impl<T, N> Pool<T, N>
@@ -116,56 +704,481 @@ where
     T: Clear,
     N: FnMut() -> T,
 {
+    /// Checks out the item in the slot at index `idx`, if it's free.
+    ///
+    /// This is meant for tests that need to exercise a particular slot (such
+    /// as the first or last slot of a block). It works by checking out free
+    /// items until slot `idx` comes up, and then releasing the others in the
+    /// reverse order, so it takes time proportional to the number of free
+    /// items, and other threads' checkouts may find the pool exhausted while
+    /// it runs.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn checkout_nth(&self, idx: usize) -> Option<Owned<T, N>> {
+        let mut skipped = Vec::new();
+        let found = loop {
+            match self.try_checkout() {
+                Some(item) if item.key().index() == idx => break Some(item),
+                Some(item) => skipped.push(item),
+                None => break None,
+            }
+        };
+        // Put the skipped items back in the order they were found in.
+        while let Some(item) = skipped.pop() {
+            drop(item);
+        }
+        found
+    }
+
+    /// Checks out every free item once, checking that the pool and the
+    /// items' [`Clear`] implementation behave, and returns a report of what
+    /// was found.
+    ///
+    /// This is meant to be run once a large pool has been built (e.g. at
+    /// startup), to fail fast if it was misconfigured. The report is only
+    /// meaningful if no other thread uses the pool while this runs. See
+    /// [`VerifyReport`] for details.
+    ///
+    /// [`Clear`]: ../traits/trait.Clear.html
+    /// [`VerifyReport`]: ../struct.VerifyReport.html
+    pub fn verify(&self) -> VerifyReport {
+        let items = std::iter::from_fn(|| self.try_checkout())
+            .map(|item: Owned<T, N>| (item.key().index(), item));
+        VerifyReport::run(self.capacity(), items, || self.audit())
+    }
+
     /// Attempt to check out a pooled resource _without_ growing the slab.
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
     pub fn try_checkout(&self) -> Option<Owned<T, N>> {
+        // The thread growing the pool holds its lock.
+        self.check_reentrancy().ok()?;
         loop {
             return match self.try_checkout2() {
                 Ok(checkout) => Some(checkout),
                 Err(slab::Error::AtCapacity) => None,
                 Err(slab::Error::ShouldRetry) => {
-                    atomic::spin_loop_hint();
+                    backoff::spin();
                     continue;
                 }
             };
         }
     }
 
+    /// Attempts to check out an item without blocking or allocating.
+    ///
+    /// This is like [`try_checkout`], except that it also returns `None`
+    /// (rather than waiting) if another thread is currently growing the pool.
+    /// See [`fixed::Pool::try_checkout_nonblocking`] for the details of this
+    /// guarantee, which also apply here, with one more exception: dropping a
+    /// growable pool's checkout acquires the pool's lock for reading, which
+    /// waits if another thread is growing the pool at that moment. As with a
+    /// fixed pool, releasing an item also takes the lock on the pool's queue
+    /// of waiters if tasks are waiting in [`checkout_async`].
+    ///
+    /// [`try_checkout`]: #method.try_checkout
+    /// [`checkout_async`]: #method.checkout_async
+    /// [`fixed::Pool::try_checkout_nonblocking`]: ../fixed/struct.Pool.html#method.try_checkout_nonblocking
+    #[must_use = "dropping a checkout immediately returns it to the pool"]
+    pub fn try_checkout_nonblocking(&self) -> Option<Owned<T, N>> {
+        let inner = match self.inner.try_read() {
+            Ok(inner) => inner,
+            Err(TryLockError::WouldBlock) => return None,
+            Err(TryLockError::Poisoned(_)) => panic!("pool poisoned"),
+        };
+        loop {
+            match self.checkout_from(&inner) {
+                Ok(checkout) => return Some(checkout),
+                Err(slab::Error::AtCapacity) => return None,
+                Err(slab::Error::ShouldRetry) => backoff::spin(),
+            }
+        }
+    }
+
+    /// Checks out an item and immediately returns it to the pool.
+    ///
+    /// Checkouts are `#[must_use]`, since a checkout that is dropped as soon
+    /// as it's made doesn't reserve anything, which is usually a bug. This
+    /// is for the rare cases where that's intended, such as to make sure that
+    /// an exhausted pool has grown to hold a free item.
+    pub fn checkout_discard(&self) {
+        drop(self.checkout());
+    }
+
+    /// Moves up to `n` of `from`'s free items into this pool, returning the
+    /// number of items moved.
+    ///
+    /// Pools can't trade slots, so each item taken from `from` is exchanged
+    /// for one of this pool's free items, and neither pool's capacity
+    /// changes. This lets a stage of a pipeline that is temporarily idle lend
+    /// its warmed up items (such as buffers that have grown to their working
+    /// size) to a busy stage, and take them back later by stealing from it in
+    /// turn. No more items are moved than both pools have free.
+    pub fn steal_free<M>(&self, from: &Pool<T, M>, n: usize) -> usize
+    where
+        M: FnMut() -> T,
+    {
+        if self.id() == from.id() {
+            return 0;
+        }
+        // Hold on to every checkout until the items have been exchanged, so
+        // that the same free items aren't checked out again.
+        let mut exchanged = Vec::new();
+        while exchanged.len() < n {
+            let theirs = match from.try_checkout() {
+                Some(theirs) => theirs,
+                None => break,
+            };
+            let ours = match self.try_checkout() {
+                Some(ours) => ours,
+                None => break,
+            };
+            exchanged.push((ours, theirs));
+        }
+        for (ours, theirs) in &mut exchanged {
+            mem::swap::<T>(ours, theirs);
+        }
+        exchanged.len()
+    }
+
     fn try_checkout2(&self) -> Result<Owned<T, N>, slab::Error> {
-        let mut slot = self
-            .inner
-            .read()
-            .expect("pool poisoned")
-            .slab
-            .try_checkout()?;
+        self.checkout_from(&self.read())
+    }
+
+    fn checkout_from(&self, inner: &Inner<T, N>) -> Result<Owned<T, N>, slab::Error> {
+        let mut slot = inner.slab.try_checkout()?;
         let slot = unsafe { slot.as_mut() };
-        let idx = slot.index();
-        let item = slot.as_ptr();
-        let checkout = Owned {
+        let idx = inner.slab.index_of(slot);
+        let generation = slot.generation();
+        let mut checkout = Owned {
             idx,
-            item,
+            item: ptr::NonNull::dangling(),
+            generation,
             slab: self.inner.clone(),
+            trace: inner.slab.trace_checkout(),
+            dirty: false,
         };
+        if slot.is_vacant() {
+            // The slot's item was taken out, so construct its replacement now
+            // that it's needed. If the constructor panics, dropping the
+            // checkout poisons the slot.
+            let mut new = inner.new.lock().expect("pool poisoned");
+            inner.slab.refill(slot, Box::new((*new)()));
+        }
+        checkout.item = slot.as_ptr();
         #[cfg(debug_assertions)]
         {
-            checkout.assert_valid();
-            self.inner.read().expect("pool poisoned").assert_valid();
+            checkout.assert_valid_in(inner);
+            inner.assert_valid();
         };
         Ok(checkout)
     }
 
+    /// Checks out an item, and initializes it with the constructor registered
+    /// as `tag` using [`Builder::with_ctor`].
+    ///
+    /// # Panics
+    ///
+    /// If no constructor named `tag` was registered.
+    ///
+    /// [`Builder::with_ctor`]: ../struct.Builder.html#method.with_ctor
+    pub fn checkout_with_ctor(&self, tag: &str) -> Owned<T, N> {
+        // Look up the constructor first, so that an unknown tag doesn't
+        // poison the slot.
+        let init = self.read().ctors.get(tag).clone();
+        let mut checkout = self.checkout();
+        init(&mut checkout);
+        checkout
+    }
+
+    /// Grows the pool by the amount its [growth policy] calls for, returning
+    /// the number of slots that were added.
+    ///
+    /// Pools normally grow only when a checkout finds them exhausted. This
+    /// lets a pool be grown ahead of time instead, such as by a task that
+    /// manages the pool's size, so that checkouts don't pay for growth. The
+    /// pool is grown whether or not any of its items are available.
+    ///
+    /// # Errors
+    ///
+    /// If the new items or slots can't be allocated, the pool is left
+    /// unchanged and a [`GrowError`] is returned, rather than aborting the
+    /// process as [`checkout`] would.
+    ///
+    /// [growth policy]: enum.Growth.html
+    /// [`GrowError`]: ../struct.GrowError.html
+    /// [`checkout`]: #method.checkout
+    pub fn try_grow(&self) -> Result<usize, GrowError> {
+        self.growing(|| self.inner.write().expect("pool poisoned").try_grow())?
+    }
+
+    /// Calls `f` with this pool marked as being grown by the current thread,
+    /// or returns an error if it already is.
+    ///
+    /// While a pool grows, the growing thread holds its lock for writing, so
+    /// if the pool's item constructor or allocator used the pool, it would
+    /// deadlock (or, if it could get past the lock, grow the pool again,
+    /// recursively). Checking for this first turns that into an error.
+    fn growing<R>(&self, f: impl FnOnce() -> R) -> Result<R, GrowError> {
+        self.check_reentrancy()?;
+        let this = Growing {
+            id: self.lock_id(),
+            outer: GROWING.with(Cell::get),
+        };
+        GROWING.with(|growing| growing.set(&this));
+        let _pop = PopGrowing(this.outer);
+        Ok(f())
+    }
+
+    /// Returns an error if the current thread is growing this pool.
+    fn check_reentrancy(&self) -> Result<(), GrowError> {
+        let id = self.lock_id();
+        let mut next = GROWING.with(Cell::get);
+        // Safety: every pool in the list is on this thread's stack, in a
+        // frame that is still running.
+        while let Some(growing) = unsafe { next.as_ref() } {
+            if growing.id == id {
+                return Err(GrowError::reentrant());
+            }
+            next = growing.outer;
+        }
+        Ok(())
+    }
+
+    fn lock_id(&self) -> usize {
+        &*self.inner as *const RwLock<Inner<T, N>> as usize
+    }
+
+    /// Adds `item` to the pool as a new free item.
+    #[cfg(feature = "compat")]
+    pub(crate) fn attach(&self, item: T) {
+        let mut item = Some(item);
+        self.inner
+            .write()
+            .expect("pool poisoned")
+            .slab
+            .grow_by(1, &mut || {
+                Box::new(item.take().expect("only one item is attached"))
+            });
+    }
+
+    /// Replaces every free item for which `keep` returns `false` with a newly
+    /// constructed one, returning the number of items replaced.
+    ///
+    /// This allows a background task to sweep the pool for items that have
+    /// gone stale while they sat unused, such as closed connections, so that
+    /// they are never handed out. Checked out items are not visited. The
+    /// pool's lock is held for writing while the sweep runs, so checkouts and
+    /// releases wait for it to finish; `keep` should be cheap.
+    pub fn retain_free(&self, mut keep: impl FnMut(&T) -> bool) -> usize {
+        let mut inner = self.inner.write().expect("pool poisoned");
+        let Inner { slab, new, .. } = &mut *inner;
+        let new = new.get_mut().expect("pool poisoned");
+        slab.retain_free(|item| keep(item), |item| **item = new())
+    }
+
+    /// Grows the pool to make room for a checkout, unless another thread is
+    /// already doing so.
+    ///
+    /// Growth happens in two phases: first, a thread claims the right to grow
+    /// the pool with a single compare-and-swap, and only then does it take the
+    /// write lock and allocate. Threads that lose the race back off and retry
+    /// their checkouts once the winner is done, rather than queueing up for
+    /// the write lock and each growing the pool in turn.
+    fn grow(&self, backoff: &mut Backoff) -> Result<(), GrowError> {
+        let claimed = self
+            .read()
+            .growing
+            .compare_exchange(0, 1, atomic::Ordering::Acquire, atomic::Ordering::Relaxed)
+            .is_ok();
+        if !claimed {
+            backoff.wait();
+            return Ok(());
+        }
+
+        let timer = Timer::start();
+        let grown = self.growing(|| {
+            let mut inner = self.inner.write().expect("pool poisoned");
+            // Items may have been released since the checkout failed, in which
+            // case there's no need to grow.
+            if inner.slab.remaining() == 0 {
+                let grown = inner.try_grow().map(drop);
+                inner.slab.record_growth(&timer);
+                grown
+            } else {
+                Ok(())
+            }
+        });
+        self.read().growing.store(0, atomic::Ordering::Release);
+        grown?
+    }
+
+    /// Checks out an item, growing the pool if it is exhausted.
+    ///
+    /// If the pool can't grow because that would exceed its [budget], this
+    /// waits for one of its items to be released instead.
+    ///
+    /// # Fairness
+    ///
+    /// As with a fixed pool's [`checkout`], a thread that starts a checkout
+    /// while other threads are already waiting for an item yields to them
+    /// first, so that a thread that keeps releasing and checking out items
+    /// doesn't starve them. This makes starvation unlikely rather than
+    /// impossible: waiting threads spin rather than queueing, so the order in
+    /// which they get items isn't guaranteed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use natatorium::growable::{Growth, Pool};
+    ///
+    /// let pool: Pool<String> = Pool::builder()
+    ///     .with_default()
+    ///     .with_elements(2)
+    ///     .with_growth(Growth::Fixed(2))
+    ///     .finish();
+    ///
+    /// let a = pool.checkout();
+    /// let b = pool.checkout();
+    /// assert_eq!(pool.capacity(), 2);
+    ///
+    /// // The pool is exhausted, so checking out another item grows it.
+    /// let c = pool.checkout();
+    /// assert_eq!(pool.capacity(), 4);
+    /// assert_eq!(pool.used(), 3);
+    /// ```
+    ///
+    /// [budget]: ../struct.Budget.html
+    /// [`checkout`]: ../fixed/struct.Pool.html#method.checkout
     pub fn checkout(&self) -> Owned<T, N> {
+        let mut backoff = Backoff::new();
+        let mut waiting = None;
+        self.read().slab.yield_to_waiters();
         loop {
-            match self.try_checkout2() {
+            match self.checkout_fallible() {
                 Ok(checkout) => return checkout,
-                Err(slab::Error::AtCapacity) => self.inner.write().expect("pool poisoned").grow(),
-                Err(slab::Error::ShouldRetry) => {}
+                Err(CheckoutError::OverBudget) => {
+                    waiting.get_or_insert_with(|| Waiting::start(self));
+                    backoff.wait();
+                }
+                Err(CheckoutError::AllocFailed(error)) => error.raise(),
+                Err(CheckoutError::Reentrant) => GrowError::reentrant().raise(),
+            }
+        }
+    }
+
+    /// Returns a future that checks out an item, growing the pool if it is
+    /// exhausted.
+    ///
+    /// This is the asynchronous counterpart to [`checkout`]. If the pool can't
+    /// grow because that would exceed its [budget], the task waits to be woken
+    /// when one of the pool's [`Owned`] or [`Shared`] checkouts is released
+    /// (or the pool grows), rather than spinning. Waiting tasks are woken in
+    /// the order in which they started waiting, as described for a fixed
+    /// pool's [`checkout_async`]. Budget freed by another pool that shares
+    /// this one's budget doesn't wake them.
+    ///
+    /// Growing the pool still happens on the polling task, and holds the
+    /// pool's lock for writing while the new items are constructed.
+    ///
+    /// # Panics
+    ///
+    /// Like [`checkout`], polling the future aborts the process if the pool
+    /// can't be grown because memory for its new items couldn't be
+    /// allocated, and panics if it's polled from the pool's item constructor
+    /// or allocator.
+    ///
+    /// [`checkout`]: #method.checkout
+    /// [budget]: ../struct.Budget.html
+    /// [`Owned`]: struct.Owned.html
+    /// [`Shared`]: struct.Shared.html
+    /// [`checkout_async`]: ../fixed/struct.Pool.html#method.checkout_async
+    pub fn checkout_async(&self) -> Checkout<'_, T, N> {
+        Checkout {
+            pool: self,
+            waiting: None,
+        }
+    }
+
+    /// Checks out an item, growing the pool if it is exhausted, but returns
+    /// an error rather than aborting the process if the pool can't be grown.
+    ///
+    /// [`checkout`] aborts when the allocator fails to provide memory for new
+    /// items, just as a `Vec` that fails to grow would. Services that must
+    /// degrade gracefully under memory pressure should use this instead, and
+    /// shed the request (or wait for an item to be released) on error. A
+    /// failed attempt to grow leaves the pool unchanged.
+    ///
+    /// [`checkout`]: #method.checkout
+    pub fn checkout_fallible(&self) -> Result<Owned<T, N>, CheckoutError> {
+        self.check_reentrancy()
+            .map_err(|_| CheckoutError::Reentrant)?;
+        let timer = Timer::start();
+        let mut backoff = Backoff::new();
+        loop {
+            // Hold the read lock for both the checkout and recording its
+            // retries, so that the common case only acquires it once.
+            let error = {
+                let inner = self.read();
+                match self.checkout_from(&inner) {
+                    Ok(checkout) => {
+                        inner.slab.record_checkout(backoff.retries(), &timer);
+                        return Ok(checkout);
+                    }
+                    Err(error) => error,
+                }
+            };
+            match error {
+                // Growing makes room for the checkout, so retry immediately.
+                slab::Error::AtCapacity => self.grow(&mut backoff).map_err(|error| {
+                    if error.is_over_budget() {
+                        CheckoutError::OverBudget
+                    } else if error.is_reentrant() {
+                        CheckoutError::Reentrant
+                    } else {
+                        CheckoutError::AllocFailed(error)
+                    }
+                })?,
+                // The snapshot got stale; back off and retry.
+                slab::Error::ShouldRetry => backoff.wait(),
+            }
+        }
+    }
+
+    /// Checks out the item in `scratch` if there is one, or else an item from
+    /// the pool.
+    ///
+    /// This lets code that keeps a scratch item of its own (such as a
+    /// per-task buffer) share its call sites with code that uses the pool: the
+    /// scratch item is cleared and used whenever the caller has one, and the
+    /// pool is only used otherwise. The scratch item is never returned to the
+    /// pool.
+    pub fn checkout_or<'a>(&self, scratch: &'a mut Option<T>) -> CheckoutOr<'a, T, N> {
+        match scratch {
+            Some(item) => {
+                slab::clear(item);
+                CheckoutOr::Scratch(item)
             }
+            None => CheckoutOr::Owned(self.checkout()),
+        }
+    }
+}
 
-            atomic::spin_loop_hint();
+impl<T, N> Clone for Pool<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
         }
     }
 }
 
+impl<T, N> fmt::Debug for Pool<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pool")
+            .field("size", &self.capacity())
+            .field("used", &self.used())
+            .finish()
+    }
+}
+
 impl<T, N> From<Builder<Settings, T, N>> for Pool<T, N>
 where
     N: FnMut() -> T,
@@ -189,7 +1202,7 @@ where
     T: Default,
 {
     fn default() -> Self {
-        Builder::new().with_default().growable().finish()
+        Self::new()
     }
 }
 
@@ -215,6 +1228,7 @@ impl<T, N> DerefMut for Owned<T, N> {
             // An `Owned` checkout requires that we have unique access to this
             // slot, and an `&mut Owned` ensures the slot cannot be mutably
             // dereferenced with a shared ref to the owned checkout.
+            self.dirty = true;
             self.item.as_mut()
         }
     }
@@ -222,74 +1236,823 @@ impl<T, N> DerefMut for Owned<T, N> {
 
 impl<T, N> Drop for Owned<T, N> {
     fn drop(&mut self) {
+        self.trace.finish(self.idx);
         // if the pool is poisoned, it'll be destroyed anyway, so don't
         // double panic!
-        if let Ok(inner) = self.slab.read() {
-            inner.slab.slot(self.idx).drop_ref(&inner.slab);
-        }
+        let idle = match self.slab.read() {
+            Ok(inner) => {
+                let slot = inner.slab.slot(self.idx);
+                if self.dirty {
+                    slot.mark_dirty();
+                }
+                // If we're panicking, the item may have been left in an
+                // inconsistent state, so don't hand it out again.
+                let idle = if thread::panicking() {
+                    unsafe { slot.poison(&inner.slab, self.generation) }
+                } else {
+                    unsafe { slot.drop_ref(&inner.slab, self.generation) }
+                };
+                if !idle {
+                    return;
+                }
+                inner.slab.idle().clone()
+            }
+            _ => return,
+        };
+        // Waiters check for idleness while holding a read lock, so notify them
+        // only once ours has been released.
+        idle.notify();
     }
 }
 
-impl<T, N> Owned<T, N> {
-    pub fn downgrade(self) -> Shared<T, N> {
-        // TODO: cloning the slot and slab will cause two ref-count bumps (one
-        // for the slot's ref count, and one for the Arc), but we can't move out
-        // of `self` since `Owned` implements `Drop`. This may not be a big deal
-        // but it would be nice to fix.
-        Shared::new(self.item, self.idx, self.slab.clone())
+impl<T, N> AsRef<T> for Owned<T, N> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
     }
+}
 
-    pub fn detach(&mut self) -> T
-    where
-        N: FnMut() -> T,
-    {
-        let mut lock = self.slab.write().expect("pool poisoned");
-        let new = &mut lock.new;
-        let slot = unsafe { self.item.as_mut() };
-        mem::replace(slot, new())
+impl<T, N> AsMut<T> for Owned<T, N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self.deref_mut()
     }
+}
 
-    /// Asserts that the invariants enforced by the pool are currently valid for
-    /// this `Owned` reference.
-    pub fn assert_valid(&self) {
-        assert_eq!(
-            self.read_slab()
-                .slot(self.idx)
-                .ref_count(atomic::Ordering::SeqCst),
-            1,
-            "invariant violated: owned checkout must have exactly one reference"
-        );
+impl<T, N> Borrow<T> for Owned<T, N> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.deref()
     }
+}
 
-    fn read_slab<'a>(&'a self) -> RwLockReadGuard<'a, Inner<T, N>> {
-        self.slab.read().expect("pool poisoned")
+impl<T, N> BorrowMut<T> for Owned<T, N> {
+    #[inline]
+    fn borrow_mut(&mut self) -> &mut T {
+        self.deref_mut()
     }
 }
 
-// === impl Shared ===
-
-impl<T, N> Shared<T, N> {
-    fn new(item: ptr::NonNull<T>, idx: usize, slab: Arc<RwLock<Inner<T, N>>>) -> Self {
-        slab.read()
-            .expect("pool poisoned")
-            .slab
-            .slot(idx)
-            .clone_ref();
-        Self {
-            item,
-            slab,
-            idx,
-        }
+impl<T, N> PartialEq for Owned<T, N>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
     }
+}
 
-    pub fn try_upgrade(self) -> Result<Owned<T, N>, Self> {
-        unimplemented!()
+impl<T, N> Eq for Owned<T, N> where T: Eq {}
+
+impl<T, N> PartialOrd for Owned<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<T, N> Ord for Owned<T, N>
+where
+    T: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.deref().cmp(other.deref())
+    }
+}
+
+impl<T, N> Hash for Owned<T, N>
+where
+    T: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
+
+impl<T, N> fmt::Debug for Owned<T, N>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T, N> fmt::Display for Owned<T, N>
+where
+    T: fmt::Display,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T, N> error::Error for Owned<T, N>
+where
+    T: error::Error,
+{
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.deref().source()
+    }
+}
+
+impl<T, N> io::Read for Owned<T, N>
+where
+    T: io::Read,
+{
+    #[inline]
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.deref_mut().read(buf)
+    }
+
+    #[inline]
+    fn read_vectored(&mut self, bufs: &mut [io::IoSliceMut<'_>]) -> io::Result<usize> {
+        self.deref_mut().read_vectored(bufs)
+    }
+
+    #[inline]
+    fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize> {
+        self.deref_mut().read_to_end(buf)
+    }
+
+    #[inline]
+    fn read_to_string(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.deref_mut().read_to_string(buf)
+    }
+
+    #[inline]
+    fn read_exact(&mut self, buf: &mut [u8]) -> io::Result<()> {
+        self.deref_mut().read_exact(buf)
+    }
+}
+
+impl<T, N> io::Write for Owned<T, N>
+where
+    T: io::Write,
+{
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.deref_mut().write(buf)
+    }
+
+    #[inline]
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        self.deref_mut().write_vectored(bufs)
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.deref_mut().flush()
+    }
+
+    #[inline]
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.deref_mut().write_all(buf)
+    }
+
+    #[inline]
+    fn write_fmt(&mut self, fmt: fmt::Arguments<'_>) -> io::Result<()> {
+        self.deref_mut().write_fmt(fmt)
+    }
+}
+
+impl<T, N> fmt::Write for Owned<T, N>
+where
+    T: fmt::Write,
+{
+    #[inline]
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.deref_mut().write_str(s)
+    }
+
+    #[inline]
+    fn write_char(&mut self, c: char) -> fmt::Result {
+        self.deref_mut().write_char(c)
+    }
+
+    #[inline]
+    fn write_fmt(&mut self, args: fmt::Arguments<'_>) -> fmt::Result {
+        self.deref_mut().write_fmt(args)
+    }
+}
+
+impl<T, N> Owned<T, N> {
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.slab.read().expect("pool poisoned").slab.id()
+    }
+
+    /// Tags the checkout with `tag`, such as the ID of the request it was
+    /// checked out for.
+    ///
+    /// The tag is passed to the function set with [`Builder::on_release`]
+    /// when the returned checkout is released.
+    ///
+    /// [`Builder::on_release`]: ../struct.Builder.html#method.on_release
+    pub fn with_tag(self, tag: u64) -> Tagged<T, N> {
+        Tagged { owned: self, tag }
+    }
+
+    pub fn downgrade(self) -> Shared<T, N> {
+        // TODO: cloning the slot and slab will cause two ref-count bumps (one
+        // for the slot's ref count, and one for the Arc), but we can't move out
+        // of `self` since `Owned` implements `Drop`. This may not be a big deal
+        // but it would be nice to fix.
+        Shared::new(self.item, self.idx, self.generation, self.slab.clone())
+    }
+
+    /// Returns the version of this item.
+    ///
+    /// Every mutable checkout of an item has a different version, which is
+    /// kept by the [`Shared`] checkouts it's downgraded to. See
+    /// [`Shared::version`] for details.
+    ///
+    /// [`Shared`]: struct.Shared.html
+    /// [`Shared::version`]: struct.Shared.html#method.version
+    pub fn version(&self) -> usize {
+        slab::version(self.generation)
+    }
+
+    /// Returns a [`Key`] for this checkout.
+    ///
+    /// The key can only be used to look the item up with [`Pool::get`] once
+    /// this checkout has been [downgraded] to a [`Shared`] checkout.
+    ///
+    /// [`Key`]: ../struct.Key.html
+    /// [`Pool::get`]: struct.Pool.html#method.get
+    /// [downgraded]: #method.downgrade
+    /// [`Shared`]: struct.Shared.html
+    pub fn key(&self) -> Key {
+        slab::key(self.idx, self.generation)
+    }
+
+    /// Returns the block of the pool that this item was allocated in.
+    ///
+    /// See [`Pool::block_of`] for what a block is.
+    ///
+    /// [`Pool::block_of`]: struct.Pool.html#method.block_of
+    pub fn block_id(&self) -> usize {
+        self.slab
+            .read()
+            .expect("pool poisoned")
+            .slab
+            .block_of(self.idx)
+            .expect("a checked out slot is always in the pool")
+    }
+
+    pub fn detach(&mut self) -> T
+    where
+        N: FnMut() -> T,
+    {
+        let mut lock = self.slab.write().expect("pool poisoned");
+        lock.slab.record_detach();
+        let new = lock.new.get_mut().expect("pool poisoned");
+        self.dirty = true;
+        let slot = unsafe { self.item.as_mut() };
+        mem::replace(slot, new())
+    }
+
+    /// Takes the item out of the pool, and releases its slot.
+    ///
+    /// Unlike [`detach`], this doesn't call the pool's constructor right
+    /// away. The slot is left empty, and the constructor is only called to
+    /// refill it when it is next checked out, so a pool whose items are
+    /// mostly taken pays for each replacement once, and only if it's used.
+    /// This ends the checkout, so the slot is immediately available again.
+    ///
+    /// Detached items are counted in the pool's [`Stats`], so that a pool
+    /// whose items are mostly detached (and which is therefore acting as an
+    /// allocator) can be spotted.
+    ///
+    /// [`detach`]: #method.detach
+    /// [`Stats`]: ../struct.Stats.html
+    pub fn take_and_release(self) -> T {
+        let item = {
+            // Other threads may look at every slot's item while holding the
+            // lock for reading (see `Pool::contains`), so empty the slot
+            // while holding it for writing.
+            let mut inner = self.slab.write().expect("pool poisoned");
+            inner.slab.record_detach();
+            // This is the slot's only reference, and releasing an empty slot
+            // doesn't touch its item.
+            unsafe { inner.slab.slot_mut(self.idx).take() }
+        };
+        *item
+    }
+
+    /// Asserts that the invariants enforced by the pool are currently valid for
+    /// this `Owned` reference.
+    pub fn assert_valid(&self) {
+        self.assert_valid_in(&self.read_slab());
+    }
+
+    fn assert_valid_in(&self, inner: &Inner<T, N>) {
+        assert_eq!(
+            inner.slot(self.idx).ref_count(atomic::Ordering::SeqCst),
+            1,
+            "invariant violated: owned checkout must have exactly one reference"
+        );
+    }
+
+    fn read_slab<'a>(&'a self) -> RwLockReadGuard<'a, Inner<T, N>> {
+        self.slab.read().expect("pool poisoned")
+    }
+}
+
+impl<T: Split, N> Owned<T, N> {
+    /// Splits the checkout into two parts that can be used independently,
+    /// such as a buffer's header and body.
+    ///
+    /// The first part is the item before `at`, and the second is the rest
+    /// (see [`Split::split_at_mut`]). The item stays checked out until both
+    /// parts have been dropped. The item can't be looked up by its key while
+    /// it is split, as it isn't shared.
+    ///
+    /// # Panics
+    ///
+    /// If the item can't be split at `at`.
+    ///
+    /// [`Split::split_at_mut`]: ../traits/trait.Split.html#tymethod.split_at_mut
+    pub fn split(mut self, at: usize) -> (OwnedPart<T, N>, OwnedPart<T, N>) {
+        let (head, tail) = self.deref_mut().split_at_mut(at);
+        let (head, tail) = (ptr::NonNull::from(head), ptr::NonNull::from(tail));
+        // The second part holds a reference of its own, so that the slot is
+        // released only once both have been dropped. The slot isn't marked as
+        // shared, so `Pool::get` still won't hand out references to it.
+        let trace = {
+            let inner = self.read_slab();
+            unsafe { inner.slot(self.idx).clone_ref() };
+            inner.slab.trace_checkout()
+        };
+        let other = Owned {
+            item: self.item,
+            idx: self.idx,
+            generation: self.generation,
+            slab: self.slab.clone(),
+            trace,
+            dirty: true,
+        };
+        (
+            OwnedPart {
+                owned: self,
+                part: head,
+            },
+            OwnedPart {
+                owned: other,
+                part: tail,
+            },
+        )
+    }
+}
+
+impl<N> Owned<AnyItem, N> {
+    /// Returns a checkout of the item as a `U`, or this checkout if the item
+    /// is not a `U`.
+    ///
+    /// The item stays checked out either way.
+    pub fn downcast<U: AnyClear>(self) -> Result<OwnedDowncast<U, N>, Self> {
+        // `Box<dyn AnyClear>` is itself `AnyClear`, so be sure to ask the
+        // boxed item.
+        if (**self).as_any().is::<U>() {
+            Ok(OwnedDowncast {
+                owned: self,
+                _type: PhantomData,
+            })
+        } else {
+            Err(self)
+        }
+    }
+}
+
+// === impl Tagged ===
+
+impl<T, N> Tagged<T, N> {
+    /// Returns the checkout's tag.
+    pub fn tag(&self) -> u64 {
+        self.tag
+    }
+
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.owned.pool_id()
+    }
+
+    /// Removes the checkout's tag, so that releasing it isn't reported.
+    pub fn into_owned(self) -> Owned<T, N> {
+        let this = mem::ManuallyDrop::new(self);
+        unsafe { ptr::read(&this.owned) }
+    }
+}
+
+impl<T, N> Deref for Tagged<T, N> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        self.owned.deref()
+    }
+}
+
+impl<T, N> DerefMut for Tagged<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.owned.deref_mut()
+    }
+}
+
+impl<T, N> Drop for Tagged<T, N> {
+    fn drop(&mut self) {
+        // If the pool is poisoned, the checkout won't be released either.
+        if let Ok(inner) = self.owned.slab.read() {
+            inner.slab.notify_release(self.tag);
+        }
+    }
+}
+
+impl<T: fmt::Debug, N> fmt::Debug for Tagged<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Tagged")
+            .field("item", self.deref())
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+// === impl OwnedPart ===
+
+impl<T: Split, N> OwnedPart<T, N> {
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.owned.pool_id()
+    }
+}
+
+impl<T: Split, N> Deref for OwnedPart<T, N> {
+    type Target = T::Part;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe {
+            // The parts of a split item are disjoint, and the item can't be
+            // accessed any other way until both parts are dropped.
+            self.part.as_ref()
+        }
+    }
+}
+
+impl<T: Split, N> DerefMut for OwnedPart<T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { self.part.as_mut() }
+    }
+}
+
+impl<T: Split, N> fmt::Debug for OwnedPart<T, N>
+where
+    T::Part: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+// === impl OwnedDowncast ===
+
+impl<U: AnyClear, N> OwnedDowncast<U, N> {
+    /// Converts this back into a checkout of the type-erased item.
+    pub fn into_inner(self) -> Owned<AnyItem, N> {
+        self.owned
+    }
+}
+
+impl<U: AnyClear, N> Deref for OwnedDowncast<U, N> {
+    type Target = U;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        (**self.owned)
+            .as_any()
+            .downcast_ref()
+            .expect("item was checked to be a `U` when it was downcast")
+    }
+}
+
+impl<U: AnyClear, N> DerefMut for OwnedDowncast<U, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        (**self.owned)
+            .as_any_mut()
+            .downcast_mut()
+            .expect("item was checked to be a `U` when it was downcast")
+    }
+}
+
+impl<U: AnyClear + fmt::Debug, N> fmt::Debug for OwnedDowncast<U, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+// === impl OwnedSet ===
+
+impl<T, N> OwnedSet<T, N> {
+    /// Returns a new, empty `OwnedSet`.
+    pub fn new() -> Self {
+        Self::with_capacity(0)
+    }
+
+    /// Returns a new, empty `OwnedSet` with space for `cap` checkouts.
+    pub fn with_capacity(cap: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(cap),
+            slab: None,
+            dirty: false,
+        }
+    }
+
+    /// Adds a checkout to the set.
+    ///
+    /// # Panics
+    ///
+    /// If `checkout` was not checked out of the same pool as the checkouts
+    /// already in the set.
+    pub fn push(&mut self, checkout: Owned<T, N>) {
+        if let Some(ref slab) = self.slab {
+            assert!(
+                Arc::ptr_eq(slab, &checkout.slab),
+                "checkouts in an `OwnedSet` must all come from the same pool (expected pool {}, got pool {})",
+                slab.read().expect("pool poisoned").slab.id(),
+                checkout.pool_id(),
+            );
+        }
+
+        // Take ownership of the checkout's reference without releasing it.
+        let checkout = mem::ManuallyDrop::new(checkout);
+        let slab = unsafe { ptr::read(&checkout.slab) };
+        if self.slab.is_none() {
+            self.slab = Some(slab);
+        }
+        self.dirty |= checkout.dirty;
+        self.items
+            .push((checkout.item, checkout.idx, checkout.generation));
+    }
+
+    /// Returns the number of checkouts in the set.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Returns `true` if the set contains no checkouts.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Returns an iterator over the checked out items.
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.items
+            .iter()
+            .map(|(item, _, _)| unsafe { item.as_ref() })
+    }
+
+    /// Returns an iterator that allows modifying each checked out item.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> + '_ {
+        self.dirty = true;
+        self.items.iter_mut().map(|(item, _, _)| unsafe {
+            // Like an `Owned` checkout, the set has unique access to each of
+            // its items.
+            item.as_mut()
+        })
+    }
+}
+
+impl<T, N> Default for OwnedSet<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, N> fmt::Debug for OwnedSet<T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T, N> Drop for OwnedSet<T, N> {
+    fn drop(&mut self) {
+        let slab = match self.slab.take() {
+            Some(slab) => slab,
+            None => return,
+        };
+        // if the pool is poisoned, it'll be destroyed anyway, so don't
+        // double panic!
+        let idle = match slab.read() {
+            Ok(inner) => {
+                let dirty = self.dirty;
+                let slots = self.items.iter().map(|&(_, idx, generation)| {
+                    let slot = inner.slab.slot(idx);
+                    if dirty {
+                        slot.mark_dirty();
+                    }
+                    (slot, generation)
+                });
+                let idle = if thread::panicking() {
+                    // As with `Owned`, don't hand out items that may have been
+                    // left in an inconsistent state.
+                    slots.fold(false, |idle, (slot, generation)| unsafe {
+                        slot.poison(&inner.slab, generation) || idle
+                    })
+                } else {
+                    inner.slab.release_all(slots)
+                };
+                if !idle {
+                    return;
+                }
+                inner.slab.idle().clone()
+            }
+            _ => return,
+        };
+        // Waiters check for idleness while holding a read lock, so notify them
+        // only once ours has been released.
+        idle.notify();
+    }
+}
+
+// === impl Shared ===
+
+impl<T, N> Shared<T, N> {
+    fn new(
+        item: ptr::NonNull<T>,
+        idx: usize,
+        generation: usize,
+        slab: Arc<RwLock<Inner<T, N>>>,
+    ) -> Self {
+        {
+            let inner = slab.read().expect("pool poisoned");
+            let slot = inner.slab.slot(idx);
+            unsafe { slot.clone_ref() };
+            slot.mark_shared();
+        }
+        Self {
+            item,
+            slab,
+            idx,
+            generation,
+        }
+    }
+
+    /// Returns a reference to the item.
+    ///
+    /// This is the same as dereferencing the checkout. It doesn't take another
+    /// reference to the checkout, so it only reads the item's address and
+    /// never touches the pool's shared state: it is wait-free. Prefer
+    /// borrowing the item from a `Shared` checkout that's already held to
+    /// cloning the checkout for each access.
+    #[inline]
+    pub fn get_ref(&self) -> &T {
+        self.deref()
+    }
+
+    /// Returns an iterator over the items of `shared`, without taking another
+    /// reference to any of their checkouts.
+    pub fn iter_refs(shared: &[Self]) -> SharedRefIter<'_, T, N> {
+        SharedRefIter {
+            shared: shared.iter(),
+        }
+    }
+
+    /// Returns the [id] of the pool this item was checked out from.
+    ///
+    /// [id]: struct.Pool.html#method.id
+    pub fn pool_id(&self) -> usize {
+        self.slab.read().expect("pool poisoned").slab.id()
+    }
+
+    /// Returns the version of this item.
+    ///
+    /// An item's version changes every time it's checked out mutably, and
+    /// stays the same for as long as it's shared. A reader that copies data
+    /// out of a shared item can later look the item up again (e.g. with
+    /// [`Pool::get`]) and compare versions to check that the data it copied
+    /// is still current.
+    ///
+    /// [`Pool::get`]: struct.Pool.html#method.get
+    pub fn version(&self) -> usize {
+        slab::version(self.generation)
+    }
+
+    /// Returns a [`Key`] that may be used to look this item up again with
+    /// [`Pool::get`].
+    ///
+    /// [`Key`]: ../struct.Key.html
+    /// [`Pool::get`]: struct.Pool.html#method.get
+    pub fn key(&self) -> Key {
+        slab::key(self.idx, self.generation)
+    }
+
+    /// Returns the block of the pool that this item was allocated in.
+    ///
+    /// See [`Pool::block_of`] for what a block is.
+    ///
+    /// [`Pool::block_of`]: struct.Pool.html#method.block_of
+    pub fn block_id(&self) -> usize {
+        self.slab
+            .read()
+            .expect("pool poisoned")
+            .slab
+            .block_of(self.idx)
+            .expect("a checked out slot is always in the pool")
+    }
+
+    /// Upgrades the checkout to an [`Owned`] checkout, if it's the only
+    /// reference to the item, or returns it unchanged if it isn't.
+    ///
+    /// Since the item may be modified once it's upgraded, it's given a new
+    /// [version], and can no longer be looked up with the keys of its shared
+    /// checkouts.
+    ///
+    /// [`Owned`]: struct.Owned.html
+    /// [version]: #method.version
+    pub fn try_upgrade(self) -> Result<Owned<T, N>, Self> {
+        let upgraded = {
+            let inner = self.slab.read().expect("pool poisoned");
+            inner
+                .slab
+                .slot(self.idx)
+                .try_unshare(self.generation)
+                .map(|generation| (generation, inner.slab.trace_checkout()))
+        };
+        let (generation, trace) = match upgraded {
+            Some(upgraded) => upgraded,
+            None => return Err(self),
+        };
+        // Hand this checkout's reference to the slot, and to the pool, over
+        // to the owned checkout.
+        let this = mem::ManuallyDrop::new(self);
+        Ok(Owned {
+            item: this.item,
+            idx: this.idx,
+            generation,
+            slab: unsafe { ptr::read(&this.slab) },
+            trace,
+            dirty: false,
+        })
+    }
+}
+
+// === impl SharedRefIter ===
+
+impl<'a, T, N> Iterator for SharedRefIter<'a, T, N> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.shared.next().map(Shared::get_ref)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shared.size_hint()
+    }
+}
+
+impl<'a, T, N> DoubleEndedIterator for SharedRefIter<'a, T, N> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.shared.next_back().map(Shared::get_ref)
+    }
+}
+
+impl<'a, T, N> ExactSizeIterator for SharedRefIter<'a, T, N> {}
+
+impl<'a, T, N> Clone for SharedRefIter<'a, T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: self.shared.clone(),
+        }
     }
 }
 
 impl<T, N> Clone for Shared<T, N> {
     fn clone(&self) -> Self {
-        Self::new(self.item, self.idx, self.slab.clone())
+        Self::new(self.item, self.idx, self.generation, self.slab.clone())
     }
 }
 
@@ -309,18 +2072,304 @@ impl<T, N> Drop for Shared<T, N> {
     fn drop(&mut self) {
         // if the pool is poisoned, it'll be destroyed anyway, so don't
         // double panic!
-        if let Ok(inner) = self.slab.read() {
-            inner.slab.slot(self.idx).drop_ref(&inner.slab);
+        let idle = match self.slab.read() {
+            Ok(inner)
+                if unsafe {
+                    inner
+                        .slab
+                        .slot(self.idx)
+                        .drop_ref(&inner.slab, self.generation)
+                } =>
+            {
+                inner.slab.idle().clone()
+            }
+            _ => return,
+        };
+        // Waiters check for idleness while holding a read lock, so notify them
+        // only once ours has been released.
+        idle.notify();
+    }
+}
+
+impl<T, N> AsRef<T> for Shared<T, N> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<T, N> Borrow<T> for Shared<T, N> {
+    #[inline]
+    fn borrow(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<T, N> PartialEq for Shared<T, N>
+where
+    T: PartialEq,
+{
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.deref() == other.deref()
+    }
+}
+
+impl<T, N> Eq for Shared<T, N> where T: Eq {}
+
+impl<T, N> PartialOrd for Shared<T, N>
+where
+    T: PartialOrd,
+{
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        self.deref().partial_cmp(other.deref())
+    }
+}
+
+impl<T, N> Ord for Shared<T, N>
+where
+    T: Ord,
+{
+    #[inline]
+    fn cmp(&self, other: &Self) -> cmp::Ordering {
+        self.deref().cmp(other.deref())
+    }
+}
+
+impl<T, N> Hash for Shared<T, N>
+where
+    T: Hash,
+{
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.deref().hash(state)
+    }
+}
+
+impl<T, N> fmt::Debug for Shared<T, N>
+where
+    T: fmt::Debug,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T, N> fmt::Display for Shared<T, N>
+where
+    T: fmt::Display,
+{
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.deref().fmt(f)
+    }
+}
+
+impl<T, N> error::Error for Shared<T, N>
+where
+    T: error::Error,
+{
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        self.deref().source()
+    }
+}
+
+// === impl CheckoutOr ===
+
+impl<'a, T, N> Deref for CheckoutOr<'a, T, N> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match self {
+            CheckoutOr::Scratch(item) => item,
+            CheckoutOr::Owned(owned) => owned,
         }
     }
 }
 
+impl<'a, T, N> DerefMut for CheckoutOr<'a, T, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            CheckoutOr::Scratch(item) => item,
+            CheckoutOr::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<'a, T, N> AsRef<T> for CheckoutOr<'a, T, N> {
+    #[inline]
+    fn as_ref(&self) -> &T {
+        self.deref()
+    }
+}
+
+impl<'a, T, N> AsMut<T> for CheckoutOr<'a, T, N> {
+    #[inline]
+    fn as_mut(&mut self) -> &mut T {
+        self.deref_mut()
+    }
+}
+
+impl<'a, T, N> fmt::Debug for CheckoutOr<'a, T, N>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckoutOr::Scratch(item) => f.debug_tuple("Scratch").field(item).finish(),
+            CheckoutOr::Owned(owned) => f.debug_tuple("Owned").field(owned).finish(),
+        }
+    }
+}
+
+// === impl Checkout ===
+
+impl<T, N> Checkout<'_, T, N>
+where
+    T: Clear,
+    N: FnMut() -> T,
+{
+    fn stop_waiting(&mut self) {
+        if let Some(id) = self.waiting.take() {
+            self.pool.read().slab.wakers().remove(id);
+        }
+    }
+}
+
+impl<T, N> Future for Checkout<'_, T, N>
+where
+    T: Clear,
+    N: FnMut() -> T,
+{
+    type Output = Owned<T, N>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.pool.checkout_fallible() {
+            Ok(checkout) => {
+                this.stop_waiting();
+                return Poll::Ready(checkout);
+            }
+            Err(CheckoutError::OverBudget) => {}
+            Err(CheckoutError::AllocFailed(error)) => error.raise(),
+            Err(CheckoutError::Reentrant) => GrowError::reentrant().raise(),
+        }
+        this.pool
+            .read()
+            .slab
+            .wakers()
+            .register(&mut this.waiting, cx.waker());
+        // An item may have been released before the waker was registered.
+        match this.pool.try_checkout() {
+            Some(checkout) => {
+                this.stop_waiting();
+                Poll::Ready(checkout)
+            }
+            None => Poll::Pending,
+        }
+    }
+}
+
+impl<T, N> Drop for Checkout<'_, T, N> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiting {
+            self.pool.read().slab.wakers().cancel(id);
+        }
+    }
+}
+
+impl<T, N> fmt::Debug for Checkout<'_, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Checkout")
+            .field("pool", &self.pool.id())
+            .field("waiting", &self.waiting.is_some())
+            .finish()
+    }
+}
+
+// === impl Leaked ===
+
+impl<T, N> Leaked<T, N> {
+    /// Converts a [`Shared`] checkout from this pool into a `&'static`
+    /// reference to the pooled item.
+    ///
+    /// The checkout's slot is never released, so the item will not be reused
+    /// or mutated again. If the checkout did not come from this pool, it is
+    /// returned unchanged.
+    ///
+    /// [`Shared`]: struct.Shared.html
+    pub fn promote(&self, shared: Shared<T, N>) -> Result<&'static T, Shared<T, N>>
+    where
+        T: 'static,
+    {
+        if !Arc::ptr_eq(&shared.slab, &self.pool.inner) {
+            return Err(shared);
+        }
+
+        let item = shared.item;
+        // Leak the checkout's reference to the slot, so that it is never
+        // released.
+        mem::forget(shared);
+        unsafe {
+            // Items are boxed, so they are never moved when the slab grows.
+            // The slab is never freed (we leaked a reference to it), and the
+            // slot is never released, so it may never be mutably accessed
+            // again.
+            Ok(item.as_ref())
+        }
+    }
+}
+
+impl<T, N> Clone for Leaked<T, N> {
+    fn clone(&self) -> Self {
+        Self {
+            pool: Pool {
+                inner: self.pool.inner.clone(),
+            },
+        }
+    }
+}
+
+impl<T, N> fmt::Debug for Leaked<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Leaked").field("pool", &self.pool).finish()
+    }
+}
+
+impl<T, N> Deref for Leaked<T, N> {
+    type Target = Pool<T, N>;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        &self.pool
+    }
+}
+
 // === impl Settings ===
 
 impl Default for Settings {
     fn default() -> Self {
         Settings {
             growth: Growth::Double,
+            budget: None,
+        }
+    }
+}
+
+// === impl Growth ===
+
+impl fmt::Debug for Growth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Growth::Double => f.write_str("Double"),
+            Growth::Half => f.write_str("Half"),
+            Growth::Fixed(amt) => f.debug_tuple("Fixed").field(amt).finish(),
+            Growth::Custom(_) => f.write_str("Custom(..)"),
         }
     }
 }
@@ -331,32 +2380,137 @@ where
 {
     type Pool = Pool<T, N>;
     fn make(mut builder: Builder<Self, T, N>) -> Self::Pool {
+        let slab = builder.boxed_slab();
+        let charge = Charge::new(
+            builder.settings.budget.clone(),
+            slab.size() * item_bytes(&slab),
+        );
         Pool {
             inner: Arc::new(RwLock::new(Inner {
-                slab: builder.slab(),
-                new: builder.new,
+                slab,
+                new: Mutex::new(builder.new),
                 settings: builder.settings,
+                ctors: builder.ctors,
+                growing: atomic::AtomicUsize::new(0),
+                charge,
             })),
         }
     }
 }
 
+impl settings::Configure for Settings {
+    fn configure(&mut self, config: &crate::Config) {
+        if let Some(growth) = config.growth {
+            self.growth = growth.into();
+        }
+    }
+}
+
+// === impl PopGrowing ===
+
+impl Drop for PopGrowing {
+    fn drop(&mut self) {
+        GROWING.with(|growing| growing.set(self.0));
+    }
+}
+
+// === impl Waiting ===
+
+impl<'a, T, N> Waiting<'a, T, N> {
+    fn start(pool: &'a Pool<T, N>) -> Self {
+        pool.read().slab.add_waiter();
+        Self { pool }
+    }
+}
+
+impl<T, N> Drop for Waiting<'_, T, N> {
+    fn drop(&mut self) {
+        // Don't panic again if the pool was poisoned while we waited.
+        if let Ok(inner) = self.pool.inner.read() {
+            inner.slab.remove_waiter();
+        }
+    }
+}
+
+// === impl CheckoutError ===
+
+impl fmt::Display for CheckoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckoutError::AllocFailed(_) => f.write_str("pool exhausted and could not be grown"),
+            CheckoutError::OverBudget => f.write_str("pool exhausted and its budget was spent"),
+            CheckoutError::Reentrant => {
+                f.write_str("checked out of a pool by the thread growing it")
+            }
+        }
+    }
+}
+
+impl error::Error for CheckoutError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            CheckoutError::AllocFailed(error) => Some(error),
+            CheckoutError::OverBudget | CheckoutError::Reentrant => None,
+        }
+    }
+}
+
 // === impl Inner ===
 
 impl<T, N> Inner<T, N>
 where
     N: FnMut() -> T,
 {
-    fn grow(&mut self) {
+    /// Grows the slab by the amount the pool's growth policy calls for,
+    /// returning the number of slots added.
+    fn try_grow(&mut self) -> Result<usize, GrowError> {
         let amt = match self.settings.growth {
             Growth::Fixed(amt) => amt,
             // If the slab is empty, grow 1 element.
             Growth::Double | Growth::Half if self.slab.size() == 0 => 1,
             Growth::Double => self.slab.size(),
             Growth::Half => self.slab.size() / 2,
+            Growth::Custom(ref f) => f(self.slab.size()),
         };
-        let new = &mut self.new;
-        self.slab.grow_by(amt, &mut || Box::new((new)()));
+        // Always grow by at least one slot; otherwise, halving a slab of size 1
+        // (or a fixed growth of 0) would never make room for the checkout.
+        let amt = amt.max(1);
+        self.slab.check_growth(amt)?;
+        let bytes = amt
+            .checked_mul(item_bytes(&self.slab))
+            .ok_or_else(GrowError::capacity_overflow)?;
+        self.charge.try_add(bytes)?;
+        if let Err(error) = self.box_and_grow(amt) {
+            self.charge.refund(bytes);
+            return Err(error);
+        }
+        trace::grew(self.slab.id(), amt, self.slab.size());
+        Ok(amt)
+    }
+
+    fn box_and_grow(&mut self, amt: usize) -> Result<(), GrowError> {
+        // Box every new item before adding any slots, so that if one of them
+        // can't be allocated, the slab is left unchanged.
+        let mut items = Vec::new();
+        items.try_reserve_exact(amt).map_err(|_| {
+            Layout::array::<Box<T>>(amt)
+                .map(GrowError::alloc_failed)
+                .unwrap_or_else(|_| GrowError::capacity_overflow())
+        })?;
+        let new = self.new.get_mut().expect("pool poisoned");
+        for _ in 0..amt {
+            items.push(try_box(new())?);
+        }
+        let mut items = items.into_iter();
+        self.slab.try_grow_by(amt, &mut || {
+            items.next().expect("an item was boxed for every new slot")
+        })
+    }
+}
+
+impl<T, N> crate::registry::Registered for RwLock<Inner<T, N>> {
+    fn row(&self) -> crate::registry::Row {
+        crate::registry::Row::new(&self.read().expect("pool poisoned").slab)
     }
 }
 
@@ -369,3 +2523,78 @@ impl<T, N> Inner<T, N> {
         self.slab.slot(idx)
     }
 }
+
+/// Returns the number of bytes charged to a pool's budget for each of its
+/// items: the item's slot in the slab's array, and the box it's stored in.
+fn item_bytes<T>(slab: &Slab<Box<T>>) -> usize {
+    slab.slot_bytes() + mem::size_of::<T>()
+}
+
+/// Moves `item` into a new box, or returns an error if the box can't be
+/// allocated, rather than aborting like `Box::new`.
+fn try_box<T>(item: T) -> Result<Box<T>, GrowError> {
+    let layout = Layout::new::<T>();
+    if layout.size() == 0 {
+        return Ok(Box::new(item));
+    }
+    unsafe {
+        let ptr = alloc::alloc(layout) as *mut T;
+        if ptr.is_null() {
+            return Err(GrowError::alloc_failed(layout));
+        }
+        ptr.write(item);
+        // The box was allocated by the global allocator with `T`'s layout.
+        Ok(Box::from_raw(ptr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    //! As with the fixed pool's tests, these live in the crate so that the
+    //! slab's own atomics, and the pool's lock, are the ones modeled by
+    //! `loom`.
+    use super::*;
+    use loom::thread;
+
+    #[test]
+    fn exhausted_pool_grows_once() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+            let held = pool.checkout();
+
+            // Both threads find the pool exhausted. Whichever claims growth
+            // second must either back off while the first grows the pool, or
+            // find the new slot free once it holds the write lock.
+            let p = pool.clone();
+            let t = thread::spawn(move || p.grow(&mut Backoff::new()).expect("grow"));
+            pool.grow(&mut Backoff::new()).expect("grow");
+            t.join().expect("thread panicked");
+
+            assert_eq!(pool.capacity(), 2);
+            drop(held);
+        });
+    }
+
+    #[test]
+    fn upgrade_races_with_checkout() {
+        loom::fuzz(|| {
+            let pool: Pool<String> = Pool::with_capacity(1);
+
+            // This checkout may find the slot that's upgraded below at the
+            // head of the free list, and try to acquire it, after it's been
+            // checked out.
+            let p = pool.clone();
+            let t = thread::spawn(move || drop(p.try_checkout()));
+
+            let shared = pool.checkout().downgrade();
+            let upgraded = shared
+                .try_upgrade()
+                .unwrap_or_else(|_| panic!("the only reference"));
+            drop(upgraded);
+            t.join().expect("thread panicked");
+
+            assert_eq!(pool.used(), 0);
+            assert!(pool.try_checkout().is_some());
+        });
+    }
+}
@@ -0,0 +1,84 @@
+use crate::sync::RwLock;
+use std::mem;
+
+/// A handle to a pool that may be replaced at runtime.
+///
+/// A `PoolHandle` allows an application to swap out the pool it checks items
+/// out of (for example, with one of a different size) without coordinating
+/// with every part of the application that uses the pool. Checkouts hold a
+/// reference to the pool they were checked out from, so any checkouts that are
+/// in flight when the pool is replaced keep the old pool alive until they are
+/// released.
+///
+/// Loading the current pool only holds a lock for as long as it takes to clone
+/// the pool (an atomic reference count increment), so handles may be shared
+/// freely between threads.
+#[derive(Debug)]
+pub struct PoolHandle<P> {
+    pool: RwLock<P>,
+}
+
+impl<P> PoolHandle<P> {
+    pub fn new(pool: P) -> Self {
+        Self {
+            pool: RwLock::new(pool),
+        }
+    }
+
+    /// Returns the current pool.
+    pub fn load(&self) -> P
+    where
+        P: Clone,
+    {
+        self.pool.read().expect("pool handle poisoned").clone()
+    }
+
+    /// Replaces the current pool with `pool`.
+    pub fn store(&self, pool: P) {
+        drop(self.swap(pool));
+    }
+
+    /// Replaces the current pool with `pool`, returning the previous pool.
+    pub fn swap(&self, pool: P) -> P {
+        let mut lock = self.pool.write().expect("pool handle poisoned");
+        mem::replace(&mut *lock, pool)
+    }
+}
+
+#[cfg(feature = "fixed")]
+impl<T, const CAP: usize> PoolHandle<crate::fixed::Pool<T, CAP>> {
+    /// Replaces the current pool with `pool`, and then blocks until every
+    /// checkout from the previous pool has been released.
+    ///
+    /// Returns the previous pool.
+    pub fn swap_and_drain(&self, pool: crate::fixed::Pool<T, CAP>) -> crate::fixed::Pool<T, CAP> {
+        let old = self.swap(pool);
+        old.drain_when_idle();
+        old
+    }
+}
+
+#[cfg(feature = "growable")]
+impl<T, N> PoolHandle<crate::growable::Pool<T, N>> {
+    /// Replaces the current pool with `pool`, and then blocks until every
+    /// checkout from the previous pool has been released.
+    ///
+    /// Returns the previous pool.
+    pub fn swap_and_drain(&self, pool: crate::growable::Pool<T, N>) -> crate::growable::Pool<T, N> {
+        let old = self.swap(pool);
+        old.drain_when_idle();
+        old
+    }
+}
+
+impl<P> From<P> for PoolHandle<P> {
+    fn from(pool: P) -> Self {
+        Self::new(pool)
+    }
+}
+
+impl<P: Default> Default for PoolHandle<P> {
+    fn default() -> Self {
+        Self::new(P::default())
+    }
+}
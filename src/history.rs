@@ -0,0 +1,183 @@
+//! A record of a pool's recent events, for post-mortem debugging.
+//!
+//! This is only recorded when the `debug-history` feature is enabled.
+#![cfg_attr(not(feature = "debug-history"), allow(dead_code))]
+use crate::sync::atomic::{AtomicUsize, Ordering};
+use std::{fmt, sync::atomic};
+
+/// The number of events retained by a pool's history.
+const CAPACITY: usize = 64;
+
+/// Something that happened to a pool.
+///
+/// Returned by a pool's `recent_events` method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Event {
+    /// The position of this event in the pool's history. Each event a pool
+    /// records is numbered one higher than the previous one.
+    pub seq: usize,
+    /// What happened.
+    pub kind: EventKind,
+    /// A number identifying the thread on which the event happened.
+    ///
+    /// This is unique for each thread in the process, but is otherwise
+    /// unrelated to the thread's `ThreadId`.
+    pub thread: usize,
+}
+
+/// The kinds of [`Event`] that a pool records.
+///
+/// [`Event`]: struct.Event.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// The slot at index `idx` was checked out.
+    Checkout { idx: usize },
+    /// The slot at index `idx` was released back to the pool.
+    Release { idx: usize },
+    /// The slot at index `idx` was poisoned.
+    Poison { idx: usize },
+    /// The slot at index `idx` was repaired after being poisoned.
+    Repair { idx: usize },
+    /// The slot at index `idx` was marked as lost after its lease expired.
+    Lost { idx: usize },
+    /// The pool grew to `size` slots.
+    Grow { size: usize },
+    /// A checkout failed because there were no free slots.
+    Exhausted,
+    /// A checkout of the slot at index `idx` lost a race with another thread,
+    /// and had to be retried.
+    Contended { idx: usize },
+}
+
+/// A lock-free ring buffer of a pool's most recent events.
+pub(crate) struct History {
+    next: AtomicUsize,
+    entries: Box<[Entry]>,
+}
+
+/// An entry in the ring buffer.
+///
+/// Each entry is a small seqlock: `seq` is odd while the entry is being
+/// written, and is `2 * (pos + 1)` once the event at position `pos` has been
+/// written to it.
+struct Entry {
+    seq: AtomicUsize,
+    kind: AtomicUsize,
+    value: AtomicUsize,
+    thread: AtomicUsize,
+}
+
+// === impl EventKind ===
+
+impl EventKind {
+    fn encode(self) -> (usize, usize) {
+        match self {
+            EventKind::Checkout { idx } => (0, idx),
+            EventKind::Release { idx } => (1, idx),
+            EventKind::Poison { idx } => (2, idx),
+            EventKind::Repair { idx } => (3, idx),
+            EventKind::Lost { idx } => (4, idx),
+            EventKind::Grow { size } => (5, size),
+            EventKind::Exhausted => (6, 0),
+            EventKind::Contended { idx } => (7, idx),
+        }
+    }
+
+    fn decode(kind: usize, value: usize) -> Option<Self> {
+        Some(match kind {
+            0 => EventKind::Checkout { idx: value },
+            1 => EventKind::Release { idx: value },
+            2 => EventKind::Poison { idx: value },
+            3 => EventKind::Repair { idx: value },
+            4 => EventKind::Lost { idx: value },
+            5 => EventKind::Grow { size: value },
+            6 => EventKind::Exhausted,
+            7 => EventKind::Contended { idx: value },
+            _ => return None,
+        })
+    }
+}
+
+// === impl History ===
+
+impl History {
+    pub(crate) fn new() -> Self {
+        Self {
+            next: AtomicUsize::new(0),
+            entries: (0..CAPACITY).map(|_| Entry::new()).collect(),
+        }
+    }
+
+    pub(crate) fn record(&self, kind: EventKind) {
+        let pos = self.next.fetch_add(1, Ordering::SeqCst);
+        let entry = &self.entries[pos % CAPACITY];
+        let (kind, value) = kind.encode();
+        entry.seq.store(pos * 2 + 1, Ordering::SeqCst);
+        entry.kind.store(kind, Ordering::SeqCst);
+        entry.value.store(value, Ordering::SeqCst);
+        entry.thread.store(thread_id(), Ordering::SeqCst);
+        entry.seq.store((pos + 1) * 2, Ordering::SeqCst);
+    }
+
+    /// Returns the retained events, oldest first.
+    ///
+    /// Events that are being overwritten while the history is read are
+    /// skipped.
+    pub(crate) fn events(&self) -> Vec<Event> {
+        let end = self.next.load(Ordering::SeqCst);
+        let start = end.saturating_sub(CAPACITY);
+        (start..end)
+            .filter_map(|pos| {
+                let entry = &self.entries[pos % CAPACITY];
+                let seq = (pos + 1) * 2;
+                if entry.seq.load(Ordering::SeqCst) != seq {
+                    return None;
+                }
+                let kind = entry.kind.load(Ordering::SeqCst);
+                let value = entry.value.load(Ordering::SeqCst);
+                let thread = entry.thread.load(Ordering::SeqCst);
+                if entry.seq.load(Ordering::SeqCst) != seq {
+                    return None;
+                }
+                Some(Event {
+                    seq: pos,
+                    kind: EventKind::decode(kind, value)?,
+                    thread,
+                })
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for History {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("History")
+            .field("next", &self.next.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+// === impl Entry ===
+
+impl Entry {
+    fn new() -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            kind: AtomicUsize::new(0),
+            value: AtomicUsize::new(0),
+            thread: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// Returns a number identifying the current thread.
+fn thread_id() -> usize {
+    // This always uses `std`'s atomics, since it is shared by every pool.
+    static NEXT: atomic::AtomicUsize = atomic::AtomicUsize::new(0);
+    thread_local! {
+        static ID: usize = NEXT.fetch_add(1, atomic::Ordering::Relaxed);
+    }
+    ID.with(|id| *id)
+}
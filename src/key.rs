@@ -0,0 +1,63 @@
+/// A compact, copyable reference to a pooled item.
+///
+/// A `Key` identifies a slot in a pool, along with the generation of the
+/// checkout it was taken from. Keys are returned by checkouts' `key` methods,
+/// and may be turned back into a [`Shared`] checkout of the same item with the
+/// pool's `get` method, for as long as the item remains checked out.
+///
+/// Because a `Key` is only eight bytes and implements `Copy` (and, with the
+/// `serde` feature enabled, `Serialize` and `Deserialize`), it may be embedded
+/// in messages or queues that outlive the borrow of a checkout, as long as
+/// they stay within the current process. Keys are only meaningful to the pool
+/// that issued them.
+///
+/// Generations are 32 bits wide, so a key may be mistaken for a later
+/// checkout of the same slot if the slot is reused 2<sup>32</sup> times while
+/// the key is held.
+///
+/// [`Shared`]: fixed/struct.Shared.html
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(C)]
+pub struct Key {
+    index: u32,
+    generation: u32,
+}
+
+impl Key {
+    #[cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+    pub(crate) fn new(index: u32, generation: u32) -> Self {
+        Self { index, generation }
+    }
+
+    /// Returns the index of the slot this key refers to.
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+
+    /// Returns the generation of the checkout this key refers to.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    /// Packs this key into a `u64`, which may be turned back into the same key
+    /// with [`from_bits`].
+    ///
+    /// This allows a key to be used where an opaque integer ID is expected,
+    /// such as a `tracing` span ID.
+    ///
+    /// [`from_bits`]: #method.from_bits
+    pub fn to_bits(self) -> u64 {
+        (self.generation as u64) << 32 | self.index as u64
+    }
+
+    /// Unpacks a key that was packed into a `u64` with [`to_bits`].
+    ///
+    /// [`to_bits`]: #method.to_bits
+    pub fn from_bits(bits: u64) -> Self {
+        Self {
+            index: bits as u32,
+            generation: (bits >> 32) as u32,
+        }
+    }
+}
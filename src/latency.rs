@@ -0,0 +1,181 @@
+//! Histograms of how long checkouts and growth take.
+//!
+//! Timing is only recorded with the `timing-stats` feature enabled. Without
+//! it, a [`Timer`] is zero-sized, and recording one does nothing.
+#[cfg(feature = "timing-stats")]
+use crate::sync::atomic::{AtomicUsize, Ordering};
+#[cfg(feature = "timing-stats")]
+use std::{
+    convert::TryFrom,
+    time::{Duration, Instant},
+};
+
+/// Measures how long an operation on a pool takes.
+#[cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+#[derive(Debug)]
+pub(crate) struct Timer {
+    #[cfg(feature = "timing-stats")]
+    start: Instant,
+}
+
+/// The number of buckets in a [`LatencyHistogram`].
+#[cfg(feature = "timing-stats")]
+const LATENCY_BUCKETS: usize = 20;
+
+/// The upper bound of a [`LatencyHistogram`]'s first bucket, in nanoseconds,
+/// as a power of two.
+#[cfg(feature = "timing-stats")]
+const FIRST_BUCKET_SHIFT: u32 = 6;
+
+/// A histogram of how long an operation on a pool took.
+///
+/// Bucket 0 counts operations that took less than 64ns, and bucket `i`
+/// counts operations that took at least 64 × 2<sup>i - 1</sup>ns and less
+/// than 64 × 2<sup>i</sup>ns, so the buckets span from tens of nanoseconds to
+/// tens of milliseconds. The last bucket also counts every operation that
+/// took longer than that.
+///
+/// Only available with the `timing-stats` feature enabled.
+#[cfg(feature = "timing-stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    buckets: [usize; LATENCY_BUCKETS],
+}
+
+/// Histograms of how long a pool's checkouts and growth took.
+///
+/// Returned by a pool's `latency_stats` method. Only available with the
+/// `timing-stats` feature enabled.
+#[cfg(feature = "timing-stats")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LatencyStats {
+    /// How long blocking checkouts took, including any time spent retrying,
+    /// waiting for an item to be released, or growing the pool.
+    pub checkout: LatencyHistogram,
+    /// How long it took to grow the pool, including waiting for the pool's
+    /// lock. Fixed pools never grow, so this is always empty for them.
+    pub growth: LatencyHistogram,
+}
+
+/// The counters backing a pool's [`LatencyStats`].
+#[cfg(feature = "timing-stats")]
+#[derive(Debug)]
+pub(crate) struct Latencies {
+    checkout: Buckets,
+    growth: Buckets,
+}
+
+/// The counters backing a [`LatencyHistogram`].
+#[cfg(feature = "timing-stats")]
+#[derive(Debug)]
+struct Buckets([AtomicUsize; LATENCY_BUCKETS]);
+
+// === impl Timer ===
+
+impl Timer {
+    #[cfg(any(feature = "fixed", feature = "growable"))]
+    #[inline]
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(feature = "timing-stats")]
+            start: Instant::now(),
+        }
+    }
+}
+
+// === impl LatencyHistogram ===
+
+#[cfg(feature = "timing-stats")]
+impl LatencyHistogram {
+    /// Returns the number of operations in each bucket.
+    pub fn buckets(&self) -> &[usize] {
+        &self.buckets[..]
+    }
+
+    /// Returns the total number of operations recorded.
+    pub fn count(&self) -> usize {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the upper bound of the bucket at `idx`, or `None` for the last
+    /// bucket, which has no upper bound.
+    pub fn upper_bound(idx: usize) -> Option<Duration> {
+        if idx + 1 >= LATENCY_BUCKETS {
+            return None;
+        }
+        Some(Duration::from_nanos(1 << (FIRST_BUCKET_SHIFT + idx as u32)))
+    }
+
+    /// Returns the upper bound of the bucket containing the `q`th quantile
+    /// (between 0 and 1) of the recorded operations, or `None` if nothing was
+    /// recorded or that bucket is the last one.
+    ///
+    /// For example, `quantile(0.99)` is a bound on the 99th percentile
+    /// latency, accurate to within a factor of two.
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((count as f64) * q.clamp(0.0, 1.0)).ceil().max(1.0) as usize;
+        let mut seen = 0;
+        let idx = self.buckets.iter().position(|&n| {
+            seen += n;
+            seen >= rank
+        })?;
+        Self::upper_bound(idx)
+    }
+}
+
+// === impl Latencies ===
+
+#[cfg(feature = "timing-stats")]
+impl Latencies {
+    pub(crate) fn new() -> Self {
+        Self {
+            checkout: Buckets::new(),
+            growth: Buckets::new(),
+        }
+    }
+
+    pub(crate) fn record_checkout(&self, timer: &Timer) {
+        self.checkout.record(timer);
+    }
+
+    #[cfg(feature = "growable")]
+    pub(crate) fn record_growth(&self, timer: &Timer) {
+        self.growth.record(timer);
+    }
+
+    pub(crate) fn snapshot(&self) -> LatencyStats {
+        LatencyStats {
+            checkout: self.checkout.snapshot(),
+            growth: self.growth.snapshot(),
+        }
+    }
+}
+
+// === impl Buckets ===
+
+#[cfg(feature = "timing-stats")]
+impl Buckets {
+    fn new() -> Self {
+        Self(std::array::from_fn(|_| AtomicUsize::new(0)))
+    }
+
+    fn record(&self, timer: &Timer) {
+        let nanos = timer.start.elapsed().as_nanos();
+        let scaled = usize::try_from(nanos >> FIRST_BUCKET_SHIFT).unwrap_or(usize::MAX);
+        let bucket = (usize::BITS - scaled.leading_zeros()) as usize;
+        self.0[bucket.min(LATENCY_BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> LatencyHistogram {
+        let mut histogram = LatencyHistogram::default();
+        for (count, bucket) in histogram.buckets.iter_mut().zip(&self.0) {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+        histogram
+    }
+}
@@ -1,10 +1,47 @@
 #![deny(rust_2018_idioms)]
 
+#[cfg(all(feature = "tokio", any(feature = "fixed", feature = "growable")))]
+mod async_io;
+pub(crate) mod backoff;
+pub(crate) mod budget;
 pub(crate) mod builder;
+#[cfg(feature = "fixed")]
+pub mod classified;
+#[cfg(feature = "compat")]
+pub mod compat;
+pub(crate) mod config;
+#[cfg(feature = "fixed")]
 pub mod fixed;
+#[cfg(feature = "growable")]
 pub mod growable;
+pub(crate) mod handle;
+pub(crate) mod history;
+pub(crate) mod key;
+pub(crate) mod latency;
+pub mod raw;
+pub(crate) mod registry;
 
 pub(crate) mod slab;
 pub(crate) mod sync;
+pub mod testing;
+pub(crate) mod trace;
 pub mod traits;
-pub use {builder::Builder, traits::Clear};
+pub(crate) mod wakers;
+pub use {
+    budget::Budget,
+    builder::Builder,
+    config::{Config, GrowthConfig, WatermarksConfig},
+    handle::PoolHandle,
+    key::Key,
+    registry::dump_all,
+    slab::{
+        AuditReport, GrowError, Pressure, RetryHistogram, Reuse, Stats, StatsCursor, VerifyReport,
+        MAX_SLOTS,
+    },
+    traits::{AnyClear, AnyItem, Clear, ClearWith, PreTouch, Split},
+};
+
+#[cfg(feature = "debug-history")]
+pub use history::{Event, EventKind};
+#[cfg(feature = "timing-stats")]
+pub use latency::{LatencyHistogram, LatencyStats};
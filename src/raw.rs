@@ -0,0 +1,36 @@
+//! The building blocks of this crate's pools, for implementing new kinds of
+//! pool.
+//!
+//! Every pool is built on a [`Slab`]: a fixed set of [`Slot`]s, each holding
+//! one item, with a lock-free list of the free ones. A pool checks out a slot
+//! with [`Slab::try_checkout`], hands out a guard holding a reference to it,
+//! and drops that reference with [`Slot::drop_ref`] when the guard is
+//! dropped. Everything else (when to grow, whether to wait for a slot, what
+//! the guards look like) is up to the pool.
+//!
+//! # Safety
+//!
+//! A checked out slot is reached through the raw pointer returned by
+//! `try_checkout`, and reading a slot's item or changing its reference count
+//! (with [`Slot::item`], [`Slot::clone_ref`], [`Slot::drop_ref`] or
+//! [`Slot::poison`]) is `unsafe`, even for a slot looked up by index. The slab
+//! keeps a reference count for each slot, and these accesses are sound as long
+//! as the pool follows these rules:
+//!
+//! - The pointer is only valid until the slab is grown (with
+//!   [`Slab::grow_by`]) or dropped. A pool that grows must keep slots' indices
+//!   (see [`Slab::index_of`]) rather than pointers, and look slots up again
+//!   with [`Slab::slot`] after taking whatever lock it grows under.
+//! - A slot's item may only be accessed by a holder of one of its references.
+//!   Each reference is created by `try_checkout` or [`Slot::clone_ref`], and
+//!   must be dropped exactly once, by [`Slot::drop_ref`] (or
+//!   [`Slot::poison`]), with the [generation] the slot had when it was checked
+//!   out, on the slab it was checked out of.
+//! - The item may only be accessed mutably by the holder of a slot's _only_
+//!   reference. Once a second reference has been taken, the item may only be
+//!   shared.
+//!
+//! The slab itself never accesses a checked out slot's item.
+//!
+//! [generation]: struct.Slot.html#method.generation
+pub use crate::slab::{Error, Slab, Slot};
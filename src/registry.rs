@@ -0,0 +1,141 @@
+//! A process-wide registry of pools, for diagnostics.
+//!
+//! Pools are only added to the registry when their `register` method is
+//! called. The registry holds weak references, so registering a pool doesn't
+//! keep it alive, and pools that have been dropped are removed the next time
+//! the registry is dumped.
+use crate::{slab::Slab, sync::Weak};
+use std::{io, sync::Mutex};
+
+#[cfg(not(test))]
+static REGISTRY: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+struct Entry {
+    name: String,
+    pool: Pool,
+}
+
+/// A registered pool, which must be `Send` to be shared by the registry.
+///
+/// Under `loom`, slabs aren't `Send` or `Sync`, so in the crate's own tests,
+/// each thread has a registry of its own (see `lock`), and pools needn't be.
+#[cfg(not(test))]
+pub(crate) type Pool = Box<dyn Handle + Send>;
+#[cfg(test)]
+pub(crate) type Pool = Box<dyn Handle>;
+
+/// A pool's storage, which can describe itself in a diagnostics dump.
+pub(crate) trait Registered {
+    fn row(&self) -> Row;
+}
+
+/// A weak reference to a registered pool's storage.
+pub(crate) trait Handle {
+    fn strong_count(&self) -> usize;
+
+    /// Returns the pool's line in a diagnostics dump, unless it has been
+    /// dropped.
+    fn row(&self) -> Option<Row>;
+}
+
+/// A registered pool's line in a diagnostics dump.
+pub(crate) struct Row {
+    pub(crate) id: usize,
+    pub(crate) capacity: usize,
+    pub(crate) used: usize,
+    pub(crate) high_water: usize,
+    pub(crate) grows: usize,
+}
+
+/// Adds `pool` to the registry under `name`.
+#[cfg(any(feature = "fixed", feature = "growable"))]
+pub(crate) fn register(name: String, pool: Pool) {
+    lock().push(Entry { name, pool });
+}
+
+/// Writes a table describing every registered pool that still exists to
+/// `out`.
+///
+/// Each row shows a pool's name and id, its capacity, how many of its items
+/// are checked out, the most that have ever been checked out at once, and how
+/// many times slots have been added to it. This is intended for on-demand
+/// diagnostics, such as a `/debug/pools` endpoint; see the `register` method
+/// of [fixed] and [growable] pools.
+///
+/// The registry is locked while the table is written, so pools can't be
+/// registered until this returns.
+///
+/// [fixed]: fixed/struct.Pool.html#method.register
+/// [growable]: growable/struct.Pool.html#method.register
+pub fn dump_all(mut out: impl io::Write) -> io::Result<()> {
+    let mut registry = lock();
+    registry.retain(|entry| entry.pool.strong_count() > 0);
+    writeln!(
+        out,
+        "{:<24} {:>6} {:>10} {:>10} {:>10} {:>6}",
+        "name", "id", "capacity", "used", "high-water", "grows"
+    )?;
+    for entry in registry.iter() {
+        let row = match entry.pool.row() {
+            Some(row) => row,
+            None => continue,
+        };
+        writeln!(
+            out,
+            "{:<24} {:>6} {:>10} {:>10} {:>10} {:>6}",
+            entry.name, row.id, row.capacity, row.used, row.high_water, row.grows
+        )?;
+    }
+    Ok(())
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Vec<Entry>> {
+    #[cfg(test)]
+    thread_local! {
+        static REGISTRY: &'static Mutex<Vec<Entry>> = Box::leak(Box::default());
+    }
+    #[cfg(test)]
+    let registry = REGISTRY.with(|registry| *registry);
+    #[cfg(not(test))]
+    let registry = &REGISTRY;
+
+    // The registry is never left inconsistent, so a panic while it was held
+    // doesn't matter.
+    registry
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+// === impl Registered ===
+
+impl<T> Registered for Slab<T> {
+    fn row(&self) -> Row {
+        Row::new(self)
+    }
+}
+
+// === impl Handle ===
+
+impl<P: Registered> Handle for Weak<P> {
+    fn strong_count(&self) -> usize {
+        Weak::strong_count(self)
+    }
+
+    fn row(&self) -> Option<Row> {
+        self.upgrade().map(|pool| pool.row())
+    }
+}
+
+// === impl Row ===
+
+impl Row {
+    pub(crate) fn new<T>(slab: &Slab<T>) -> Self {
+        Self {
+            id: slab.id(),
+            capacity: slab.size(),
+            used: slab.used(),
+            high_water: slab.high_water(),
+            grows: slab.grows(),
+        }
+    }
+}
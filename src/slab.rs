@@ -1,220 +1,2534 @@
-use std::{ops::DerefMut, ptr};
+// Without either pool flavor, only the parts of the slab exposed through
+// `raw` are used.
+#![cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+pub(crate) use self::slots::Allocator;
+use self::slots::Slots;
+use std::{
+    alloc::{self, Layout},
+    convert::TryFrom,
+    error, fmt,
+    mem::ManuallyDrop,
+    ops::DerefMut,
+    ptr,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    sync::atomic::{AtomicUsize, Ordering},
+    history::EventKind,
+    key::Key,
+    latency::Timer,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Condvar, Mutex,
+    },
     traits::Clear,
+    wakers::Wakers,
 };
 
-#[derive(Debug)]
+mod slots;
+
+/// The storage underlying every pool: a fixed set of slots, and a lock-free
+/// list of the free ones.
+///
+/// See the [`raw`](../raw/index.html) module for how to build a pool on top of
+/// a `Slab`.
 pub struct Slab<T> {
-    inner: Vec<Slot<T>>,
+    inner: Slots<T>,
+    /// The index of the first slot added by each call to `grow_by`, in
+    /// order.
+    blocks: Vec<usize>,
+    /// A number identifying this slab, unique within the process.
+    id: usize,
+    /// The index of the first free slot, and a count of the changes made to
+    /// the free list's head (see `HEAD_INDEX`).
     head: AtomicUsize,
+    /// The order in which free slots are reused.
+    reuse: Reuse,
+    /// In round-robin mode, the index at which the next checkout starts
+    /// looking for a free slot.
+    cursor: AtomicUsize,
+    /// If `true`, items that weren't modified while they were checked out
+    /// aren't cleared before they are checked out again.
+    dirty_tracking: bool,
+    /// If set, items are cleared with this function when they are released,
+    /// rather than when they are checked out.
+    clear_on_release: Option<fn(&mut T)>,
+    /// Constructs the items of slots whose items were taken out, when they
+    /// are next checked out. If unset, the pool refills them itself (see
+    /// `refill`).
+    rebuild: OnceLock<fn() -> T>,
+    /// The number of slots reachable from the free list or currently checked
+    /// out.
+    ///
+    /// This is published only once new slots have been linked into the free
+    /// list, so that `used() + remaining()` never exceeds it.
+    size: AtomicUsize,
     used: AtomicUsize,
+    poisoned: AtomicUsize,
+    /// The number of blocking checkouts currently waiting for a slot.
+    waiters: AtomicUsize,
+    idle: Arc<Idle>,
+    lease: Option<Lease>,
+    watermarks: Watermarks,
+    on_release: OnRelease,
+    /// Only fixed pools have recyclers, but growable pools share the builder
+    /// code that threads one through.
+    #[cfg_attr(not(feature = "fixed"), allow(dead_code))]
+    recycle: Recycle<T>,
+    /// Asynchronous checkouts waiting for a slot to be released.
+    wakers: Wakers,
+    retries: Retries,
+    counters: Counters,
+    #[cfg(feature = "timing-stats")]
+    latencies: crate::latency::Latencies,
+    #[cfg(feature = "debug-history")]
+    history: crate::history::History,
+    /// How long a checkout may be held before it is traced.
+    #[cfg(feature = "tracing")]
+    trace_checkouts: Option<Duration>,
 }
 
-#[derive(Debug)]
+/// A slot in a slab, holding one item and the reference count of its
+/// current checkout.
+///
+/// The slot's item is stored first, so that aligning the slot aligns the
+/// item (see `Slots`).
+#[repr(C)]
 pub struct Slot<T> {
-    item: T,
-    idx: usize,
+    item: ManuallyDrop<T>,
     ref_count: AtomicUsize,
     next: AtomicUsize,
+    /// Incremented by `GENERATION_STEP` every time the slot is released, so
+    /// that checkouts of a reclaimed slot (and keys to a previous checkout)
+    /// can tell that it is no longer theirs. The `LOST` and `POISONED` bits
+    /// are set when the slot has been marked as lost or poisoned,
+    /// respectively.
+    generation: AtomicUsize,
+    /// The time (in milliseconds since the slab's lease epoch, plus one) at
+    /// which the current checkout's lease expires, or `NO_DEADLINE`.
+    deadline: AtomicUsize,
+    /// Non-zero once the current checkout has been downgraded to shared
+    /// references, so that it may be looked up by key.
+    shared: AtomicUsize,
+    /// Non-zero if the item may have been modified since it was last
+    /// cleared.
+    dirty: AtomicUsize,
+    /// Non-zero if the item has been taken out of the slot, which must be
+    /// refilled before it is handed out again.
+    vacant: AtomicUsize,
+}
+
+/// Counts a blocking checkout as waiting for a slot while it exists.
+#[cfg(feature = "fixed")]
+#[derive(Debug)]
+pub struct Waiting<'a, T> {
+    slab: &'a Slab<T>,
+}
+
+/// Configuration for a slab's lease mode.
+#[derive(Debug)]
+struct Lease {
+    duration: Duration,
+    epoch: Instant,
+    lost: AtomicUsize,
+}
+
+/// How close a pool is to running out of items.
+///
+/// A pool's pressure is determined by comparing the number of checked out
+/// items to the low and high watermarks set with [`Builder::with_watermarks`].
+///
+/// [`Builder::with_watermarks`]: struct.Builder.html#method.with_watermarks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Pressure {
+    /// Fewer items than the low watermark are checked out.
+    Low,
+    /// At least as many items as the low watermark, but fewer than the high
+    /// watermark, are checked out.
+    Medium,
+    /// At least as many items as the high watermark are checked out.
+    High,
+}
+
+/// The order in which a pool reuses its free items.
+///
+/// Set with [`Builder::with_reuse`].
+///
+/// [`Builder::with_reuse`]: struct.Builder.html#method.with_reuse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+#[non_exhaustive]
+pub enum Reuse {
+    /// The most recently released item is checked out next.
+    ///
+    /// This is the default. Recently used items are the most likely to still
+    /// be in the CPU's caches, and checking one out only touches the head of
+    /// the pool's free list.
+    Lifo,
+    /// Free items are checked out in turn, so that every item is reused about
+    /// as often as every other.
+    ///
+    /// This is useful when items wrap resources that wear out with use, such
+    /// as sessions that may only be used a limited number of times. Each
+    /// checkout scans the pool for a free item, starting after the item
+    /// checked out before it, so it becomes slower as the pool fills up.
+    RoundRobin,
+}
+
+/// Watermarks on the number of checked out slots, and a callback to notify
+/// when the slab's pressure changes.
+#[derive(Clone)]
+pub(crate) struct Watermarks {
+    low: usize,
+    high: usize,
+    on_change: Option<std::sync::Arc<dyn Fn(Pressure) + Send + Sync>>,
 }
 
+/// A callback to notify with a tagged checkout's tag when it is released.
+#[derive(Clone, Default)]
+pub(crate) struct OnRelease(Option<std::sync::Arc<dyn Fn(u64) + Send + Sync>>);
+
+/// A recycler that is handed each item released by an owned checkout, before
+/// the item is returned to the free list.
+///
+/// The recycler takes over the checkout's reference to the slot, and must
+/// eventually release it itself.
+pub(crate) struct Recycle<T>(Option<std::sync::Arc<RecycleFn<T>>>);
+
+type RecycleFn<T> = dyn Fn(&Arc<Slab<T>>, ptr::NonNull<Slot<T>>, usize) + Send + Sync;
+
+/// Why a slot could not be checked out of a [`Slab`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Error {
+    /// Every slot in the slab is checked out (or lost, or poisoned). A pool
+    /// that can grow should do so now.
     AtCapacity,
+    /// Another thread checked out the slot this thread was about to take.
+    /// Trying again will likely succeed.
     ShouldRetry,
 }
 
-// ===== impl Slot =====
+/// An error returned when a pool or [`Slab`] could not be grown.
+///
+/// Growing fails if the new slots can't be allocated, or if the pool would
+/// have more slots than fit in memory. The pool is left unchanged, so it
+/// remains usable at its current size.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrowError {
+    kind: GrowErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum GrowErrorKind {
+    TooManySlots,
+    OverBudget,
+    CapacityOverflow,
+    AllocFailed(Layout),
+    Reentrant,
+}
+
+/// A snapshot of where every slot in a pool is accounted for.
+///
+/// Returned by a pool's `audit` method. Every slot in a healthy pool is either
+/// checked out, on the free list, lost, or poisoned; slots that are none of
+/// these are [missing], and can never be checked out again.
+///
+/// Auditing a pool walks its free list, so the report is only guaranteed to
+/// be consistent if no items are checked out or released while it runs.
+///
+/// [missing]: #method.missing
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AuditReport {
+    /// The total number of slots in the pool.
+    pub size: usize,
+    /// The pool's count of checked out slots.
+    pub used: usize,
+    /// The number of slots that actually have outstanding checkouts.
+    pub checked_out: usize,
+    /// The number of slots reachable from the free list.
+    pub free: usize,
+    /// The number of slots marked as lost after their lease expired.
+    pub lost: usize,
+    /// The number of poisoned slots.
+    pub poisoned: usize,
+    /// The approximate number of checkouts currently waiting for a free slot.
+    pub waiters: usize,
+}
+
+/// The result of running a pool's `verify` method.
+///
+/// Verifying a pool checks out every free slot once, clears each item again
+/// to check that its [`Clear`] implementation retains the item's capacity,
+/// and then releases them all and [audits] the pool.
+///
+/// [`Clear`]: trait.Clear.html
+/// [audits]: struct.AuditReport.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct VerifyReport {
+    /// The number of slots that were checked out.
+    pub checked_out: usize,
+    /// The indices of slots that were checked out more than once.
+    pub duplicates: Vec<usize>,
+    /// The indices of slots whose items lost capacity when they were cleared.
+    pub shrunk: Vec<usize>,
+    /// An audit of the pool, taken after every slot was released.
+    pub audit: AuditReport,
+}
+
+/// The number of buckets in a [`RetryHistogram`].
+const RETRY_BUCKETS: usize = 8;
+
+/// A histogram of how many times checkouts from a pool had to retry before
+/// they succeeded.
+///
+/// Returned by a pool's `retry_histogram` method. Bucket 0 counts checkouts
+/// that succeeded on the first attempt, and bucket `i` counts checkouts that
+/// retried at least 2<sup>i - 1</sup> and fewer than 2<sup>i</sup> times. The
+/// last bucket also counts every checkout that retried more often than that.
+///
+/// Only blocking checkouts are recorded. A checkout retries when it races with
+/// another thread for the same free slot, or (for fixed pools) when the pool
+/// is exhausted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetryHistogram {
+    buckets: [usize; RETRY_BUCKETS],
+}
+
+/// The counters backing a [`RetryHistogram`].
+#[derive(Debug)]
+struct Retries {
+    buckets: [AtomicUsize; RETRY_BUCKETS],
+}
+
+/// Counts of a pool's activity since it was created, or since a previous
+/// call to its `stats_delta` method.
+///
+/// Returned by a pool's `stats` and `stats_delta` methods.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Stats {
+    /// The number of items checked out.
+    pub checkouts: usize,
+    /// The number of items released back to the pool.
+    pub releases: usize,
+    /// The number of slots poisoned because a checkout was dropped while
+    /// panicking.
+    pub poisoned: usize,
+    /// The number of slots marked as lost after their lease expired.
+    pub lost: usize,
+    /// The number of blocking checkouts that had to retry at least once.
+    pub contended: usize,
+    /// The number of items detached from the pool, whose slots were given a
+    /// new item in their place.
+    pub detached: usize,
+}
+
+/// Remembers the [`Stats`] returned by the last call to a pool's
+/// `stats_delta` method, so that the next call returns only the counts since
+/// then.
+///
+/// A new cursor has seen nothing, so the first call with it returns the same
+/// counts as `stats`.
+///
+/// [`Stats`]: struct.Stats.html
+#[derive(Debug, Clone, Default)]
+pub struct StatsCursor {
+    last: Stats,
+}
+
+/// The cumulative counters backing a slab's [`Stats`].
+///
+/// These are only ever incremented, with relaxed ordering, so that keeping
+/// them costs the hot path as little as possible. Since they don't order
+/// anything, they always use `std`'s atomics, so that `loom` doesn't explore
+/// their interleavings.
+#[derive(Debug)]
+struct Counters {
+    checkouts: StatAtomic,
+    releases: StatAtomic,
+    poisoned: StatAtomic,
+    detached: StatAtomic,
+    /// The largest number of slots that have been checked out at once.
+    high_water: StatAtomic,
+}
+
+type StatAtomic = std::sync::atomic::AtomicUsize;
+
+/// Marks the end of the free list.
+const EMPTY: usize = usize::MAX;
+
+/// In round-robin mode, slots aren't linked into a free list. Instead, a free
+/// slot's `next` is `FREE`, and a checked out slot's is `TAKEN`.
+const FREE: usize = EMPTY - 1;
+const TAKEN: usize = EMPTY - 2;
+
+/// The bits of the free list's head that hold the index of the first free
+/// slot, or all ones if there are no free slots.
+///
+/// The rest of the head counts the changes made to it. A checkout pops a slot
+/// by swapping the head it loaded for the slot's `next`, but by the time it
+/// does, the slot may have been checked out, released, and pushed again, with
+/// a different `next` (the ABA problem). Every push and pop changes the count,
+/// so the stale head no longer matches.
+#[cfg(target_pointer_width = "64")]
+const HEAD_INDEX: usize = u32::MAX as usize;
+#[cfg(not(target_pointer_width = "64"))]
+const HEAD_INDEX: usize = (1 << 24) - 1;
+
+/// Returns the index of the first free slot in `head`, or `EMPTY`.
+#[inline]
+fn head_index(head: usize) -> usize {
+    match head & HEAD_INDEX {
+        HEAD_INDEX => EMPTY,
+        idx => idx,
+    }
+}
+
+/// Returns the head that replaces `head` to make `idx` (or `EMPTY`) the first
+/// free slot.
+#[inline]
+fn next_head(head: usize, idx: usize) -> usize {
+    (head & !HEAD_INDEX).wrapping_add(HEAD_INDEX + 1) | (idx & HEAD_INDEX)
+}
+
+/// The largest number of slots a pool may have.
+///
+/// Every slot's index must fit in a [`Key`], and must be distinct from the
+/// markers that the free list uses in place of an index. Growing a pool past
+/// this many slots fails with a [`GrowError`].
+///
+/// [`Key`]: struct.Key.html
+/// [`GrowError`]: struct.GrowError.html
+pub const MAX_SLOTS: usize = {
+    let key_max = u32::MAX as usize;
+    if key_max < HEAD_INDEX {
+        key_max
+    } else {
+        HEAD_INDEX
+    }
+};
+
+/// A checked out slot's reference count while its only reference is being
+/// upgraded (see `Slot::try_unshare`). No new references may be taken to the
+/// slot while it has this count, and it's never released.
+const UNSHARING: usize = usize::MAX;
+
+/// A free slot's reference count while a checkout that found it at the head
+/// of the free list tries to pop it (see `Slot::try_claim`). No references
+/// may be taken to the slot while it has this count, and it's never
+/// released: if the pop fails, the count goes back to zero.
+const CLAIMED: usize = UNSHARING - 1;
+
+const NO_DEADLINE: usize = 0;
+const LOST: usize = 0b01;
+const POISONED: usize = 0b10;
+const GENERATION_STEP: usize = 0b100;
+
+/// Tracks threads waiting for a slab to become idle (i.e. for every slot to be
+/// released).
+///
+/// This is kept behind an `Arc` so that pools which guard their slab with a
+/// lock can wait for (and notify) idleness without holding that lock.
+#[derive(Debug)]
+pub struct Idle {
+    // This always uses `std`'s atomics, like the mutex and condvar below.
+    waiters: std::sync::atomic::AtomicUsize,
+    lock: Mutex<()>,
+    cv: Condvar,
+}
+
+// ===== impl Slab =====
+
+impl<T> Default for Slab<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl<T> Slab<T> {
+    /// Returns a new slab with no slots.
     pub fn new() -> Self {
+        Self::with_alignment(1)
+    }
+
+    /// Returns an empty slab whose items are aligned to (at least) `align`
+    /// bytes.
+    pub fn with_alignment(align: usize) -> Self {
+        // This always uses `std`'s atomics, since it's shared by every slab.
+        static NEXT_ID: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(1);
         Slab {
-            inner: Vec::new(),
-            head: AtomicUsize::new(0),
+            inner: Slots::with_alignment(align),
+            blocks: Vec::new(),
+            id: NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+            head: AtomicUsize::new(EMPTY),
+            reuse: Reuse::Lifo,
+            cursor: AtomicUsize::new(0),
+            dirty_tracking: false,
+            clear_on_release: None,
+            rebuild: OnceLock::new(),
+            size: AtomicUsize::new(0),
             used: AtomicUsize::new(0),
+            poisoned: AtomicUsize::new(0),
+            waiters: AtomicUsize::new(0),
+            idle: Arc::new(Idle::new()),
+            lease: None,
+            watermarks: Watermarks::default(),
+            on_release: OnRelease::default(),
+            recycle: Recycle::default(),
+            wakers: Wakers::new(),
+            retries: Retries::new(),
+            counters: Counters::new(),
+            #[cfg(feature = "timing-stats")]
+            latencies: crate::latency::Latencies::new(),
+            #[cfg(feature = "debug-history")]
+            history: crate::history::History::new(),
+            #[cfg(feature = "tracing")]
+            trace_checkouts: None,
         }
     }
 
+    /// Allocates the slab's slots with `alloc`.
+    ///
+    /// This must be called before the slab first grows.
+    pub(crate) fn set_allocator(&mut self, alloc: Allocator) {
+        self.inner.set_allocator(alloc);
+    }
+
+    /// Sets the order in which free slots are reused.
+    ///
+    /// This must be called before the slab first grows.
+    pub fn set_reuse(&mut self, reuse: Reuse) {
+        debug_assert_eq!(self.inner.len(), 0, "reuse must be set before growing");
+        self.reuse = reuse;
+    }
+
+    /// Skips clearing items that weren't modified while they were checked
+    /// out.
+    pub(crate) fn set_dirty_tracking(&mut self) {
+        self.dirty_tracking = true;
+    }
+
+    /// Clears items with `clear` when they are released, rather than when
+    /// they are checked out.
+    pub(crate) fn set_clear_on_release(&mut self, clear: fn(&mut T)) {
+        self.clear_on_release = Some(clear);
+    }
+
+    /// Refills slots whose items were taken out by calling `rebuild` when
+    /// they are next checked out.
+    ///
+    /// Only the first function set is used.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn rebuild_with(&self, rebuild: fn() -> T) {
+        self.rebuild.get_or_init(|| rebuild);
+    }
+
+    /// Traces checkouts that are held for longer than `threshold`, or that are
+    /// released on another thread.
+    #[cfg(feature = "tracing")]
+    pub(crate) fn set_trace_checkouts(&mut self, threshold: Duration) {
+        self.trace_checkouts = Some(threshold);
+    }
+
+    /// Starts tracing a new checkout, if the `tracing` feature is enabled and
+    /// checkouts from this slab are traced.
+    #[inline]
+    pub(crate) fn trace_checkout(&self) -> crate::trace::Checkout {
+        #[cfg(feature = "tracing")]
+        return crate::trace::Checkout::start(self.trace_checkouts);
+        #[cfg(not(feature = "tracing"))]
+        crate::trace::Checkout::start(None)
+    }
+
+    /// Records an event in the slab's history, if the `debug-history` feature
+    /// is enabled.
+    #[inline]
+    fn record(&self, _event: EventKind) {
+        #[cfg(feature = "debug-history")]
+        self.history.record(_event);
+    }
+
+    /// Returns the slab's recent events, oldest first.
+    #[cfg(feature = "debug-history")]
+    pub fn recent_events(&self) -> Vec<crate::history::Event> {
+        self.history.events()
+    }
+
+    /// Records that a blocking checkout, timed by `timer`, retried `retries`
+    /// times.
+    pub(crate) fn record_checkout(&self, retries: usize, timer: &Timer) {
+        self.retries.record(retries);
+        #[cfg(feature = "timing-stats")]
+        self.latencies.record_checkout(timer);
+        #[cfg(not(feature = "timing-stats"))]
+        let _ = timer;
+    }
+
+    /// Records that growing the slab took as long as `timer` has been running.
+    #[cfg(feature = "growable")]
+    pub(crate) fn record_growth(&self, timer: &Timer) {
+        #[cfg(feature = "timing-stats")]
+        self.latencies.record_growth(timer);
+        #[cfg(not(feature = "timing-stats"))]
+        let _ = timer;
+    }
+
+    /// Returns histograms of how long checkouts from, and growth of, this
+    /// slab took, as recorded by the pools that use it.
+    #[cfg(feature = "timing-stats")]
+    pub fn latency_stats(&self) -> crate::LatencyStats {
+        self.latencies.snapshot()
+    }
+
+    /// Returns a histogram of how many times blocking checkouts have retried,
+    /// as recorded by the pools that use this slab.
+    pub fn retry_histogram(&self) -> RetryHistogram {
+        self.retries.snapshot()
+    }
+
+    /// Returns the counts of this slab's activity since it was created.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            checkouts: self.counters.checkouts.load(Ordering::Relaxed),
+            releases: self.counters.releases.load(Ordering::Relaxed),
+            poisoned: self.counters.poisoned.load(Ordering::Relaxed),
+            lost: self.lost(),
+            contended: self.retries.snapshot().contended(),
+            detached: self.counters.detached.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Records that a checked out item was detached from the slab, and
+    /// replaced with a new one.
+    pub(crate) fn record_detach(&self) {
+        self.counters.detached.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the largest number of slots that have been checked out at
+    /// once.
+    pub fn high_water(&self) -> usize {
+        self.counters.high_water.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of times slots have been added to the slab.
+    pub(crate) fn grows(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Returns the counts of this slab's activity since the last call with
+    /// `cursor`, and advances `cursor` past them.
+    pub fn stats_delta(&self, cursor: &mut StatsCursor) -> Stats {
+        cursor.advance(self.stats())
+    }
+
+    /// Returns a slab whose only slot holds `item`, and is already checked
+    /// out.
+    ///
+    /// The slot has a single reference, which belongs to the caller.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn pinned(item: T) -> Self {
+        let mut item = Some(item);
+        let slab = Self::from_fn(1, &mut || item.take().expect("only one item"));
+        let slot = &slab.inner[0];
+        slot.try_acquire()
+            .expect("a new slot cannot already be checked out");
+        let head = slab.head.load(Ordering::Relaxed);
+        slab.head
+            .store(next_head(head, slot.next()), Ordering::Release);
+        slab.add_used();
+        slab
+    }
+
+    pub(crate) fn set_watermarks(&mut self, watermarks: Watermarks) {
+        self.watermarks = watermarks;
+    }
+
+    pub(crate) fn set_on_release(&mut self, on_release: OnRelease) {
+        self.on_release = on_release;
+    }
+
+    #[cfg(feature = "fixed")]
+    pub(crate) fn set_recycle(&mut self, recycle: Recycle<T>) {
+        self.recycle = recycle;
+    }
+
+    /// Drops the caller's reference to `slot`, which it holds at
+    /// `generation`, or hands it to the recycler if it's the last one.
+    /// Returns `false`, without dropping the reference, if there is no
+    /// recycler.
+    ///
+    /// If this returns `true`, the caller's reference was either dropped, or
+    /// now belongs to the recycler, so the caller must not release it.
+    #[inline]
+    #[cfg(feature = "fixed")]
+    pub(crate) fn recycle(
+        this: &Arc<Self>,
+        slot: ptr::NonNull<Slot<T>>,
+        generation: usize,
+    ) -> bool {
+        match this.recycle.0 {
+            // A vacant slot has no item to recycle, so it's just released.
+            Some(ref recycle) if !unsafe { slot.as_ref() }.is_vacant() => {
+                // Another checkout still refers to the item, so it isn't being
+                // released yet.
+                if !unsafe { slot.as_ref() }.drop_ref_unless_last() {
+                    recycle(this, slot, generation);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Notifies the release callback, if there is one, that a checkout tagged
+    /// with `tag` is being released.
+    #[inline]
+    pub(crate) fn notify_release(&self, tag: u64) {
+        if let Some(ref on_release) = self.on_release.0 {
+            on_release(tag);
+        }
+    }
+
+    /// Returns the slab's current pressure.
+    pub(crate) fn pressure(&self) -> Pressure {
+        self.watermarks.pressure(self.used())
+    }
+
+    fn add_used(&self) {
+        let used = self.used.fetch_add(1, Ordering::Relaxed);
+        self.counters.checkouts.fetch_add(1, Ordering::Relaxed);
+        // Only contend on the high-water mark when it's actually exceeded.
+        let mut high_water = self.counters.high_water.load(Ordering::Relaxed);
+        while used + 1 > high_water {
+            match self.counters.high_water.compare_exchange(
+                high_water,
+                used + 1,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => high_water = actual,
+            }
+        }
+        self.watermarks.update(used, used + 1);
+    }
+
+    /// Decrements the count of used slots by `n`, returning `true` if this
+    /// left the slab idle.
+    fn sub_used(&self, n: usize) -> bool {
+        let used = self.used.fetch_sub(n, Ordering::SeqCst);
+        self.watermarks.update(used, used - n);
+        used == n
+    }
+
+    /// Decrements the count of used slots by `n`, once that many slots have
+    /// been pushed onto the free list, and wakes as many asynchronous
+    /// checkouts waiting for them. Returns `true` if this left the slab idle.
+    fn sub_released(&self, n: usize) -> bool {
+        // `sub_used` is `SeqCst`, so a task that registers its waker either
+        // is seen by `wake`, or sees the released slots when it checks again.
+        let idle = self.sub_used(n);
+        self.wakers.wake(n);
+        idle
+    }
+
+    /// Enables lease mode, in which each checkout must be released within
+    /// `duration` or it may be marked as lost by `reclaim_expired`.
+    pub(crate) fn set_lease(&mut self, duration: Duration) {
+        self.lease = Some(Lease {
+            duration,
+            epoch: Instant::now(),
+            lost: AtomicUsize::new(0),
+        });
+    }
+
+    /// Returns a new slab with `cap` free slots, whose items are constructed
+    /// by `new`.
     pub fn from_fn(cap: usize, new: &mut impl FnMut() -> T) -> Self {
         let mut this = Self::new();
         this.grow_by(cap, new);
         this
     }
 
+    /// Adds `cap` free slots to the slab, whose items are constructed by
+    /// `new`.
+    ///
+    /// Growing the slab may move its slots, so any pointers to slots returned
+    /// by [`try_checkout`](#method.try_checkout) must not be used afterwards.
+    ///
+    /// # Panics
+    ///
+    /// If the slab's capacity would overflow. If the slots can't be
+    /// allocated, the process is aborted, like when a `Vec` fails to grow.
     pub fn grow_by(&mut self, cap: usize, new: &mut impl FnMut() -> T) {
+        if let Err(error) = self.try_grow_by(cap, new) {
+            error.raise();
+        }
+    }
+
+    /// Adds `cap` free slots to the slab like [`grow_by`], but returns an
+    /// error if they can't be allocated.
+    ///
+    /// If this fails, `new` is never called and the slab is unchanged.
+    ///
+    /// [`grow_by`]: #method.grow_by
+    pub fn try_grow_by(
+        &mut self,
+        cap: usize,
+        new: &mut impl FnMut() -> T,
+    ) -> Result<(), GrowError> {
+        if cap == 0 {
+            return Ok(());
+        }
+
+        self.check_growth(cap)?;
         let next = self.inner.len();
+        self.blocks
+            .try_reserve(1)
+            .map_err(|_| GrowError::alloc_failed(Layout::new::<usize>()))?;
+        self.inner.try_extend(cap, |i| Slot::new(new(), i))?;
+        self.blocks.push(next);
+
+        if self.reuse == Reuse::RoundRobin {
+            for slot in self.inner.iter().skip(next) {
+                slot.next.store(FREE, Ordering::Release);
+            }
+            self.size.store(self.inner.len(), Ordering::Release);
+            self.record(EventKind::Grow {
+                size: self.inner.len(),
+            });
+            self.wakers.wake(cap);
+            return Ok(());
+        }
+
+        // Slots may have been released since the caller observed that the
+        // slab was at capacity, so the free list may not be empty. Link the
+        // last new slot to the current head (which is `EMPTY` if it is),
+        // rather than dropping whatever is already on the free list.
+        let head = self.head.load(Ordering::Acquire);
+        self.inner[next + cap - 1]
+            .next
+            .store(head_index(head), Ordering::Relaxed);
+        self.head.store(next_head(head, next), Ordering::Release);
+
+        // Only publish the new size once the new slots are reachable.
+        self.size.store(self.inner.len(), Ordering::Release);
+        self.record(EventKind::Grow {
+            size: self.inner.len(),
+        });
+        self.wakers.wake(cap);
+        Ok(())
+    }
+
+    /// Returns an error if growing the slab by `cap` slots would leave it with
+    /// more than `MAX_SLOTS` slots.
+    pub(crate) fn check_growth(&self, cap: usize) -> Result<(), GrowError> {
+        if cap > MAX_SLOTS - self.inner.len() {
+            return Err(GrowError::too_many_slots());
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bytes each slot takes up in the slab's array,
+    /// including any padding needed to align it.
+    #[cfg(feature = "growable")]
+    pub(crate) fn slot_bytes(&self) -> usize {
+        self.inner.stride()
+    }
 
-        // Avoid multiple allocations.
-        self.inner.reserve(cap);
-        for i in next..next + cap {
-            self.inner.push(Slot::new(new(), i));
+    /// Returns the block that the slot at `idx` was added to the slab in, or
+    /// `None` if there is no such slot.
+    ///
+    /// Each call to `grow_by` adds a new block, numbered from zero.
+    #[cfg(feature = "growable")]
+    pub(crate) fn block_of(&self, idx: usize) -> Option<usize> {
+        if idx >= self.inner.len() {
+            return None;
         }
+        Some(self.blocks.partition_point(|&start| start <= idx) - 1)
+    }
+
+    /// Consumes the slab, returning all of its items in slot order.
+    ///
+    /// Slots whose items were taken out, and which haven't been refilled
+    /// since, hold no item, and are skipped.
+    pub fn into_items(self) -> Vec<T> {
+        self.inner
+            .into_vec()
+            .into_iter()
+            .filter_map(Slot::into_item)
+            .collect()
+    }
 
-        self.head.store(next, Ordering::Release);
+    /// Returns a number identifying this slab, which is unique within the
+    /// process.
+    pub fn id(&self) -> usize {
+        self.id
     }
 
+    /// Returns the number of slots in the slab.
     pub fn size(&self) -> usize {
-        self.inner.len()
+        self.size.load(Ordering::Acquire)
     }
 
+    /// Returns the number of slots that are currently checked out.
     pub fn used(&self) -> usize {
-        self.used.load(Ordering::Relaxed)
+        self.used.load(Ordering::Acquire)
     }
 
+    /// Returns the number of slots that may currently be checked out.
     pub fn remaining(&self) -> usize {
-        self.size() - self.used()
+        // Load the counters that may decrease before the size, which only
+        // ever increases. Slots may still be checked out or released between
+        // these loads, so saturate rather than underflowing if the snapshot
+        // is momentarily inconsistent.
+        let used = self.used() + self.lost() + self.poisoned();
+        self.size().saturating_sub(used)
     }
 
-    pub fn slot(&self, idx: usize) -> &Slot<T> {
-        &self.inner[idx]
+    /// Returns the number of slots that have been marked as lost after their
+    /// lease expired.
+    pub(crate) fn lost(&self) -> usize {
+        self.lease
+            .as_ref()
+            .map(|lease| lease.lost.load(Ordering::Acquire))
+            .unwrap_or(0)
     }
 
-    pub fn assert_valid(&self) {
-        let used = self.used.load(Ordering::SeqCst);
-        let mut actual_used = 0;
-        for (idx, slot) in self.inner.iter().enumerate() {
-            assert_eq!(
-                slot.idx, idx,
-                "invariant violated: slot index did not match actual slab index",
+    /// Returns the number of slots that have been poisoned because a checkout
+    /// was dropped while panicking.
+    pub fn poisoned(&self) -> usize {
+        self.poisoned.load(Ordering::Acquire)
+    }
+
+    /// Calls `replace` on the item in every free slot for which `keep`
+    /// returns `false`, returning the number of items replaced.
+    ///
+    /// Slots that are checked out, lost, poisoned, or vacant are skipped.
+    /// Taking the slab mutably ensures that no slot is checked out or
+    /// released while this runs. Replaced items are cleared when they are
+    /// next checked out, like new ones.
+    #[cfg(feature = "growable")]
+    pub(crate) fn retain_free(
+        &mut self,
+        mut keep: impl FnMut(&T) -> bool,
+        mut replace: impl FnMut(&mut T),
+    ) -> usize {
+        let mut replaced = 0;
+        for slot in self.inner.iter_mut() {
+            let free = slot.ref_count(Ordering::Acquire) == 0
+                && slot.generation() & (LOST | POISONED) == 0
+                && !slot.is_vacant();
+            if free && !keep(&slot.item) {
+                replace(slot.item_mut());
+                replaced += 1;
+            }
+        }
+        replaced
+    }
+
+    /// Returns the indices of all currently poisoned slots.
+    pub fn poisoned_slots(&self) -> Vec<usize> {
+        self.inner
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_poisoned())
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Drops one reference to each of `slots`, releasing every slot whose last
+    /// reference was dropped back to the free list at once.
+    ///
+    /// Each slot is paired with the generation observed when it was checked
+    /// out. Returns `true` if releasing the slots left the slab idle, as with
+    /// [`Slot::drop_ref`].
+    #[must_use]
+    pub(crate) fn release_all<'a>(
+        &'a self,
+        slots: impl IntoIterator<Item = (&'a Slot<T>, usize)>,
+    ) -> bool {
+        // Link the released slots into a chain, so that the whole chain can be
+        // pushed onto the free list with a single compare-and-swap.
+        let mut chain: Option<(&Slot<T>, &Slot<T>)> = None;
+        let mut released = 0;
+        for (slot, generation) in slots {
+            debug_assert!(
+                self.inner.owns(slot),
+                "a slot was released to pool {}, which it does not belong to",
+                self.id,
             );
-            if self.head.load(Ordering::SeqCst) == idx {
-                assert_eq!(
-                    slot.ref_count(Ordering::SeqCst),
-                    0,
-                    "invariant violated: head slot had non-zero ref count",
-                );
+            if !slot.unlink(self, generation, false) {
+                continue;
             }
-            slot.assert_valid();
-            if slot.ref_count(Ordering::SeqCst) > 0 {
-                actual_used += 1;
+            released += 1;
+            chain = match chain {
+                None => Some((slot, slot)),
+                Some((first, last)) => {
+                    slot.next.store(self.index_of(first), Ordering::Relaxed);
+                    Some((slot, last))
+                }
+            };
+        }
+
+        match chain {
+            Some((first, last)) => {
+                self.push_free_list(first, last);
+                self.sub_released(released)
             }
+            None => false,
         }
-        assert!(
-            self.head.load(Ordering::SeqCst) <= self.size(),
-            "invariant violated: free list head should not point past the end of the slab",
-        );
+    }
 
-        if used == self.used.load(Ordering::SeqCst) {
-            assert_eq!(
-                used, actual_used,
-                "invariant violated: used did not equal number of slots with non-zero ref counts",
-            );
+    /// Pushes a chain of slots, linked through their `next` indices from
+    /// `first` to `last`, onto the free list.
+    fn push_free_list(&self, first: &Slot<T>, last: &Slot<T>) {
+        if self.reuse == Reuse::RoundRobin {
+            // There's no free list to push onto; mark each slot in the chain
+            // as free instead. A slot may be checked out again as soon as it
+            // is marked free, so read the link to the next slot first.
+            let mut slot = first;
+            loop {
+                let next = slot.next();
+                slot.next.store(FREE, Ordering::Release);
+                if ptr::eq(slot, last) {
+                    return;
+                }
+                slot = &self.inner[next];
+            }
+        }
+
+        let first = self.index_of(first);
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            // Link the chain to the current head before publishing it. The
+            // head's `Release` publishes the link to checkouts that `Acquire`
+            // the head. A checkout may already have claimed one of these
+            // slots, having loaded the head before the slot was last popped,
+            // and may read a `next` from before or after this store. Either
+            // way, pushing the chain changes the head, so that pop fails.
+            last.next.store(head_index(head), Ordering::Relaxed);
+            match self.head.compare_exchange(
+                head,
+                next_head(head, first),
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(actual) => head = actual,
+            }
         }
     }
-}
 
-impl<T> Slab<T>
-where
-    T: Clear,
-{
-    pub fn try_checkout(&self) -> Result<ptr::NonNull<Slot<T>>, Error> {
-        // The slab's free list is a modification of Treiber's lock-free stack,
-        // using slab indices instead of pointers, and with a provison for
-        // growing the slab when needed.
-        //
-        // In order to check out an item from the slab, we "pop" the next free
-        // slot from the stack.
-        let idx = self.head.load(Ordering::Acquire);
+    /// Returns `true` if `item` points to an item stored in one of this
+    /// slab's slots.
+    pub fn contains(&self, item: *const T) -> bool {
+        self.slot_of(item).is_some()
+    }
 
-        // Can we insert without reallocating?
+    /// Returns the slot holding the item that `item` points to, if it is one
+    /// of this slab's.
+    pub fn slot_of(&self, item: *const T) -> Option<&Slot<T>> {
+        let start = self.inner.as_ptr() as usize;
+        let stride = self.inner.stride();
+        let addr = item as usize;
+        if addr < start || addr >= start + self.inner.len() * stride {
+            return None;
+        }
+        let slot = &self.inner[(addr - start) / stride];
+        if ptr::eq(&*slot.item, item) {
+            Some(slot)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a poisoned slot to the free list.
+    ///
+    /// Returns `false` if the slot at `idx` is not poisoned (or does not
+    /// exist).
+    pub fn repair(&self, idx: usize) -> bool {
+        let slot = match self.inner.get(idx) {
+            Some(slot) => slot,
+            None => return false,
+        };
+        let generation = slot.generation();
+        if generation & POISONED == 0 {
+            return false;
+        }
+        let repaired = (generation & !POISONED).wrapping_add(GENERATION_STEP);
+        if slot
+            .generation
+            .compare_exchange(generation, repaired, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            // Someone else repaired the slot first.
+            return false;
+        }
+
+        // Put the slot back on the free list *before* it stops being counted
+        // as poisoned, so that `remaining` never overestimates the free
+        // capacity.
+        slot.push_free(self);
+        self.poisoned.fetch_sub(1, Ordering::Release);
+        // Like `sub_released`, make the slot's release visible to a task that
+        // registers a waker before waking any.
+        std::sync::atomic::fence(Ordering::SeqCst);
+        self.wakers.wake(1);
+        self.record(EventKind::Repair { idx });
+        true
+    }
+
+    /// Walks the free list and every slot, reporting where each slot is
+    /// accounted for.
+    pub fn audit(&self) -> AuditReport {
         let len = self.inner.len();
+        let mut free = 0;
+        if self.reuse == Reuse::RoundRobin {
+            free = self
+                .inner
+                .iter()
+                .filter(|slot| slot.next.load(Ordering::Acquire) == FREE)
+                .count();
+        }
+        let mut idx = head_index(self.head.load(Ordering::Acquire));
+        // Bound the walk, in case the free list contains a cycle. A cycle will
+        // show up as more free slots than the pool has room for.
+        while idx < len && free <= len {
+            free += 1;
+            idx = self.inner[idx].next();
+        }
 
-        // println!("try_checkout head={:?}; len={:?}", idx, len);
-        if idx >= len {
-            return Err(Error::AtCapacity);
+        let checked_out = self
+            .inner
+            .iter()
+            .filter(|slot| {
+                let refs = slot.ref_count(Ordering::Acquire);
+                // A claimed slot is still free, and was counted above.
+                refs > 0 && refs != CLAIMED && !slot.is_lost() && !slot.is_poisoned()
+            })
+            .count();
+
+        AuditReport {
+            size: len,
+            used: self.used(),
+            checked_out,
+            free,
+            lost: self.lost(),
+            poisoned: self.poisoned(),
+            waiters: self.waiters(),
         }
+    }
 
-        // If someone else has locked the slot, bail and try again.
-        let slot = &self.inner[idx];
-        let mut lease = slot.try_acquire()?;
-        let next = slot.next();
+    /// Marks every checked out slot whose lease has expired as lost.
+    ///
+    /// Lost slots are never returned to the free list, and dropping a
+    /// checkout of a lost slot does nothing. Returns the number of slots that
+    /// were marked as lost by this call.
+    pub(crate) fn reclaim_expired(&self) -> usize {
+        let lease = match self.lease {
+            Some(ref lease) => lease,
+            None => return 0,
+        };
+        let now = lease.now();
+        let mut reclaimed = 0;
+        for (idx, slot) in self.inner.iter().enumerate() {
+            // Load the generation *before* the deadline: releasing a slot
+            // clears its deadline before bumping the generation, so if the
+            // slot has since been released and checked out again, we will
+            // see its new deadline.
+            let generation = slot.generation.load(Ordering::Acquire);
+            if generation & LOST == LOST {
+                continue;
+            }
+            let deadline = slot.deadline.load(Ordering::Acquire);
+            if deadline == NO_DEADLINE || deadline > now {
+                continue;
+            }
+            let refs = slot.ref_count(Ordering::Acquire);
+            if refs == 0 || refs == CLAIMED {
+                continue;
+            }
 
-        // Is our snapshot still valid?
-        if self.head.compare_and_swap(idx, next, Ordering::Release) == idx {
-            // We can use this slot!
-            unsafe { lease.as_mut() }.item.clear();
-            self.used.fetch_add(1, Ordering::Relaxed);
-            Ok(lease)
-        } else {
-            slot.release();
-            Err(Error::ShouldRetry)
+            if slot
+                .generation
+                .compare_exchange(
+                    generation,
+                    generation | LOST,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .is_ok()
+            {
+                // Count the slot as lost before it stops being used, so that
+                // `remaining` never overestimates the free capacity.
+                lease.lost.fetch_add(1, Ordering::Release);
+                // Reclaiming a slot can never leave the slab idle, since the
+                // stuck checkout is never counted as released.
+                let _ = self.sub_used(1);
+                self.record(EventKind::Lost { idx });
+                let overdue = Duration::from_millis((now - deadline) as u64);
+                crate::trace::reclaimed(self.id(), idx, overdue);
+                reclaimed += 1;
+            }
         }
+        reclaimed
     }
-}
 
-// ===== impl Slot =====
+    /// Takes a new reference to the shared checkout that `key` refers to,
+    /// returning the slot's generation if it is still checked out.
+    ///
+    /// The caller must drop the reference with that generation. Otherwise,
+    /// this returns whether the slab became idle, as with `Slot::drop_ref`.
+    pub(crate) fn get(&self, key: Key) -> Result<usize, bool> {
+        if key.index() >= self.inner.len() {
+            return Err(false);
+        }
+        let slot = &self.inner[key.index()];
 
-impl<T> Slot<T> {
-    pub fn new(item: T, idx: usize) -> Self {
-        Slot {
-            item,
-            ref_count: AtomicUsize::new(0),
-            next: AtomicUsize::new(idx + 1),
-            idx,
+        // Like upgrading a `Weak`, only take a reference if the slot still has
+        // one; a slot without any references may be checked out again at any
+        // time.
+        let mut refs = slot.ref_count(Ordering::Relaxed);
+        loop {
+            // A claimed slot is free, and its count is never released, so a
+            // reference taken to it could end up releasing a free slot.
+            if refs == 0 || refs == UNSHARING || refs == CLAIMED {
+                return Err(false);
+            }
+            match slot.ref_count.compare_exchange(
+                refs,
+                refs + 1,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(actual) => refs = actual,
+            }
         }
+
+        // Now that we hold a reference, the slot can't be released out from
+        // under us, but it may have been released and checked out again since
+        // the key was issued.
+        let generation = slot.generation();
+        if generation & (LOST | POISONED) == 0
+            && self::key(key.index(), generation) == key
+            && slot.shared.load(Ordering::Acquire) != 0
+        {
+            return Ok(generation);
+        }
+        Err(unsafe { slot.drop_ref(self, generation) })
     }
 
-    fn next(&self) -> usize {
-        self.next.load(Ordering::Relaxed)
+    /// Returns the number of blocking checkouts currently waiting for a slot.
+    pub(crate) fn waiters(&self) -> usize {
+        self.waiters.load(Ordering::Relaxed)
     }
 
-    fn try_acquire(&self) -> Result<ptr::NonNull<Self>, Error> {
-        if self.ref_count.compare_and_swap(0, 1, Ordering::Acquire) == 0 {
-            Ok(ptr::NonNull::from(self))
-        } else {
-            Err(Error::ShouldRetry)
+    /// Returns the queue of asynchronous checkouts waiting for a slot.
+    pub(crate) fn wakers(&self) -> &Wakers {
+        &self.wakers
+    }
+
+    /// Yields if other threads are already waiting for a slot, so that one of
+    /// them can take the next slot that's released before a new blocking
+    /// checkout tries to.
+    ///
+    /// Without this, a thread that releases a slot and immediately checks one
+    /// out again usually takes back the slot it released, and can starve the
+    /// threads waiting for it indefinitely.
+    #[inline]
+    pub(crate) fn yield_to_waiters(&self) {
+        if self.waiters() > 0 {
+            crate::backoff::yield_now();
         }
     }
 
-    fn release(&self) -> bool {
-        self.ref_count.fetch_sub(1, Ordering::Relaxed) == 1
+    /// Counts the caller as waiting for a slot until the returned guard is
+    /// dropped.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn start_waiting(&self) -> Waiting<'_, T> {
+        self.add_waiter();
+        Waiting { slab: self }
     }
 
-    pub fn clone_ref(&self) {
-        self.ref_count.fetch_add(1, Ordering::Relaxed);
+    /// Counts the caller as waiting for a slot, until a matching call to
+    /// `remove_waiter`.
+    ///
+    /// This is for callers that can't hold a `Waiting` guard while they wait,
+    /// such as a growable pool's checkout, which can't keep the pool's lock.
+    pub(crate) fn add_waiter(&self) {
+        self.waiters.fetch_add(1, Ordering::Relaxed);
     }
 
-    pub(crate) fn drop_ref(&self, slab: &Slab<T>) {
-        if self.release() {
-            // Free the slot.
-            let next = slab.head.swap(self.idx, Ordering::Release);
-            self.next.store(next, Ordering::Release);
-            slab.used.fetch_sub(1, Ordering::Relaxed);
-        }
+    pub(crate) fn remove_waiter(&self) {
+        self.waiters.fetch_sub(1, Ordering::Relaxed);
     }
 
-    pub fn ref_count(&self, ordering: Ordering) -> usize {
-        self.ref_count.load(ordering)
+    /// Returns `true` if no slots in the slab are currently checked out.
+    pub fn is_idle(&self) -> bool {
+        self.used.load(Ordering::SeqCst) == 0
     }
 
-    #[inline]
-    pub fn index(&self) -> usize {
-        self.idx
+    pub(crate) fn idle(&self) -> &Arc<Idle> {
+        &self.idle
     }
 
-    #[inline]
-    pub fn item(&self) -> &T {
-        &self.item
+    /// Returns the slot at `idx`.
+    ///
+    /// Whether or not the slot is checked out, accessing its item or its
+    /// reference count through the returned reference is `unsafe`; see the
+    /// [`raw`](../raw/index.html#safety) module for the rules.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is out of bounds.
+    pub fn slot(&self, idx: usize) -> &Slot<T> {
+        &self.inner[idx]
+    }
+
+    /// Returns the slot at `idx`.
+    ///
+    /// # Panics
+    ///
+    /// If `idx` is out of bounds.
+    #[cfg(feature = "growable")]
+    pub(crate) fn slot_mut(&mut self, idx: usize) -> &mut Slot<T> {
+        &mut self.inner[idx]
     }
 
+    /// Returns the index of `slot`, which must belong to this slab.
+    ///
+    /// Slots don't store their own index, which would cost a word per slot;
+    /// instead, it's computed from the slot's position in the slab.
     #[inline]
-    pub fn item_mut(&mut self) -> &mut T {
-        &mut self.item
+    pub fn index_of(&self, slot: &Slot<T>) -> usize {
+        self.inner.index_of(slot)
     }
 
-    /// Asserts that this slot is currently in a valid state.
-    pub fn assert_valid(&self) {
-        assert_ne!(
-            self.next.load(Ordering::SeqCst),
-            self.idx,
-            "invariant violated: next pointer may not point to self"
+    /// Checks the slab's structural invariants.
+    ///
+    /// This is called after every checkout in debug builds, while other
+    /// threads may be checking items out and releasing them, so it only checks
+    /// invariants that hold at every instant. Whether the `used` count agrees
+    /// with the slots' reference counts can only be checked when the slab is
+    /// quiescent; see `audit`.
+    pub(crate) fn assert_valid(&self) {
+        for (idx, slot) in self.inner.iter().enumerate() {
+            slot.assert_valid(idx);
+        }
+        assert!(
+            {
+                let head = head_index(self.head.load(Ordering::SeqCst));
+                head == EMPTY || head < self.inner.len()
+            },
+            "invariant violated: free list head should not point past the end of the slab",
         );
     }
 }
 
+impl<T> Slab<T>
+where
+    T: Clear,
+{
+    /// Checks out a free slot, clearing its item.
+    ///
+    /// The returned slot has a single reference, which belongs to the caller.
+    /// The pointer remains valid until the slab is grown or dropped. See the
+    /// [`raw`](../raw/index.html) module for the rules for accessing the slot
+    /// through it.
+    pub fn try_checkout(&self) -> Result<ptr::NonNull<Slot<T>>, Error> {
+        // The slab's free list is a modification of Treiber's lock-free stack,
+        // using slab indices instead of pointers, and with a provison for
+        // growing the slab when needed.
+        //
+        // In order to check out an item from the slab, we "pop" the next free
+        // slot from the stack.
+        //
+        // Callers may poll an exhausted slab at a high rate, so first check
+        // whether every slot is checked out. This costs a single load, and
+        // avoids touching the free list (which is contended) at all.
+        if self.used.load(Ordering::Relaxed) >= self.inner.len() && self.is_exhausted() {
+            cold();
+            self.record(EventKind::Exhausted);
+            return Err(Error::AtCapacity);
+        }
+
+        if self.reuse == Reuse::RoundRobin {
+            return self.try_checkout_round_robin();
+        }
+
+        let head = self.head.load(Ordering::Acquire);
+        let idx = head_index(head);
+
+        // Can we insert without reallocating?
+        let len = self.inner.len();
+
+        // println!("try_checkout head={:?}; len={:?}", idx, len);
+        if idx >= len {
+            self.record(EventKind::Exhausted);
+            return Err(Error::AtCapacity);
+        }
+
+        // If someone else has locked the slot, bail and try again.
+        let slot = &self.inner[idx];
+        let lease = slot
+            .try_claim()
+            .inspect_err(|_| self.record(EventKind::Contended { idx }))?;
+        let next = slot.next();
+
+        // Is our snapshot still valid? The slot may have been checked out and
+        // released since we loaded the head, and we may have claimed it
+        // before it was pushed back onto the free list, and read a `next`
+        // that's since been changed. If so, the head has changed, even if the
+        // slot is at the head again. Otherwise, this must also `Acquire` the
+        // push, to see the generation that the release left the slot with.
+        if self
+            .head
+            .compare_exchange(
+                head,
+                next_head(head, next),
+                Ordering::AcqRel,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+        {
+            // We can use this slot! This must be `Release`, so that a lookup
+            // by key which takes a reference to the new checkout also sees
+            // the generation it was checked out with (see `Slab::get`).
+            slot.ref_count.store(1, Ordering::Release);
+            Ok(self.checked_out(idx, lease))
+        } else {
+            slot.ref_count.store(0, Ordering::Release);
+            self.record(EventKind::Contended { idx });
+            Err(Error::ShouldRetry)
+        }
+    }
+
+    /// Returns `true` if no slot is free, once `used` says that every slot is
+    /// checked out.
+    ///
+    /// A released slot is made free before `used` is decremented, so while a
+    /// release is in flight, `used` may still count a slot that can already
+    /// be checked out.
+    fn is_exhausted(&self) -> bool {
+        match self.reuse {
+            Reuse::Lifo => head_index(self.head.load(Ordering::Acquire)) >= self.inner.len(),
+            // Finding a free slot takes the same scan as checking one out, so
+            // leave that to the checkout.
+            Reuse::RoundRobin => false,
+        }
+    }
+
+    /// Checks out the first free slot at or after the cursor, wrapping around
+    /// to the start of the slab.
+    fn try_checkout_round_robin(&self) -> Result<ptr::NonNull<Slot<T>>, Error> {
+        let len = self.inner.len();
+        let start = self.cursor.load(Ordering::Relaxed) % len;
+        for idx in (start..len).chain(0..start) {
+            let slot = &self.inner[idx];
+            // Claiming the slot's `FREE` marker synchronizes with the release
+            // that set it, so the slot's generation has already advanced.
+            if slot.next.load(Ordering::Relaxed) != FREE
+                || slot
+                    .next
+                    .compare_exchange(FREE, TAKEN, Ordering::Acquire, Ordering::Relaxed)
+                    .is_err()
+            {
+                continue;
+            }
+
+            // A free slot has no references, and only the thread that claimed
+            // its marker may take the first one.
+            let lease = slot
+                .try_acquire()
+                .expect("a free slot cannot already be checked out");
+            self.cursor.store(idx + 1, Ordering::Relaxed);
+            return Ok(self.checked_out(idx, lease));
+        }
+
+        self.record(EventKind::Exhausted);
+        Err(Error::AtCapacity)
+    }
+
+    /// Prepares the slot at `idx`, which the caller has just acquired, to be
+    /// handed out.
+    fn checked_out(&self, idx: usize, mut lease: ptr::NonNull<Slot<T>>) -> ptr::NonNull<Slot<T>> {
+        let slot = unsafe { lease.as_mut() };
+        if slot.is_vacant() {
+            if let Some(rebuild) = self.rebuild.get() {
+                slot.refill(rebuild());
+            }
+        }
+        // The slot is checked out, so no other thread may mark it dirty. If
+        // items are cleared on release, only those that haven't been since
+        // they were constructed or poisoned are dirty. A slot that is still
+        // vacant is cleared once the pool refills it.
+        let track = self.dirty_tracking || self.clear_on_release.is_some();
+        if !slot.is_vacant() && (!track || slot.dirty.load(Ordering::Relaxed) != 0) {
+            clear(&mut *slot.item);
+            slot.dirty.store(0, Ordering::Relaxed);
+        }
+        self.add_used();
+        if let Some(ref settings) = self.lease {
+            let deadline = settings.now() + settings.duration.as_millis() as usize;
+            slot.deadline.store(deadline, Ordering::Release);
+        }
+        self.record(EventKind::Checkout { idx });
+        lease
+    }
+
+    /// Puts `item` in `slot`, a vacant slot that the caller has checked out,
+    /// and clears it like a new item.
+    #[cfg(feature = "growable")]
+    pub(crate) fn refill(&self, slot: &mut Slot<T>, item: T) {
+        slot.refill(item);
+        clear(&mut *slot.item);
+        slot.dirty.store(0, Ordering::Relaxed);
+    }
+}
+
+// Items are left out, since reading a slot's item is only sound for the
+// holder of a reference to it.
+impl<T> fmt::Debug for Slab<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Slab")
+            .field("id", &self.id)
+            .field("size", &self.size())
+            .field("used", &self.used())
+            .field("poisoned", &self.poisoned())
+            .field("head", &head_index(self.head.load(Ordering::Relaxed)))
+            .field("reuse", &self.reuse)
+            .field("slots", &self.inner)
+            .finish()
+    }
+}
+
+// ===== impl Slot =====
+
+impl<T> Slot<T> {
+    pub(crate) fn new(item: T, idx: usize) -> Self {
+        Slot {
+            item: ManuallyDrop::new(item),
+            ref_count: AtomicUsize::new(0),
+            next: AtomicUsize::new(idx + 1),
+            generation: AtomicUsize::new(0),
+            deadline: AtomicUsize::new(NO_DEADLINE),
+            shared: AtomicUsize::new(0),
+            // New items are cleared when they're first checked out, as they
+            // always have been.
+            dirty: AtomicUsize::new(1),
+            vacant: AtomicUsize::new(0),
+        }
+    }
+
+    fn next(&self) -> usize {
+        self.next.load(Ordering::Relaxed)
+    }
+
+    /// Claims a slot found at the head of the free list, so that no other
+    /// checkout pops it, and no lookup by key takes a reference to it.
+    ///
+    /// If the slot is popped, the caller gives it its first reference;
+    /// otherwise, it resets the count to zero.
+    fn try_claim(&self) -> Result<ptr::NonNull<Self>, Error> {
+        if self
+            .ref_count
+            .compare_exchange(0, CLAIMED, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+        {
+            Ok(ptr::NonNull::from(self))
+        } else {
+            Err(Error::ShouldRetry)
+        }
+    }
+
+    fn try_acquire(&self) -> Result<ptr::NonNull<Self>, Error> {
+        // This must also be `Release`, so that a lookup by key which takes a
+        // reference to the new checkout also sees the generation it was
+        // checked out with (see `Slab::get`).
+        if self
+            .ref_count
+            .compare_exchange(0, 1, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            Ok(ptr::NonNull::from(self))
+        } else {
+            Err(Error::ShouldRetry)
+        }
+    }
+
+    /// Drops a reference to this slot, unless it's the last one, returning
+    /// `false` if it was, in which case the caller still holds it.
+    #[cfg(feature = "fixed")]
+    pub(crate) fn drop_ref_unless_last(&self) -> bool {
+        let mut refs = self.ref_count.load(Ordering::Acquire);
+        while refs > 1 {
+            match self.ref_count.compare_exchange(
+                refs,
+                refs - 1,
+                Ordering::Release,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return true,
+                Err(actual) => refs = actual,
+            }
+        }
+        false
+    }
+
+    fn release(&self) -> bool {
+        // As with `Arc`, every access through a dropped reference must happen
+        // before the slot is released, so the decrement must be `Release`,
+        // and the thread that drops the last reference must `Acquire` the
+        // other threads' decrements before releasing the slot.
+        self.ref_count.fetch_sub(1, Ordering::AcqRel) == 1
+    }
+
+    /// Records that the slot's item may have been modified, so that it must
+    /// be cleared before it's reused.
+    ///
+    /// [`item_mut`] does this, so only checkouts that modify the item some
+    /// other way need to call it, before they drop their reference.
+    ///
+    /// [`item_mut`]: #method.item_mut
+    #[inline]
+    pub fn mark_dirty(&self) {
+        self.dirty.store(1, Ordering::Relaxed);
+    }
+
+    /// Marks the slot's current checkout as shared, so that it may be
+    /// looked up by key.
+    pub(crate) fn mark_shared(&self) {
+        self.shared.store(1, Ordering::Release);
+    }
+
+    /// Takes back the only reference to a shared checkout, so that the item
+    /// may be mutated again, and returns the checkout's new generation.
+    ///
+    /// Returns `None` if the checkout has other references, or if it was
+    /// reclaimed after its lease expired.
+    pub(crate) fn try_unshare(&self, generation: usize) -> Option<usize> {
+        // While the count is `UNSHARING`, `Slab::get` won't take new
+        // references, so no one can look the item up while its version
+        // changes. The count must never drop to zero, though: a checkout that
+        // found the slot at the head of the free list before it was checked
+        // out could then claim it, and reset the count to zero when it found
+        // the head had moved, leaving the upgraded checkout without a
+        // reference.
+        self.ref_count
+            .compare_exchange(1, UNSHARING, Ordering::Acquire, Ordering::Relaxed)
+            .ok()?;
+        let next = generation.wrapping_add(GENERATION_STEP);
+        let unshared = self
+            .generation
+            .compare_exchange(generation, next, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok();
+        if unshared {
+            self.shared.store(0, Ordering::Relaxed);
+        }
+        self.ref_count.store(1, Ordering::Release);
+        if unshared {
+            Some(next)
+        } else {
+            None
+        }
+    }
+
+    /// Takes another reference to the slot's current checkout.
+    ///
+    /// # Safety
+    ///
+    /// The caller must already hold a reference to the slot's current
+    /// checkout, and becomes responsible for dropping the new one as well.
+    /// Otherwise, this could take a reference to a free slot, which the slab
+    /// may hand out again at any time.
+    pub unsafe fn clone_ref(&self) {
+        // Creating a new reference requires an existing one, which already
+        // synchronizes access to the slot, so this may be `Relaxed`.
+        self.ref_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the slot's current generation.
+    ///
+    /// Checkouts record the generation of the slot they were issued for, so
+    /// that releasing them after the slot has been reclaimed is a no-op.
+    #[inline]
+    pub fn generation(&self) -> usize {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    fn is_lost(&self) -> bool {
+        self.generation.load(Ordering::Acquire) & LOST == LOST
+    }
+
+    fn is_poisoned(&self) -> bool {
+        self.generation.load(Ordering::Acquire) & POISONED == POISONED
+    }
+
+    /// Drops a reference to this slot, releasing it back to the slab if it
+    /// was the last one.
+    ///
+    /// `generation` is the slot generation observed when the reference was
+    /// checked out.
+    ///
+    /// Returns `true` if releasing the slot left the slab idle, so that a pool
+    /// which lets threads wait for it to become idle can wake them (after
+    /// releasing any locks that a waiter might also acquire).
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold a reference to the slot, which was checked out of
+    /// `slab`, and this drops it: each reference must be dropped exactly once.
+    /// Once the last reference is dropped, the slot may be checked out again
+    /// at any time, and no reference to its item may be used.
+    #[must_use]
+    pub unsafe fn drop_ref(&self, slab: &Slab<T>, generation: usize) -> bool {
+        self.release_ref(slab, generation, false)
+    }
+
+    /// Drops a reference to this slot, marking it as poisoned rather than
+    /// releasing it if it was the last one.
+    ///
+    /// This is used when a checkout is dropped while panicking, in which case
+    /// the item may have been left in an inconsistent state. Poisoned slots are
+    /// not reused until they are [repaired](Slab::repair).
+    ///
+    /// Returns `true` if poisoning the slot left the slab idle, as with
+    /// `drop_ref`.
+    ///
+    /// # Safety
+    ///
+    /// As with [`drop_ref`](Slot::drop_ref), the caller must hold a reference
+    /// to the slot, which this drops.
+    #[must_use]
+    pub unsafe fn poison(&self, slab: &Slab<T>, generation: usize) -> bool {
+        self.release_ref(slab, generation, true)
+    }
+
+    fn release_ref(&self, slab: &Slab<T>, generation: usize, poison: bool) -> bool {
+        debug_assert!(
+            slab.inner.owns(self),
+            "a slot was released to pool {}, which it does not belong to",
+            slab.id,
+        );
+        if !self.unlink(slab, generation, poison) {
+            return false;
+        }
+        if poison {
+            return slab.sub_used(1);
+        }
+        self.push_free(slab);
+        slab.sub_released(1)
+    }
+
+    /// Drops a reference to this slot, returning `true` if it was the last
+    /// one.
+    ///
+    /// If this returns `true` and the slot was not poisoned, the caller must
+    /// push it onto the free list and then decrement the slab's `used` count.
+    fn unlink(&self, slab: &Slab<T>, generation: usize, poison: bool) -> bool {
+        let leased = slab.lease.is_some();
+        if leased && (generation & LOST == LOST || self.generation() != generation) {
+            // The slot was reclaimed after its lease expired; it now belongs to
+            // no one.
+            return false;
+        }
+
+        if self.release() {
+            self.shared.store(0, Ordering::Relaxed);
+            let next_generation = if poison {
+                generation | POISONED
+            } else {
+                generation.wrapping_add(GENERATION_STEP)
+            };
+            if leased {
+                self.deadline.store(NO_DEADLINE, Ordering::Release);
+                if self
+                    .generation
+                    .compare_exchange(
+                        generation,
+                        next_generation,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    )
+                    .is_err()
+                {
+                    // The slot was reclaimed while we were releasing it.
+                    return false;
+                }
+            } else if poison {
+                self.generation.fetch_or(POISONED, Ordering::AcqRel);
+            } else {
+                self.generation.fetch_add(GENERATION_STEP, Ordering::AcqRel);
+            }
+
+            if poison {
+                self.mark_dirty();
+            } else if let Some(clear) = slab.clear_on_release {
+                let dirty = self.dirty.load(Ordering::Relaxed) != 0;
+                if !self.is_vacant() && (!slab.dirty_tracking || dirty) {
+                    // The last reference was just dropped, and the slot isn't
+                    // free until the caller returns it to the free list, so
+                    // nothing else may access it.
+                    clear(unsafe { &mut *ptr::NonNull::from(self).as_mut().item });
+                    self.dirty.store(0, Ordering::Relaxed);
+                }
+            }
+
+            if poison {
+                slab.poisoned.fetch_add(1, Ordering::Release);
+                slab.counters.poisoned.fetch_add(1, Ordering::Relaxed);
+                slab.record(EventKind::Poison {
+                    idx: slab.index_of(self),
+                });
+            } else {
+                slab.counters.releases.fetch_add(1, Ordering::Relaxed);
+                slab.record(EventKind::Release {
+                    idx: slab.index_of(self),
+                });
+            }
+            return true;
+        }
+        false
+    }
+
+    fn push_free(&self, slab: &Slab<T>) {
+        slab.push_free_list(self, self);
+    }
+
+    pub(crate) fn ref_count(&self, ordering: Ordering) -> usize {
+        self.ref_count.load(ordering)
+    }
+
+    /// Returns the slot's item.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold a reference to the slot, and the item must not
+    /// be accessed mutably while the returned reference is used. A free
+    /// slot's item may be cleared by whichever checkout acquires it next, and
+    /// the holder of a checkout's only reference may mutate its item.
+    #[inline]
+    pub unsafe fn item(&self) -> &T {
+        &self.item
+    }
+
+    /// Returns the slot's item, and marks it as modified.
+    #[inline]
+    pub fn item_mut(&mut self) -> &mut T {
+        self.mark_dirty();
+        &mut self.item
+    }
+
+    /// Moves the item out of the slot, leaving it vacant until it is
+    /// refilled.
+    ///
+    /// # Safety
+    ///
+    /// The caller must hold the slot's only reference, and must release it
+    /// without accessing the item again.
+    pub(crate) unsafe fn take(&mut self) -> T {
+        debug_assert!(!self.is_vacant(), "slot's item was already taken");
+        self.vacant.store(1, Ordering::Release);
+        ManuallyDrop::take(&mut self.item)
+    }
+
+    /// Returns `true` if the slot's item has been taken out, and the slot
+    /// hasn't been refilled since.
+    pub(crate) fn is_vacant(&self) -> bool {
+        self.vacant.load(Ordering::Acquire) != 0
+    }
+
+    /// Puts `item` in this vacant slot. Like a new item, it is dirty until
+    /// it is cleared.
+    fn refill(&mut self, item: T) {
+        debug_assert!(self.is_vacant(), "only a vacant slot may be refilled");
+        self.item = ManuallyDrop::new(item);
+        self.dirty.store(1, Ordering::Relaxed);
+        self.vacant.store(0, Ordering::Release);
+    }
+
+    fn into_item(mut self) -> Option<T> {
+        if self.is_vacant() {
+            return None;
+        }
+        // The slot is being dropped, so nothing else refers to it.
+        Some(unsafe { self.take() })
+    }
+
+    /// Asserts that this slot, at index `idx`, is currently in a valid state.
+    pub(crate) fn assert_valid(&self, idx: usize) {
+        assert_ne!(
+            self.next.load(Ordering::SeqCst),
+            idx,
+            "invariant violated: next pointer may not point to self"
+        );
+    }
+}
+
+impl<T> fmt::Debug for Slot<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Slot")
+            .field("ref_count", &self.ref_count.load(Ordering::Relaxed))
+            .field("next", &self.next.load(Ordering::Relaxed))
+            .field("generation", &self.generation.load(Ordering::Relaxed))
+            .field("shared", &self.shared.load(Ordering::Relaxed))
+            .field("dirty", &self.dirty.load(Ordering::Relaxed))
+            .field("vacant", &self.is_vacant())
+            .finish()
+    }
+}
+
+impl<T> Drop for Slot<T> {
+    fn drop(&mut self) {
+        if !self.is_vacant() {
+            unsafe { ManuallyDrop::drop(&mut self.item) }
+        }
+    }
+}
+
+// ===== impl GrowError =====
+
+impl GrowError {
+    pub(crate) fn too_many_slots() -> Self {
+        Self {
+            kind: GrowErrorKind::TooManySlots,
+        }
+    }
+
+    #[cfg(feature = "growable")]
+    pub(crate) fn over_budget() -> Self {
+        Self {
+            kind: GrowErrorKind::OverBudget,
+        }
+    }
+
+    pub(crate) fn capacity_overflow() -> Self {
+        Self {
+            kind: GrowErrorKind::CapacityOverflow,
+        }
+    }
+
+    #[cfg(feature = "growable")]
+    pub(crate) fn reentrant() -> Self {
+        Self {
+            kind: GrowErrorKind::Reentrant,
+        }
+    }
+
+    pub(crate) fn alloc_failed(layout: Layout) -> Self {
+        Self {
+            kind: GrowErrorKind::AllocFailed(layout),
+        }
+    }
+
+    /// Returns `true` if growing failed because the allocator could not
+    /// provide memory for the new slots or items.
+    pub fn is_alloc_failed(&self) -> bool {
+        matches!(self.kind, GrowErrorKind::AllocFailed(_))
+    }
+
+    /// Returns `true` if growing failed because the pool would have had more
+    /// than [`MAX_SLOTS`] slots.
+    ///
+    /// [`MAX_SLOTS`]: constant.MAX_SLOTS.html
+    pub fn is_too_many_slots(&self) -> bool {
+        self.kind == GrowErrorKind::TooManySlots
+    }
+
+    /// Returns `true` if growing failed because the pool's items would have
+    /// exceeded its [`Budget`].
+    ///
+    /// [`Budget`]: struct.Budget.html
+    pub fn is_over_budget(&self) -> bool {
+        self.kind == GrowErrorKind::OverBudget
+    }
+
+    /// Returns `true` if growing failed because the pool would have had more
+    /// slots than fit in memory.
+    pub fn is_capacity_overflow(&self) -> bool {
+        self.kind == GrowErrorKind::CapacityOverflow
+    }
+
+    /// Returns `true` if growing failed because the thread trying to grow the
+    /// pool was already growing it, such as when the pool's allocator or item
+    /// constructor tries to check an item out of the same pool.
+    pub fn is_reentrant(&self) -> bool {
+        self.kind == GrowErrorKind::Reentrant
+    }
+
+    /// Fails the way infallible growth always has: panicking on capacity
+    /// overflow, and aborting if allocation failed.
+    pub(crate) fn raise(self) -> ! {
+        match self.kind {
+            GrowErrorKind::TooManySlots | GrowErrorKind::OverBudget | GrowErrorKind::Reentrant => {
+                panic!("{}", self)
+            }
+            GrowErrorKind::CapacityOverflow => panic!("slab capacity overflow"),
+            GrowErrorKind::AllocFailed(layout) => alloc::handle_alloc_error(layout),
+        }
+    }
+}
+
+impl fmt::Display for GrowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            GrowErrorKind::TooManySlots => {
+                write!(f, "a pool may have at most {} slots", MAX_SLOTS)
+            }
+            GrowErrorKind::OverBudget => f.write_str("growing would exceed the pool's budget"),
+            GrowErrorKind::CapacityOverflow => f.write_str("slab capacity overflow"),
+            GrowErrorKind::Reentrant => {
+                f.write_str("a pool was used by the thread growing it, while growing it")
+            }
+            GrowErrorKind::AllocFailed(layout) => write!(
+                f,
+                "memory allocation of {} bytes failed while growing",
+                layout.size()
+            ),
+        }
+    }
+}
+
+impl error::Error for GrowError {}
+
+// ===== impl AuditReport =====
+
+impl AuditReport {
+    /// Returns the number of slots that are neither checked out, free, lost,
+    /// nor poisoned.
+    pub fn missing(&self) -> usize {
+        self.size
+            .saturating_sub(self.checked_out + self.free + self.lost + self.poisoned)
+    }
+
+    /// Returns `true` if every slot is accounted for exactly once, and the
+    /// pool's count of checked out slots matches the slots actually checked
+    /// out.
+    pub fn is_consistent(&self) -> bool {
+        self.used == self.checked_out
+            && self.checked_out + self.free + self.lost + self.poisoned == self.size
+    }
+}
+
+// ===== impl VerifyReport =====
+
+impl VerifyReport {
+    /// Returns `true` if every free slot was checked out exactly once, no
+    /// item lost capacity when it was cleared, and the audit is consistent.
+    pub fn is_ok(&self) -> bool {
+        self.duplicates.is_empty()
+            && self.shrunk.is_empty()
+            && self.audit.is_consistent()
+            && self.checked_out == self.audit.free
+    }
+
+    /// Verifies `items`, which were checked out of a pool of `size` slots
+    /// and are released when this returns, before `audit` is called.
+    pub(crate) fn run<T: Clear, C: DerefMut<Target = T>>(
+        size: usize,
+        items: impl IntoIterator<Item = (usize, C)>,
+        audit: impl FnOnce() -> AuditReport,
+    ) -> Self {
+        let mut seen = vec![false; size];
+        let mut checked_out = 0;
+        let mut duplicates = Vec::new();
+        let mut shrunk = Vec::new();
+        let mut held = Vec::new();
+        for (idx, mut item) in items {
+            checked_out += 1;
+            match seen.get_mut(idx) {
+                Some(seen) if !*seen => *seen = true,
+                _ => duplicates.push(idx),
+            }
+            let before = item.retained_capacity();
+            item.clear();
+            if let (Some(before), Some(after)) = (before, item.retained_capacity()) {
+                if after < before {
+                    shrunk.push(idx);
+                }
+            }
+            // Hold on to every item until they have all been checked out, so
+            // that none is checked out twice.
+            held.push(item);
+        }
+        drop(held);
+        Self {
+            checked_out,
+            duplicates,
+            shrunk,
+            audit: audit(),
+        }
+    }
+}
+
+// ===== impl RetryHistogram =====
+
+impl RetryHistogram {
+    /// Returns the number of checkouts in each bucket.
+    pub fn buckets(&self) -> &[usize] {
+        &self.buckets[..]
+    }
+
+    /// Returns the total number of checkouts recorded.
+    pub fn checkouts(&self) -> usize {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns the number of checkouts that had to retry at least once.
+    pub fn contended(&self) -> usize {
+        self.buckets[1..].iter().sum()
+    }
+}
+
+// ===== impl Retries =====
+
+impl Retries {
+    fn new() -> Self {
+        Self {
+            buckets: [
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+                AtomicUsize::new(0),
+            ],
+        }
+    }
+
+    fn record(&self, retries: usize) {
+        let bucket = (usize::BITS - retries.leading_zeros()) as usize;
+        self.buckets[bucket.min(RETRY_BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> RetryHistogram {
+        let mut histogram = RetryHistogram::default();
+        for (count, bucket) in histogram.buckets.iter_mut().zip(&self.buckets) {
+            *count = bucket.load(Ordering::Relaxed);
+        }
+        histogram
+    }
+}
+
+// ===== impl StatsCursor =====
+
+impl StatsCursor {
+    /// Returns a cursor that hasn't seen any activity.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the counts in `now` that this cursor hasn't seen, and
+    /// remembers `now`.
+    fn advance(&mut self, now: Stats) -> Stats {
+        let last = std::mem::replace(&mut self.last, now);
+        // The counters may wrap around on very long-lived pools.
+        Stats {
+            checkouts: now.checkouts.wrapping_sub(last.checkouts),
+            releases: now.releases.wrapping_sub(last.releases),
+            poisoned: now.poisoned.wrapping_sub(last.poisoned),
+            lost: now.lost.wrapping_sub(last.lost),
+            contended: now.contended.wrapping_sub(last.contended),
+            detached: now.detached.wrapping_sub(last.detached),
+        }
+    }
+}
+
+// ===== impl Counters =====
+
+impl Counters {
+    fn new() -> Self {
+        Self {
+            checkouts: StatAtomic::new(0),
+            releases: StatAtomic::new(0),
+            poisoned: StatAtomic::new(0),
+            detached: StatAtomic::new(0),
+            high_water: StatAtomic::new(0),
+        }
+    }
+}
+
+// ===== impl Waiting =====
+
+#[cfg(feature = "fixed")]
+impl<T> Drop for Waiting<'_, T> {
+    fn drop(&mut self) {
+        self.slab.remove_waiter();
+    }
+}
+
+// ===== impl Watermarks =====
+
+impl Watermarks {
+    pub fn levels(self, low: usize, high: usize) -> Self {
+        assert!(
+            low <= high,
+            "low watermark ({}) must not exceed high watermark ({})",
+            low,
+            high
+        );
+        Self { low, high, ..self }
+    }
+
+    pub fn on_change(self, f: impl Fn(Pressure) + Send + Sync + 'static) -> Self {
+        Self {
+            on_change: Some(std::sync::Arc::new(f)),
+            ..self
+        }
+    }
+
+    fn pressure(&self, used: usize) -> Pressure {
+        if used >= self.high {
+            Pressure::High
+        } else if used >= self.low {
+            Pressure::Medium
+        } else {
+            Pressure::Low
+        }
+    }
+
+    /// Notifies the callback, if there is one, if the number of used slots
+    /// changing from `prev` to `next` crossed a watermark.
+    #[inline]
+    fn update(&self, prev: usize, next: usize) {
+        if let Some(ref on_change) = self.on_change {
+            let pressure = self.pressure(next);
+            if pressure != self.pressure(prev) {
+                on_change(pressure);
+            }
+        }
+    }
+}
+
+impl Default for Watermarks {
+    /// Without watermarks, a slab's pressure is always `Low`.
+    fn default() -> Self {
+        Self {
+            low: usize::MAX,
+            high: usize::MAX,
+            on_change: None,
+        }
+    }
+}
+
+impl fmt::Debug for Watermarks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watermarks")
+            .field("low", &self.low)
+            .field("high", &self.high)
+            .field("on_change", &self.on_change.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+// ===== impl OnRelease =====
+
+impl OnRelease {
+    pub fn new(f: impl Fn(u64) + Send + Sync + 'static) -> Self {
+        Self(Some(std::sync::Arc::new(f)))
+    }
+}
+
+impl fmt::Debug for OnRelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+// ===== impl Recycle =====
+
+#[cfg(feature = "fixed")]
+impl<T> Recycle<T> {
+    pub fn new(
+        f: impl Fn(&Arc<Slab<T>>, ptr::NonNull<Slot<T>>, usize) + Send + Sync + 'static,
+    ) -> Self {
+        Self(Some(std::sync::Arc::new(f)))
+    }
+}
+
+impl<T> Clone for Recycle<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Default for Recycle<T> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<T> fmt::Debug for Recycle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(if self.0.is_some() { "Some(..)" } else { "None" })
+    }
+}
+
+// ===== impl Lease =====
+
+impl Lease {
+    fn now(&self) -> usize {
+        // Offset by one so that a deadline is never `NO_DEADLINE`.
+        self.epoch.elapsed().as_millis() as usize + 1
+    }
+}
+
+// ===== impl Idle =====
+
+impl Idle {
+    fn new() -> Self {
+        Self {
+            waiters: std::sync::atomic::AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            cv: Condvar::new(),
+        }
+    }
+
+    /// Blocks the current thread until `is_idle` returns `true`.
+    ///
+    /// `is_idle` must not acquire any lock that is held while calling
+    /// `notify`.
+    pub fn wait_until(&self, mut is_idle: impl FnMut() -> bool) {
+        // Announce ourselves *before* checking, so that a concurrent release
+        // either observes the waiter and notifies it, or happens-before our
+        // check.
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        let mut lock = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        while !is_idle() {
+            lock = self.cv.wait(lock).unwrap_or_else(|e| e.into_inner());
+        }
+        drop(lock);
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Wakes any threads waiting for the slab to become idle.
+    pub fn notify(&self) {
+        if self.waiters.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        // Acquiring the lock ensures that any waiter has either not yet
+        // checked whether the slab is idle, or is already parked on the
+        // condvar.
+        drop(self.lock.lock().unwrap_or_else(|e| e.into_inner()));
+        self.cv.notify_all();
+    }
+}
+
+#[cfg(feature = "growable")]
+impl<T> Slab<Box<T>> {
+    /// Returns `true` if `item` points to an item owned by one of this slab's
+    /// slots.
+    ///
+    /// Since boxed items are not stored inline, this must check every slot.
+    pub(crate) fn contains_boxed(&self, item: *const T) -> bool {
+        // Items are only taken out of a slot while the pool is locked for
+        // writing, so a slot that isn't vacant keeps its item while this
+        // runs.
+        self.inner
+            .iter()
+            .any(|slot| !slot.is_vacant() && ptr::eq(&**slot.item, item))
+    }
+}
+
+#[cfg(feature = "growable")]
 impl<T> Slot<Box<T>> {
-    pub fn as_ptr(&mut self) -> ptr::NonNull<T> {
-        ptr::NonNull::from(self.item.deref_mut())
+    pub(crate) fn as_ptr(&mut self) -> ptr::NonNull<T> {
+        ptr::NonNull::from(&mut **self.item)
+    }
+}
+
+/// Returns a key to the checkout of the slot at `idx` with the given
+/// generation.
+///
+/// # Panics
+///
+/// If `idx` does not fit in 32 bits.
+pub fn key(idx: usize, generation: usize) -> Key {
+    let index = u32::try_from(idx).expect("slot index does not fit in a key");
+    Key::new(index, (generation / GENERATION_STEP) as u32)
+}
+
+/// Returns the version of an item checked out with the given generation.
+///
+/// A slot's generation advances every time it's released, and every
+/// mutable checkout follows a release, so no two mutable checkouts of a slot
+/// share a version (until it wraps around).
+pub(crate) fn version(generation: usize) -> usize {
+    generation / GENERATION_STEP
+}
+
+/// Clears an item for reuse.
+///
+/// In debug builds, this also checks that the item's `Clear` implementation
+/// retained its capacity.
+#[inline]
+pub(crate) fn clear<T: Clear>(item: &mut T) {
+    let before = if cfg!(debug_assertions) {
+        item.retained_capacity()
+    } else {
+        None
+    };
+    item.clear();
+    if let Some(before) = before {
+        let after = item.retained_capacity().unwrap_or(0);
+        assert!(
+            after >= before,
+            "`Clear` implementation for `{}` did not retain capacity (before: {}, after: {})",
+            std::any::type_name::<T>(),
+            before,
+            after,
+        );
+    }
+}
+
+/// Marks the calling branch as unlikely to be taken.
+#[cold]
+#[inline]
+fn cold() {}
+
+#[cfg(test)]
+mod tests {
+    //! Slab internals that the pools' tests can't reach. Like the pools'
+    //! tests, these run under `loom`, whose atomics the slab uses in tests.
+    use super::*;
+
+    #[test]
+    fn checkout_finds_slot_released_before_used_is_decremented() {
+        loom::fuzz(|| {
+            let slab = Slab::from_fn(1, &mut String::new);
+            let slot = unsafe { slab.try_checkout().expect("slab has a free slot").as_ref() };
+            assert_eq!(slab.try_checkout().unwrap_err(), Error::AtCapacity);
+
+            // Release the slot the way `drop_ref` does, but stop short of
+            // decrementing `used`, as if the release were still in flight.
+            assert!(slot.unlink(&slab, slot.generation(), false));
+            slot.push_free(&slab);
+            assert_eq!(slab.used(), 1);
+
+            assert!(slab.try_checkout().is_ok());
+        });
+    }
+
+    /// Checks out a slot, if one is free, and returns it with the generation
+    /// it was checked out with.
+    fn checkout(slab: &Slab<String>) -> Option<(&Slot<String>, usize)> {
+        let slot = unsafe { slab.try_checkout().ok()?.as_ref() };
+        Some((slot, slot.generation()))
+    }
+
+    /// Races `release`, run by a thread that checks out and releases slots,
+    /// with a checkout on another thread and one on this thread, and then
+    /// checks that every slot that wasn't left checked out is free.
+    // `loom`'s threads don't need the slab to be `Send`, and unlike the pools'
+    // `Arc`, `std`'s doesn't add atomics for `loom` to explore.
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn race_checkouts(release: fn(&Slab<String>)) {
+        let slab = std::sync::Arc::new(Slab::from_fn(2, &mut String::new));
+        let releaser = {
+            let slab = slab.clone();
+            loom::thread::spawn(move || release(&slab))
+        };
+        let other = {
+            let slab = slab.clone();
+            loom::thread::spawn(move || slab.try_checkout().is_ok())
+        };
+
+        let mine = slab.try_checkout().is_ok();
+        releaser.join().expect("thread panicked");
+        let other = other.join().expect("thread panicked");
+        let held = mine as usize + other as usize;
+
+        let audit = slab.audit();
+        assert_eq!(audit.checked_out, held);
+        assert_eq!(audit.free, 2 - held);
+    }
+
+    #[test]
+    fn checkouts_race_with_release_of_a_popped_slot() {
+        // A checkout that loaded the head of the free list before another
+        // thread checked that slot out and released it must not pop it with a
+        // stale `next`, which a third thread may have checked out since.
+        loom::fuzz(|| {
+            race_checkouts(|slab| {
+                if let Some((slot, generation)) = checkout(slab) {
+                    let _ = unsafe { slot.drop_ref(slab, generation) };
+                }
+            })
+        });
+    }
+
+    #[test]
+    fn checkouts_race_with_release_all() {
+        // Likewise when the released slots are pushed as one chain, as they
+        // are when an `OwnedSet` is dropped.
+        loom::fuzz(|| {
+            race_checkouts(|slab| {
+                let set: Vec<_> = (0..2).filter_map(|_| checkout(slab)).collect();
+                let _ = slab.release_all(set);
+            })
+        });
+    }
+
+    #[test]
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn get_races_with_failing_checkout() {
+        loom::fuzz(|| {
+            let slab = std::sync::Arc::new(Slab::from_fn(2, &mut String::new));
+            let (first, generation) = checkout(&slab).expect("slab has a free slot");
+            let (_, second_generation) = checkout(&slab).expect("slab has a free slot");
+            first.mark_shared();
+            let key = key(0, generation);
+            assert!(!unsafe { first.drop_ref(&slab, generation) });
+
+            // Releasing the second slot while a checkout is popping the first
+            // makes the checkout fail. Until it does, it holds a count on the
+            // first slot, which a lookup by the first slot's stale key must
+            // neither add to nor release.
+            let checkout = {
+                let slab = slab.clone();
+                loom::thread::spawn(move || slab.try_checkout().is_ok())
+            };
+            let release = {
+                let slab = slab.clone();
+                loom::thread::spawn(move || {
+                    let _ = unsafe { slab.inner[1].drop_ref(&slab, second_generation) };
+                })
+            };
+
+            assert_eq!(slab.get(key), Err(false));
+            release.join().expect("thread panicked");
+            let held = checkout.join().expect("thread panicked") as usize;
+            let audit = slab.audit();
+            assert_eq!(audit.checked_out, held);
+            assert_eq!(audit.free, 2 - held);
+        });
+    }
+
+    #[test]
+    fn unsharing_slot_cannot_be_acquired_or_looked_up() {
+        loom::fuzz(|| {
+            let slab = Slab::from_fn(1, &mut String::new);
+            let slot = unsafe { slab.try_checkout().expect("slab has a free slot").as_ref() };
+            slot.mark_shared();
+            let key = key(0, slot.generation());
+
+            // Stop partway through `try_unshare`. A checkout that found the
+            // slot at the head of the free list before it was checked out
+            // must not be able to claim it.
+            slot.ref_count
+                .compare_exchange(1, UNSHARING, Ordering::Acquire, Ordering::Relaxed)
+                .expect("the only reference");
+            assert!(slot.try_claim().is_err());
+            assert_eq!(slab.get(key), Err(false));
+        });
     }
 }
@@ -0,0 +1,298 @@
+//! Storage for a slab's slots, with a configurable alignment.
+use super::{GrowError, Slot};
+use std::{
+    alloc::{self, GlobalAlloc, Layout},
+    cmp, fmt,
+    marker::PhantomData,
+    mem,
+    ops::Index,
+    ptr,
+    sync::Arc,
+};
+
+/// The allocator that a slab's slots are allocated with.
+///
+/// This is the global allocator, unless a custom one was provided with
+/// `Builder::with_allocator`. Only the slot array itself is allocated this
+/// way; boxed items in growable pools use the global allocator as usual.
+#[derive(Clone, Default)]
+pub(crate) struct Allocator {
+    custom: Option<Arc<dyn GlobalAlloc + Send + Sync>>,
+}
+
+/// A growable array of slots, like a `Vec<Slot<T>>`, except that each slot
+/// starts on a boundary of (at least) a runtime-configurable alignment.
+///
+/// Since a `Slot` stores its item first, aligning the slot aligns the item.
+/// When the requested alignment is larger than the slot's own alignment, the
+/// slots are padded so that every one of them is aligned.
+pub(crate) struct Slots<T> {
+    ptr: ptr::NonNull<u8>,
+    len: usize,
+    cap: usize,
+    /// The distance in bytes between the start of consecutive slots.
+    stride: usize,
+    align: usize,
+    alloc: Allocator,
+    /// The array owns its slots.
+    slots: PhantomData<Slot<T>>,
+}
+
+// Slots are only shared and sent between threads in the ways that a
+// `Vec<Slot<T>>` would be.
+unsafe impl<T: Send> Send for Slots<T> {}
+unsafe impl<T: Sync> Sync for Slots<T> {}
+
+impl<T> Slots<T> {
+    /// Returns an empty array of slots aligned to `align` bytes, or to the
+    /// slot type's own alignment, if that's larger.
+    ///
+    /// # Panics
+    ///
+    /// If `align` is not a power of two.
+    pub(crate) fn with_alignment(align: usize) -> Self {
+        assert!(
+            align.is_power_of_two(),
+            "item alignment must be a power of two, but was {}",
+            align
+        );
+        let align = cmp::max(align, mem::align_of::<Slot<T>>());
+        let size = mem::size_of::<Slot<T>>();
+        let stride = (size + align - 1) & !(align - 1);
+        Self {
+            ptr: dangling(align),
+            len: 0,
+            cap: 0,
+            stride,
+            align,
+            alloc: Allocator::default(),
+            slots: PhantomData,
+        }
+    }
+
+    /// Allocates slots with `alloc` from now on.
+    ///
+    /// # Panics
+    ///
+    /// If any slots have already been allocated.
+    pub(crate) fn set_allocator(&mut self, alloc: Allocator) {
+        assert_eq!(
+            self.cap, 0,
+            "the allocator must be set before any slots are allocated"
+        );
+        self.alloc = alloc;
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the distance in bytes between the start of consecutive slots.
+    #[inline]
+    pub(crate) fn stride(&self) -> usize {
+        self.stride
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *const u8 {
+        self.ptr.as_ptr()
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, idx: usize) -> Option<&Slot<T>> {
+        if idx < self.len {
+            Some(unsafe { &*self.slot_ptr(idx) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if `slot` is one of this array's slots.
+    pub(crate) fn owns(&self, slot: &Slot<T>) -> bool {
+        let start = self.ptr.as_ptr() as usize;
+        let addr = slot as *const Slot<T> as usize;
+        addr >= start
+            && addr < start + self.len * self.stride
+            && (addr - start).is_multiple_of(self.stride)
+    }
+
+    /// Returns the index of `slot`, which must be one of this array's slots.
+    #[inline]
+    pub(crate) fn index_of(&self, slot: &Slot<T>) -> usize {
+        debug_assert!(self.owns(slot), "slot does not belong to this array");
+        let offset = (slot as *const Slot<T> as usize) - (self.ptr.as_ptr() as usize);
+        offset / self.stride
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &Slot<T>> + '_ {
+        (0..self.len).map(move |idx| unsafe { &*self.slot_ptr(idx) })
+    }
+
+    #[cfg(feature = "growable")]
+    pub(crate) fn iter_mut(&mut self) -> impl Iterator<Item = &mut Slot<T>> + '_ {
+        let (ptr, stride) = (self.ptr, self.stride);
+        (0..self.len)
+            .map(move |idx| unsafe { &mut *(ptr.as_ptr().add(idx * stride) as *mut Slot<T>) })
+    }
+
+    /// Appends `additional` slots, constructing each by calling `new` with its
+    /// index, or returns an error if they can't be allocated.
+    ///
+    /// If this fails, no slots are constructed and the array is unchanged.
+    pub(crate) fn try_extend(
+        &mut self,
+        additional: usize,
+        mut new: impl FnMut(usize) -> Slot<T>,
+    ) -> Result<(), GrowError> {
+        self.try_reserve(additional)?;
+        for _ in 0..additional {
+            // Write each slot before counting it, so that if `new` panics,
+            // only initialized slots are dropped.
+            let slot = new(self.len);
+            unsafe { ptr::write(self.slot_ptr(self.len), slot) };
+            self.len += 1;
+        }
+        Ok(())
+    }
+
+    /// Consumes the array, returning each slot in order.
+    pub(crate) fn into_vec(self) -> Vec<Slot<T>> {
+        let mut this = mem::ManuallyDrop::new(self);
+        let slots = (0..this.len)
+            .map(|idx| unsafe { ptr::read(this.slot_ptr(idx)) })
+            .collect();
+        // Every slot has been moved out, so only the allocation is left.
+        this.len = 0;
+        unsafe { this.dealloc() };
+        slots
+    }
+
+    fn try_reserve(&mut self, additional: usize) -> Result<(), GrowError> {
+        let cap = self
+            .len
+            .checked_add(additional)
+            .ok_or_else(GrowError::capacity_overflow)?;
+        if cap <= self.cap {
+            return Ok(());
+        }
+
+        let layout = Self::try_layout(cap, self.stride, self.align)?;
+        let ptr = unsafe { self.alloc.alloc(layout) };
+        let ptr = ptr::NonNull::new(ptr).ok_or_else(|| GrowError::alloc_failed(layout))?;
+        unsafe {
+            // Slots are never referenced while the array is borrowed mutably,
+            // so they may be moved to the new allocation.
+            ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len * self.stride);
+            self.dealloc();
+        }
+        self.ptr = ptr;
+        self.cap = cap;
+        Ok(())
+    }
+
+    fn try_layout(cap: usize, stride: usize, align: usize) -> Result<Layout, GrowError> {
+        cap.checked_mul(stride)
+            .and_then(|size| Layout::from_size_align(size, align).ok())
+            .ok_or_else(GrowError::capacity_overflow)
+    }
+
+    fn layout(&self, cap: usize) -> Layout {
+        // The current capacity was allocated, so its layout is valid.
+        Self::try_layout(cap, self.stride, self.align).expect("slab capacity overflow")
+    }
+
+    #[inline]
+    fn slot_ptr(&self, idx: usize) -> *mut Slot<T> {
+        unsafe { self.ptr.as_ptr().add(idx * self.stride) as *mut Slot<T> }
+    }
+
+    /// Frees the allocation, without dropping any slots.
+    unsafe fn dealloc(&mut self) {
+        if self.cap != 0 {
+            self.alloc.dealloc(self.ptr.as_ptr(), self.layout(self.cap));
+        }
+    }
+}
+
+impl<T> Index<usize> for Slots<T> {
+    type Output = Slot<T>;
+
+    #[inline]
+    fn index(&self, idx: usize) -> &Slot<T> {
+        match self.get(idx) {
+            Some(slot) => slot,
+            None => panic!("slot index {} out of bounds (len {})", idx, self.len),
+        }
+    }
+}
+
+#[cfg(feature = "growable")]
+impl<T> std::ops::IndexMut<usize> for Slots<T> {
+    #[inline]
+    fn index_mut(&mut self, idx: usize) -> &mut Slot<T> {
+        assert!(
+            idx < self.len,
+            "slot index {} out of bounds (len {})",
+            idx,
+            self.len
+        );
+        unsafe { &mut *self.slot_ptr(idx) }
+    }
+}
+
+impl<T> Drop for Slots<T> {
+    fn drop(&mut self) {
+        unsafe {
+            for idx in 0..self.len {
+                ptr::drop_in_place(self.slot_ptr(idx));
+            }
+            self.dealloc();
+        }
+    }
+}
+
+impl<T> fmt::Debug for Slots<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+// === impl Allocator ===
+
+impl Allocator {
+    pub(crate) fn new(alloc: impl GlobalAlloc + Send + Sync + 'static) -> Self {
+        Self {
+            custom: Some(Arc::new(alloc)),
+        }
+    }
+
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        match self.custom {
+            Some(ref custom) => custom.alloc(layout),
+            None => alloc::alloc(layout),
+        }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        match self.custom {
+            Some(ref custom) => custom.dealloc(ptr, layout),
+            None => alloc::dealloc(ptr, layout),
+        }
+    }
+}
+
+impl fmt::Debug for Allocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.custom {
+            Some(_) => f.pad("Allocator::Custom"),
+            None => f.pad("Allocator::Global"),
+        }
+    }
+}
+
+/// Returns a well-aligned, non-null pointer for an empty array.
+fn dangling(align: usize) -> ptr::NonNull<u8> {
+    // `align` is a non-zero power of two, so it is a valid, aligned address.
+    unsafe { ptr::NonNull::new_unchecked(align as *mut u8) }
+}
@@ -2,16 +2,298 @@ pub use self::inner::*;
 
 #[cfg(test)]
 mod inner {
-    pub use loom::sync::Arc;
-    // TODO: when `loom` supports `RwLock`, fuzz the growable slab implementation.
-    pub use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+    pub use self::arc::{Arc, Weak};
+    pub use self::rwlock::RwLock;
+    #[cfg(feature = "growable")]
+    pub use self::rwlock::RwLockReadGuard;
+    #[cfg(feature = "growable")]
+    pub use std::sync::TryLockError;
+    pub use std::sync::{Condvar, Mutex};
     pub mod atomic {
         pub use loom::sync::atomic::AtomicUsize;
-        pub use std::sync::atomic::{spin_loop_hint, Ordering};
+        pub use std::sync::atomic::Ordering;
+    }
+
+    mod arc {
+        //! The `Arc` that keeps a pool's storage alive.
+        //!
+        //! Like `loom::sync::Arc`, this counts its references with a `loom`
+        //! atomic, so that `loom` models dropping a pool's last reference as
+        //! ordered after every access through the others. `loom`'s own `Arc`
+        //! only supports `new`, `clone` and `Deref`, so this wraps `std`'s,
+        //! and supports the rest of its API that the pools and the registry
+        //! use.
+        // Only the fixed pool uses all of it.
+        #![cfg_attr(not(feature = "fixed"), allow(dead_code))]
+        use loom::sync::atomic::AtomicUsize;
+        use std::{fmt, mem, ops::Deref, ptr, sync::atomic::Ordering};
+
+        pub struct Arc<T> {
+            inner: std::sync::Arc<Inner<T>>,
+        }
+
+        pub struct Weak<T> {
+            inner: std::sync::Weak<Inner<T>>,
+        }
+
+        /// The value is stored first, so that a pointer to it is also a
+        /// pointer to the `Inner` (see `as_ptr` and `from_raw`).
+        #[repr(C)]
+        struct Inner<T> {
+            value: T,
+            refs: AtomicUsize,
+        }
+
+        impl<T> Arc<T> {
+            pub fn new(value: T) -> Self {
+                Self {
+                    inner: std::sync::Arc::new(Inner {
+                        value,
+                        refs: AtomicUsize::new(1),
+                    }),
+                }
+            }
+
+            pub fn try_unwrap(this: Self) -> Result<T, Self> {
+                let this = mem::ManuallyDrop::new(this);
+                let inner = unsafe { ptr::read(&this.inner) };
+                match std::sync::Arc::try_unwrap(inner) {
+                    Ok(inner) => {
+                        inner.refs.fetch_sub(1, Ordering::AcqRel);
+                        Ok(inner.value)
+                    }
+                    Err(inner) => Err(Self { inner }),
+                }
+            }
+
+            pub fn strong_count(this: &Self) -> usize {
+                std::sync::Arc::strong_count(&this.inner)
+            }
+
+            pub fn downgrade(this: &Self) -> Weak<T> {
+                Weak {
+                    inner: std::sync::Arc::downgrade(&this.inner),
+                }
+            }
+
+            pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+                std::sync::Arc::ptr_eq(&this.inner, &other.inner)
+            }
+
+            pub fn as_ptr(this: &Self) -> *const T {
+                std::sync::Arc::as_ptr(&this.inner) as *const T
+            }
+
+            /// # Safety
+            ///
+            /// As for `std::sync::Arc::from_raw`.
+            pub unsafe fn from_raw(ptr: *const T) -> Self {
+                Self {
+                    inner: std::sync::Arc::from_raw(ptr as *const Inner<T>),
+                }
+            }
+        }
+
+        impl<T> Weak<T> {
+            pub fn upgrade(&self) -> Option<Arc<T>> {
+                let inner = self.inner.upgrade()?;
+                inner.refs.fetch_add(1, Ordering::Relaxed);
+                Some(Arc { inner })
+            }
+
+            pub fn strong_count(&self) -> usize {
+                self.inner.strong_count()
+            }
+        }
+
+        impl<T> Clone for Arc<T> {
+            fn clone(&self) -> Self {
+                self.inner.refs.fetch_add(1, Ordering::Relaxed);
+                Self {
+                    inner: self.inner.clone(),
+                }
+            }
+        }
+
+        impl<T> Drop for Arc<T> {
+            fn drop(&mut self) {
+                self.inner.refs.fetch_sub(1, Ordering::AcqRel);
+            }
+        }
+
+        impl<T> Deref for Arc<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.inner.value
+            }
+        }
+
+        impl<T: fmt::Debug> fmt::Debug for Arc<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.inner.value.fmt(f)
+            }
+        }
+    }
+
+    mod rwlock {
+        //! The lock that guards a growable pool's storage.
+        //!
+        //! `loom` has no `RwLock`, so this builds one out of its `Mutex` and
+        //! `Condvar`, so that `loom` models threads waiting for each other to
+        //! grow the pool. Accesses through the guards are checked by a
+        //! `CausalCell`, so a guard that doesn't exclude a writer is caught.
+        //! The lock is never poisoned.
+        use loom::sync::{CausalCell, Condvar, Mutex, MutexGuard};
+        use std::{
+            cell::UnsafeCell,
+            fmt,
+            ops::{Deref, DerefMut},
+            sync::{LockResult, TryLockError, TryLockResult},
+        };
+
+        pub struct RwLock<T> {
+            state: Mutex<State>,
+            changed: Condvar,
+            data: UnsafeCell<T>,
+            causality: CausalCell<()>,
+        }
+
+        pub struct RwLockReadGuard<'a, T> {
+            lock: &'a RwLock<T>,
+        }
+
+        pub struct RwLockWriteGuard<'a, T> {
+            lock: &'a RwLock<T>,
+        }
+
+        #[derive(Default)]
+        struct State {
+            readers: usize,
+            writing: bool,
+            waiting: usize,
+        }
+
+        impl<T> RwLock<T> {
+            pub fn new(data: T) -> Self {
+                Self {
+                    state: Mutex::new(State::default()),
+                    changed: Condvar::new(),
+                    data: UnsafeCell::new(data),
+                    causality: CausalCell::new(()),
+                }
+            }
+
+            pub fn read(&self) -> LockResult<RwLockReadGuard<'_, T>> {
+                let mut state = self.wait_while(|state| state.writing);
+                state.readers += 1;
+                // `Condvar` can only wake one thread at a time, so pass the
+                // wakeup along to any other readers that are waiting.
+                self.notify(state);
+                Ok(RwLockReadGuard { lock: self })
+            }
+
+            pub fn try_read(&self) -> TryLockResult<RwLockReadGuard<'_, T>> {
+                let mut state = self.state.lock().unwrap();
+                if state.writing {
+                    return Err(TryLockError::WouldBlock);
+                }
+                state.readers += 1;
+                Ok(RwLockReadGuard { lock: self })
+            }
+
+            pub fn write(&self) -> LockResult<RwLockWriteGuard<'_, T>> {
+                let mut state = self.wait_while(|state| state.writing || state.readers > 0);
+                state.writing = true;
+                Ok(RwLockWriteGuard { lock: self })
+            }
+
+            #[cfg(feature = "growable")]
+            pub fn into_inner(self) -> LockResult<T> {
+                Ok(self.data.into_inner())
+            }
+
+            fn wait_while(&self, blocked: impl Fn(&State) -> bool) -> MutexGuard<'_, State> {
+                let mut state = self.state.lock().unwrap();
+                while blocked(&state) {
+                    state.waiting += 1;
+                    state = self.changed.wait(state).unwrap();
+                    state.waiting -= 1;
+                }
+                state
+            }
+
+            /// Wakes a waiting thread, if there is one.
+            ///
+            /// Notifying the `Condvar` is a point at which `loom` switches
+            /// threads, so skipping it when no one is waiting keeps the number
+            /// of interleavings to explore down.
+            fn notify(&self, state: MutexGuard<'_, State>) {
+                let waiting = state.waiting > 0;
+                drop(state);
+                if waiting {
+                    self.changed.notify_one();
+                }
+            }
+        }
+
+        impl<T: fmt::Debug> fmt::Debug for RwLock<T> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.try_read() {
+                    Ok(guard) => f.debug_struct("RwLock").field("data", &&*guard).finish(),
+                    Err(_) => f.write_str("RwLock { <locked> }"),
+                }
+            }
+        }
+
+        impl<T> Deref for RwLockReadGuard<'_, T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                self.lock.causality.with(|_| ());
+                unsafe { &*self.lock.data.get() }
+            }
+        }
+
+        impl<T> Drop for RwLockReadGuard<'_, T> {
+            fn drop(&mut self) {
+                let mut state = self.lock.state.lock().unwrap();
+                state.readers -= 1;
+                if state.readers == 0 {
+                    self.lock.notify(state);
+                }
+            }
+        }
+
+        impl<T> Deref for RwLockWriteGuard<'_, T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                self.lock.causality.with(|_| ());
+                unsafe { &*self.lock.data.get() }
+            }
+        }
+
+        impl<T> DerefMut for RwLockWriteGuard<'_, T> {
+            fn deref_mut(&mut self) -> &mut T {
+                self.lock.causality.with_mut(|_| ());
+                unsafe { &mut *self.lock.data.get() }
+            }
+        }
+
+        impl<T> Drop for RwLockWriteGuard<'_, T> {
+            fn drop(&mut self) {
+                let mut state = self.lock.state.lock().unwrap();
+                state.writing = false;
+                self.lock.notify(state);
+            }
+        }
     }
 }
 
 #[cfg(not(test))]
 mod inner {
-    pub use std::sync::{atomic, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+    pub use std::sync::{atomic, Arc, Condvar, Mutex, RwLock, Weak};
+    #[cfg(feature = "growable")]
+    pub use std::sync::{RwLockReadGuard, TryLockError};
 }
@@ -0,0 +1,52 @@
+//! Helpers for testing [`Clear`] implementations.
+//!
+//! Pools rely on [`Clear`] retaining an item's allocated capacity; an
+//! implementation that releases it doesn't break anything, but quietly turns
+//! every checkout back into an allocation. These helpers let users check their
+//! own implementations in their test suites.
+//!
+//! [`Clear`]: ../trait.Clear.html
+use crate::traits::{Clear, HasCapacity, WithCapacity};
+use std::any::type_name;
+
+/// Asserts that clearing a `T` retains its allocated capacity.
+///
+/// This constructs items of type `T` with a range of capacities, clears them,
+/// and checks that their capacity did not shrink. To test items that have
+/// been filled with data, use [`assert_clear_retains_capacity_of`].
+///
+/// # Panics
+///
+/// If `T`'s [`Clear`] implementation releases capacity.
+///
+/// [`assert_clear_retains_capacity_of`]: fn.assert_clear_retains_capacity_of.html
+/// [`Clear`]: ../trait.Clear.html
+pub fn assert_clear_retains_capacity<T>()
+where
+    T: Clear + WithCapacity,
+{
+    for &cap in &[0, 1, 16, 1024] {
+        assert_clear_retains_capacity_of(T::with_capacity(cap));
+    }
+}
+
+/// Asserts that clearing `item` retains its allocated capacity.
+///
+/// # Panics
+///
+/// If clearing `item` releases capacity.
+pub fn assert_clear_retains_capacity_of<T>(mut item: T)
+where
+    T: Clear + HasCapacity,
+{
+    let before = item.capacity();
+    item.clear();
+    let after = item.capacity();
+    assert!(
+        after >= before,
+        "`Clear` implementation for `{}` did not retain capacity (before: {}, after: {})",
+        type_name::<T>(),
+        before,
+        after,
+    );
+}
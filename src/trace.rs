@@ -0,0 +1,100 @@
+//! Tracing for checkouts that are held for too long, or that are released on a
+//! different thread than they were checked out on.
+//!
+//! Either is a common symptom of a checkout being held across an `.await`
+//! point, which starves other tasks of the pool's items. This is only
+//! recorded when the `tracing` feature is enabled, and the pool was built
+//! with [`Builder::trace_checkouts`].
+//!
+//! When the `tracing` feature is enabled, growable pools also record an event
+//! every time they grow, since unexpected growth is often caused by leaked
+//! checkouts, and pools with a lease record an event for every checkout
+//! whose lease expired before it was reclaimed.
+//!
+//! [`Builder::trace_checkouts`]: ../struct.Builder.html#method.trace_checkouts
+#![cfg_attr(not(feature = "tracing"), allow(dead_code))]
+use std::time::Duration;
+
+/// The start of a traced checkout's lifetime.
+#[derive(Debug)]
+pub(crate) struct Checkout {
+    #[cfg(feature = "tracing")]
+    #[cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+    start: Option<Start>,
+}
+
+#[cfg(feature = "tracing")]
+#[cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+#[derive(Debug)]
+struct Start {
+    at: std::time::Instant,
+    thread: std::thread::ThreadId,
+    threshold: Duration,
+}
+
+impl Checkout {
+    /// Starts tracing a checkout, if `threshold` is set.
+    #[inline]
+    pub(crate) fn start(_threshold: Option<Duration>) -> Self {
+        Self {
+            #[cfg(feature = "tracing")]
+            start: _threshold.map(|threshold| Start {
+                at: std::time::Instant::now(),
+                thread: std::thread::current().id(),
+                threshold,
+            }),
+        }
+    }
+
+    /// Records the end of the checkout of the slot at index `idx`.
+    #[cfg(any(feature = "fixed", feature = "growable"))]
+    #[inline]
+    pub(crate) fn finish(&self, _idx: usize) {
+        #[cfg(feature = "tracing")]
+        if let Some(ref start) = self.start {
+            let held_for = start.at.elapsed();
+            if held_for > start.threshold {
+                tracing::warn!(
+                    idx = _idx,
+                    ?held_for,
+                    threshold = ?start.threshold,
+                    "checkout held for longer than expected"
+                );
+            }
+            if std::thread::current().id() != start.thread {
+                tracing::warn!(
+                    idx = _idx,
+                    ?held_for,
+                    "checkout released on a different thread than it was checked out on"
+                );
+            }
+        }
+    }
+}
+
+/// Records that the pool with id `pool` grew by a block of `block` slots, to
+/// `capacity` slots in total.
+#[cfg(feature = "growable")]
+#[inline]
+pub(crate) fn grew(_pool: usize, _block: usize, _capacity: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::info!(
+        pool = _pool,
+        block = _block,
+        capacity = _capacity,
+        "pool grew"
+    );
+}
+
+/// Records that the slot at index `idx` in the pool with id `pool` was
+/// reclaimed, `overdue` after its checkout's lease expired.
+#[inline]
+pub(crate) fn reclaimed(_pool: usize, _idx: usize, _overdue: Duration) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        pool = _pool,
+        idx = _idx,
+        overdue = ?_overdue,
+        "reclaimed a checkout whose lease expired"
+    );
+}
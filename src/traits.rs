@@ -1,4 +1,9 @@
-use std::{collections, hash, ops::DerefMut};
+use std::{
+    any::Any,
+    collections, fmt, hash, hint,
+    mem::MaybeUninit,
+    ops::{Deref, DerefMut},
+};
 
 pub trait Clear {
     /// Clear all data in `self`, retaining the allocated capacithy.
@@ -8,8 +13,72 @@ pub trait Clear {
     /// This should only be implemented for types whose clear operation *retains
     /// any allocations* for that type. Types such as `BTreeMap`, whose
     /// `clear()` method releases the existing allocation, should *not*
-    /// implement this trait.
+    /// implement this trait. [`testing::assert_clear_retains_capacity`] may
+    /// be used to test that an implementation upholds this.
+    ///
+    /// [`testing::assert_clear_retains_capacity`]: ../testing/fn.assert_clear_retains_capacity.html
     fn clear(&mut self);
+
+    /// Returns the capacity that `clear` should retain, if `self` has one.
+    ///
+    /// In debug builds, pools check that clearing an item for reuse did not
+    /// reduce its retained capacity, so that a `Clear` implementation which
+    /// releases its allocation is caught rather than silently defeating the
+    /// pool. The default implementation returns `None`, which disables the
+    /// check.
+    #[inline]
+    fn retained_capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Adapts a type that does not implement [`Clear`] by clearing it with a
+/// closure.
+///
+/// This allows pooling types from other crates, which cannot implement `Clear`
+/// due to the orphan rules, without writing a newtype. A `ClearWith`
+/// dereferences to the wrapped item. See also [`Builder::with_clear_fn`].
+///
+/// [`Clear`]: trait.Clear.html
+/// [`Builder::with_clear_fn`]: ../struct.Builder.html#method.with_clear_fn
+#[derive(Clone)]
+pub struct ClearWith<T, F> {
+    item: T,
+    clear: F,
+}
+
+/// A [`Clear`]able type that can be used as a trait object and downcast back
+/// to its concrete type.
+///
+/// This is implemented for every `'static` type that implements `Clear`. A
+/// pool of [`AnyItem`]s may hold items of different types, and checkouts of
+/// them can be recovered as their concrete types with `Owned::downcast`.
+///
+/// [`Clear`]: trait.Clear.html
+/// [`AnyItem`]: type.AnyItem.html
+pub trait AnyClear: Any + Clear {
+    /// Returns `self` as a `&dyn Any`.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns `self` as a `&mut dyn Any`.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+/// A boxed item of any [`AnyClear`] type, which may be pooled.
+///
+/// [`AnyClear`]: trait.AnyClear.html
+pub type AnyItem = Box<dyn AnyClear + Send + Sync>;
+
+/// Buffers whose allocated memory can be touched ahead of time, so that the
+/// first write to a freshly allocated buffer does not incur page faults.
+///
+/// See [`Builder::pre_touch`].
+///
+/// [`Builder::pre_touch`]: ../struct.Builder.html#method.pre_touch
+pub trait PreTouch {
+    /// Writes to every page of `self`'s allocated capacity, without changing
+    /// its contents.
+    fn pre_touch(&mut self);
 }
 
 pub trait HasCapacity {
@@ -22,16 +91,66 @@ pub trait WithCapacity: HasCapacity {
     fn with_capacity(cap: usize) -> Self;
 }
 
+/// An item that can be split into two disjoint parts, which may then be
+/// used independently.
+///
+/// See `Owned::split`.
+pub trait Split {
+    /// The type of each part.
+    type Part: ?Sized;
+
+    /// Splits `self` into the part before `at`, and the part from `at`
+    /// onwards.
+    ///
+    /// # Panics
+    ///
+    /// If `at` is out of bounds, or (for strings) not on a character
+    /// boundary.
+    fn split_at_mut(&mut self, at: usize) -> (&mut Self::Part, &mut Self::Part);
+}
+
 // ===== impl Clear =====
 
 impl<T> Clear for Box<T>
 where
-    T: Clear,
+    T: Clear + ?Sized,
 {
     #[inline]
     fn clear(&mut self) {
         self.deref_mut().clear()
     }
+
+    #[inline]
+    fn retained_capacity(&self) -> Option<usize> {
+        self.deref().retained_capacity()
+    }
+}
+
+impl<T> AnyClear for T
+where
+    T: Any + Clear,
+{
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl fmt::Debug for dyn AnyClear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("AnyClear { .. }")
+    }
+}
+
+impl fmt::Debug for dyn AnyClear + Send + Sync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("AnyClear { .. }")
+    }
 }
 
 impl<T> Clear for Vec<T> {
@@ -39,6 +158,11 @@ impl<T> Clear for Vec<T> {
     fn clear(&mut self) {
         Vec::clear(self)
     }
+
+    #[inline]
+    fn retained_capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
 }
 
 impl<K, V, S> Clear for collections::HashMap<K, V, S>
@@ -50,6 +174,11 @@ where
     fn clear(&mut self) {
         collections::HashMap::clear(self)
     }
+
+    #[inline]
+    fn retained_capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
 }
 
 impl<T, S> Clear for collections::HashSet<T, S>
@@ -61,6 +190,11 @@ where
     fn clear(&mut self) {
         collections::HashSet::clear(self)
     }
+
+    #[inline]
+    fn retained_capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
 }
 
 impl Clear for String {
@@ -68,6 +202,111 @@ impl Clear for String {
     fn clear(&mut self) {
         String::clear(self)
     }
+
+    #[inline]
+    fn retained_capacity(&self) -> Option<usize> {
+        Some(self.capacity())
+    }
+}
+
+// ===== impl ClearWith =====
+
+impl<T, F> ClearWith<T, F>
+where
+    F: Fn(&mut T),
+{
+    pub fn new(item: T, clear: F) -> Self {
+        Self { item, clear }
+    }
+}
+
+impl<T, F> ClearWith<T, F> {
+    pub fn into_inner(self) -> T {
+        self.item
+    }
+}
+
+impl<T, F> Clear for ClearWith<T, F>
+where
+    F: Fn(&mut T),
+{
+    #[inline]
+    fn clear(&mut self) {
+        (self.clear)(&mut self.item)
+    }
+}
+
+impl<T, F> Deref for ClearWith<T, F> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        &self.item
+    }
+}
+
+impl<T, F> DerefMut for ClearWith<T, F> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.item
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for ClearWith<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClearWith")
+            .field("item", &self.item)
+            .finish()
+    }
+}
+
+// ===== impl PreTouch =====
+
+/// The page size assumed when pre-touching buffers. On systems with larger
+/// pages, this touches some pages more than once, which is harmless.
+const PAGE_SIZE: usize = 4096;
+
+fn touch_pages(buf: &mut [MaybeUninit<u8>]) {
+    // The buffer needn't start on a page boundary, so touch its first byte,
+    // and then the first byte of every page that starts within it. Stepping
+    // from the start of the buffer instead could miss its last page.
+    if let Some(first) = buf.first_mut() {
+        *first = MaybeUninit::new(0);
+    }
+    let boundary = buf.as_ptr().align_offset(PAGE_SIZE);
+    if let Some(pages) = buf.get_mut(boundary..) {
+        for byte in pages.iter_mut().step_by(PAGE_SIZE) {
+            *byte = MaybeUninit::new(0);
+        }
+    }
+    // Ensure the writes aren't optimized away, since nothing ever reads them.
+    hint::black_box(buf);
+}
+
+impl<T> PreTouch for Box<T>
+where
+    T: PreTouch,
+{
+    #[inline]
+    fn pre_touch(&mut self) {
+        self.deref_mut().pre_touch()
+    }
+}
+
+impl PreTouch for Vec<u8> {
+    fn pre_touch(&mut self) {
+        // Pages backing the initialized part of the buffer have already been
+        // written to.
+        touch_pages(self.spare_capacity_mut())
+    }
+}
+
+impl PreTouch for String {
+    fn pre_touch(&mut self) {
+        // Safety: writing to the vector's spare capacity doesn't change the
+        // string's contents, so it remains valid UTF-8.
+        unsafe { self.as_mut_vec() }.pre_touch()
+    }
 }
 
 // ===== impl HasCapacity =====
@@ -134,3 +373,23 @@ impl WithCapacity for String {
         String::with_capacity(cap)
     }
 }
+
+// ===== impl Split =====
+
+impl<T> Split for Vec<T> {
+    type Part = [T];
+
+    #[inline]
+    fn split_at_mut(&mut self, at: usize) -> (&mut [T], &mut [T]) {
+        self.as_mut_slice().split_at_mut(at)
+    }
+}
+
+impl Split for String {
+    type Part = str;
+
+    #[inline]
+    fn split_at_mut(&mut self, at: usize) -> (&mut str, &mut str) {
+        self.as_mut_str().split_at_mut(at)
+    }
+}
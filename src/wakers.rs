@@ -0,0 +1,174 @@
+//! Wakers for asynchronous checkouts that are waiting for an item to be
+//! released.
+// Only pools wait for slots, so without either pool flavor, nothing uses
+// this beyond the slab waking an empty queue.
+#![cfg_attr(not(any(feature = "fixed", feature = "growable")), allow(dead_code))]
+use crate::sync::Mutex;
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::atomic::{self, AtomicUsize, Ordering},
+    task::Waker,
+};
+#[cfg(feature = "fixed")]
+use std::{
+    sync::Arc,
+    task::Wake,
+    thread::{self, Thread},
+};
+
+/// A queue of tasks waiting for a slot to be released.
+///
+/// Tasks are woken in the order in which they started waiting, one per
+/// released slot.
+pub(crate) struct Wakers {
+    // This always uses `std`'s atomics, like the mutex below.
+    waiting: AtomicUsize,
+    queue: Mutex<Queue>,
+}
+
+struct Queue {
+    next_id: usize,
+    wakers: VecDeque<(usize, Waker)>,
+}
+
+/// Wakes a thread that parked itself until a slot is released.
+#[cfg(feature = "fixed")]
+struct Unpark(Thread);
+
+/// Returns a waker that unparks the current thread.
+#[cfg(feature = "fixed")]
+pub(crate) fn unpark_current() -> Waker {
+    Waker::from(Arc::new(Unpark(thread::current())))
+}
+
+// === impl Wakers ===
+
+impl Wakers {
+    pub(crate) fn new() -> Self {
+        Self {
+            waiting: AtomicUsize::new(0),
+            queue: Mutex::new(Queue {
+                next_id: 0,
+                wakers: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Registers `waker` to be woken when a slot is released.
+    ///
+    /// `id` identifies the waiting task's place in the queue. If the task is
+    /// still waiting, its waker is replaced, and it keeps its place. If it was
+    /// woken, but another checkout took the slot before it could, it goes back
+    /// to the front of the queue. Otherwise, it's added to the back.
+    ///
+    /// Once this returns, the caller must check for a free slot again, since
+    /// one may have been released before the waker was registered.
+    pub(crate) fn register(&self, id: &mut Option<usize>, waker: &Waker) {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        match *id {
+            Some(id) => match queue.wakers.iter_mut().find(|(i, _)| *i == id) {
+                Some((_, registered)) => {
+                    if !registered.will_wake(waker) {
+                        *registered = waker.clone();
+                    }
+                }
+                None => queue.wakers.push_front((id, waker.clone())),
+            },
+            None => {
+                let next = queue.next_id;
+                queue.next_id = next.wrapping_add(1);
+                queue.wakers.push_back((next, waker.clone()));
+                *id = Some(next);
+            }
+        }
+        self.waiting.store(queue.wakers.len(), Ordering::SeqCst);
+        drop(queue);
+        // Releasing a slot makes it free with a `SeqCst` operation before
+        // calling `wake`, so either the releasing thread sees this waker, or
+        // the caller's next check sees the released slot.
+        atomic::fence(Ordering::SeqCst);
+    }
+
+    /// Removes the waiting task `id` from the queue, returning `false` if it
+    /// had already been woken.
+    pub(crate) fn remove(&self, id: usize) -> bool {
+        let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+        match queue.wakers.iter().position(|(i, _)| *i == id) {
+            Some(idx) => {
+                queue.wakers.remove(idx);
+                self.waiting.store(queue.wakers.len(), Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops waiting on behalf of a task that gave up before checking out a
+    /// slot.
+    ///
+    /// If the task was already woken for a released slot, it didn't take
+    /// that slot, so the next task in the queue is woken in its place.
+    pub(crate) fn cancel(&self, id: usize) {
+        if !self.remove(id) {
+            self.wake(1);
+        }
+    }
+
+    /// Wakes up to `n` waiting tasks, since `n` slots were released.
+    ///
+    /// The slots must have been made free by a `SeqCst` operation (or
+    /// followed by a `SeqCst` fence), so that a task registering a waker
+    /// concurrently sees them if this doesn't see the task.
+    #[inline]
+    pub(crate) fn wake(&self, n: usize) {
+        if self.waiting.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+        self.wake_slow(n);
+    }
+
+    #[cold]
+    fn wake_slow(&self, n: usize) {
+        for _ in 0..n {
+            let waker = {
+                let mut queue = self.queue.lock().unwrap_or_else(|e| e.into_inner());
+                let waker = queue.wakers.pop_front();
+                self.waiting.store(queue.wakers.len(), Ordering::SeqCst);
+                waker
+            };
+            // Wake the task once the lock is released, as waking it may poll
+            // it immediately.
+            match waker {
+                Some((_, waker)) => waker.wake(),
+                None => return,
+            }
+        }
+    }
+
+    /// Returns the number of tasks waiting for a slot.
+    pub(crate) fn waiting(&self) -> usize {
+        self.waiting.load(Ordering::Relaxed)
+    }
+}
+
+impl fmt::Debug for Wakers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Wakers")
+            .field("waiting", &self.waiting())
+            .finish()
+    }
+}
+
+// === impl Unpark ===
+
+#[cfg(feature = "fixed")]
+impl Wake for Unpark {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
+}
@@ -0,0 +1,270 @@
+//! Tests that the checkout paths don't allocate.
+//!
+//! This is its own test binary, since it replaces the global allocator.
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct Counting;
+
+thread_local! {
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+    static FAIL: Cell<bool> = const { Cell::new(false) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCS.try_with(|allocs| allocs.set(allocs.get() + 1));
+        if FAIL.try_with(Cell::get).unwrap_or(false) {
+            return std::ptr::null_mut();
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the number of allocations made on this thread while running `f`.
+fn allocs_in(f: impl FnOnce()) -> usize {
+    let before = ALLOCS.with(Cell::get);
+    f();
+    ALLOCS.with(Cell::get) - before
+}
+
+/// Runs `f` with every allocation on this thread failing.
+fn failing_allocs<R>(f: impl FnOnce() -> R) -> R {
+    FAIL.with(|fail| fail.set(true));
+    let result = f();
+    FAIL.with(|fail| fail.set(false));
+    result
+}
+
+#[test]
+fn fixed_exhausted_try_checkout_does_not_allocate() {
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::with_capacity(2);
+    let _a = pool.checkout();
+    let _b = pool.checkout();
+    let allocs = allocs_in(|| {
+        for _ in 0..1000 {
+            assert!(pool.try_checkout().is_none());
+        }
+    });
+    assert_eq!(allocs, 0);
+}
+
+#[test]
+fn fixed_checkout_does_not_allocate() {
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::with_capacity(2);
+    let allocs = allocs_in(|| {
+        for _ in 0..1000 {
+            let a = pool.checkout();
+            let b = pool.try_checkout();
+            assert!(b.is_some());
+            drop((a, b));
+        }
+    });
+    assert_eq!(allocs, 0);
+}
+
+#[test]
+fn growable_exhausted_try_checkout_does_not_allocate() {
+    let pool: natatorium::growable::Pool<String> = natatorium::growable::Pool::with_capacity(2);
+    let _a = pool.checkout();
+    let _b = pool.checkout();
+    let allocs = allocs_in(|| {
+        for _ in 0..1000 {
+            assert!(pool.try_checkout().is_none());
+        }
+    });
+    assert_eq!(allocs, 0);
+}
+
+#[test]
+fn fixed_nonblocking_checkout_and_release_do_not_allocate() {
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::with_capacity(4);
+    let allocs = allocs_in(|| {
+        for _ in 0..1000 {
+            let checkouts = [
+                pool.try_checkout_nonblocking(),
+                pool.try_checkout_nonblocking(),
+                pool.try_checkout_nonblocking(),
+                pool.try_checkout_nonblocking(),
+            ];
+            assert!(checkouts.iter().all(Option::is_some));
+            assert!(pool.try_checkout_nonblocking().is_none());
+            let shared = IntoIterator::into_iter(checkouts)
+                .flatten()
+                .map(|c| c.downgrade());
+            for s in shared {
+                drop(s.clone());
+            }
+        }
+    });
+    assert_eq!(allocs, 0);
+}
+
+#[test]
+fn growable_nonblocking_checkout_and_release_do_not_allocate() {
+    let pool: natatorium::growable::Pool<String> = natatorium::growable::Pool::with_capacity(2);
+    let allocs = allocs_in(|| {
+        for _ in 0..1000 {
+            let a = pool.try_checkout_nonblocking();
+            let b = pool.try_checkout_nonblocking();
+            assert!(a.is_some() && b.is_some());
+            // The pool is never grown by a non-blocking checkout.
+            assert!(pool.try_checkout_nonblocking().is_none());
+            drop((a, b));
+        }
+    });
+    assert_eq!(allocs, 0);
+    assert_eq!(pool.capacity(), 2);
+}
+
+/// A slab allocator that tracks how many bytes it has outstanding.
+#[derive(Clone, Default)]
+struct Tracking(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+impl Tracking {
+    fn outstanding(&self) -> usize {
+        self.0.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+unsafe impl GlobalAlloc for Tracking {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.0
+            .fetch_add(layout.size(), std::sync::atomic::Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0
+            .fetch_sub(layout.size(), std::sync::atomic::Ordering::SeqCst);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn slabs_use_custom_allocator() {
+    let alloc = Tracking::default();
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::builder()
+        .with_default()
+        .with_elements(16)
+        .with_allocator(alloc.clone())
+        .finish();
+    assert!(alloc.outstanding() >= 16 * std::mem::size_of::<String>());
+    drop(pool.checkout());
+    drop(pool);
+    assert_eq!(alloc.outstanding(), 0);
+
+    let alloc = Tracking::default();
+    let pool: natatorium::growable::Pool<String> = natatorium::growable::Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_allocator(alloc.clone())
+        .finish();
+    let before = alloc.outstanding();
+    assert!(before > 0);
+    // Growing the pool reallocates its slots with the same allocator.
+    let checkouts: Vec<_> = (0..8).map(|_| pool.checkout()).collect();
+    assert!(alloc.outstanding() > before);
+    drop(checkouts);
+    drop(pool);
+    assert_eq!(alloc.outstanding(), 0);
+}
+
+#[test]
+fn fixed_slots_are_allocated_exactly() {
+    // A fixed pool's slots are a single allocation of exactly its capacity,
+    // like a boxed slice, with no room left over for growth.
+    let outstanding = |cap| {
+        let alloc = Tracking::default();
+        let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::builder()
+            .with_default()
+            .with_elements(cap)
+            .with_allocator(alloc.clone())
+            .finish();
+        let bytes = alloc.outstanding();
+        drop(pool);
+        bytes
+    };
+    let one = outstanding(1);
+    assert_eq!(outstanding(3), 3 * one);
+    assert_eq!(outstanding(100), 100 * one);
+}
+
+/// A slab allocator that fails every allocation once it's told to.
+#[derive(Clone, Default)]
+struct Failing(std::sync::Arc<std::sync::atomic::AtomicBool>);
+
+impl Failing {
+    fn fail(&self) {
+        self.0.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+}
+
+unsafe impl GlobalAlloc for Failing {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if self.0.load(std::sync::atomic::Ordering::SeqCst) {
+            return std::ptr::null_mut();
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[test]
+fn growable_try_grow_reports_alloc_failure() {
+    let alloc = Failing::default();
+    let pool: natatorium::growable::Pool<String> = natatorium::growable::Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_allocator(alloc.clone())
+        .finish();
+    assert_eq!(pool.try_grow(), Ok(2));
+
+    alloc.fail();
+    let error = pool.try_grow().unwrap_err();
+    assert!(error.is_alloc_failed());
+    assert!(!error.is_capacity_overflow());
+
+    // The pool is left as it was, and is still usable.
+    assert_eq!(pool.capacity(), 4);
+    let checkouts: Vec<_> = (0..4).map(|_| pool.checkout()).collect();
+    assert!(pool.try_checkout_nonblocking().is_none());
+    drop(checkouts);
+    assert_eq!(pool.available(), 4);
+}
+
+#[test]
+fn growable_checkout_fallible_reports_alloc_failure() {
+    let pool: natatorium::growable::Pool<String> = natatorium::growable::Pool::with_capacity(1);
+    let a = pool.checkout();
+
+    let result = failing_allocs(|| pool.checkout_fallible().map(drop));
+    match result {
+        Err(natatorium::growable::CheckoutError::AllocFailed(error)) => {
+            assert!(error.is_alloc_failed())
+        }
+        Err(error) => panic!("unexpected error: {}", error),
+        Ok(()) => panic!("checkout should have failed to grow the pool"),
+    }
+    // The pool is left as it was, and is still usable.
+    assert_eq!(pool.capacity(), 1);
+    drop(a);
+    assert!(failing_allocs(|| pool.checkout_fallible().is_ok()));
+
+    // Once memory is available again, the pool grows as usual.
+    let checkouts: Vec<_> = (0..4).map(|_| pool.checkout_fallible().unwrap()).collect();
+    assert_eq!(pool.capacity(), 4);
+    drop(checkouts);
+}
@@ -0,0 +1,95 @@
+use natatorium::classified::Pool;
+
+#[test]
+fn checks_out_smallest_fitting_class() {
+    let pool: Pool<Vec<u8>> = Pool::new(vec![(4096, 1), (64, 2), (1024, 1)]);
+    let capacities: Vec<_> = pool.classes().iter().map(|c| c.capacity()).collect();
+    assert_eq!(capacities, [64, 1024, 4096]);
+    assert_eq!(pool.capacity(), 4);
+
+    let small = pool.checkout(10);
+    assert!(small.capacity() >= 10 && small.capacity() < 1024);
+    let medium = pool.checkout(100);
+    assert!(medium.capacity() >= 1024 && medium.capacity() < 4096);
+    assert_eq!(pool.class_for(100).unwrap().capacity(), 1024);
+    assert!(pool.class_for(5000).is_none());
+
+    assert_eq!(pool.classes()[0].used(), 1);
+    assert_eq!(pool.classes()[1].used(), 1);
+    assert_eq!(pool.used(), 2);
+    drop((small, medium));
+    assert_eq!(pool.used(), 0);
+}
+
+#[test]
+fn spills_into_larger_classes() {
+    let pool: Pool<Vec<u8>> = Pool::new(vec![(64, 1), (1024, 1)]);
+    let a = pool.checkout(64);
+    let b = pool.checkout(64);
+    assert!(b.capacity() >= 1024);
+    assert_eq!(pool.classes()[0].spilled(), 1);
+    assert_eq!(pool.classes()[1].spilled(), 0);
+
+    assert!(pool.try_checkout(1).is_none());
+    drop(a);
+    assert!(pool.try_checkout(1).is_some());
+    drop(b);
+}
+
+#[test]
+#[should_panic(expected = "no size class has a capacity of at least 2048")]
+fn oversized_requests_panic() {
+    let pool: Pool<String> = Pool::new(vec![(64, 1), (1024, 1)]);
+    let _ = pool.checkout(2048);
+}
+
+#[test]
+fn blocks_until_a_fitting_item_is_released() {
+    let pool: Pool<String> = Pool::new(vec![(16, 1)]);
+    let held = pool.checkout(16);
+    let p = pool.clone();
+    let t = std::thread::spawn(move || p.checkout(8).capacity());
+    while pool.classes()[0].pool().waiters() == 0 {
+        std::thread::yield_now();
+    }
+    drop(held);
+    assert!(t.join().unwrap() >= 16);
+}
+
+#[test]
+fn checks_out_from_a_chosen_class() {
+    use natatorium::classified::Occupancy;
+
+    let pool: Pool<Vec<u8>> = Pool::new(vec![(64, 1), (1024, 2)]);
+    let small = pool.try_checkout_from_class(0).unwrap();
+    assert!(small.capacity() >= 64 && small.capacity() < 1024);
+    // The class is exhausted, and checkouts from it don't spill.
+    assert!(pool.try_checkout_from_class(0).is_none());
+    assert_eq!(pool.classes()[0].spilled(), 0);
+
+    let large = pool.try_checkout_from_class(1).unwrap();
+    assert!(large.capacity() >= 1024);
+
+    let occupancy: Vec<_> = pool
+        .occupancy()
+        .into_iter()
+        .map(
+            |Occupancy {
+                 capacity,
+                 size,
+                 used,
+                 ..
+             }| (capacity, size, used),
+        )
+        .collect();
+    assert_eq!(occupancy, [(64, 1, 1), (1024, 2, 1)]);
+    drop((small, large));
+    assert!(pool.occupancy().iter().all(|class| class.used == 0));
+}
+
+#[test]
+#[should_panic(expected = "no size class at index 2")]
+fn checking_out_from_a_missing_class_panics() {
+    let pool: Pool<String> = Pool::new(vec![(64, 1), (1024, 1)]);
+    let _ = pool.try_checkout_from_class(2);
+}
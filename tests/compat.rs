@@ -0,0 +1,62 @@
+use natatorium::compat::{lifeguard, object_pool};
+
+#[test]
+fn object_pool_adapter() {
+    let pool = object_pool::Pool::new(2, || Vec::<u8>::with_capacity(16));
+    assert_eq!(pool.len(), 2);
+
+    let mut a = pool.try_pull().unwrap();
+    a.extend_from_slice(b"hello");
+    let b = pool.try_pull().unwrap();
+    assert!(pool.try_pull().is_none());
+    assert!(pool.is_empty());
+
+    // Pulling from an exhausted pool adds an item made by the fallback.
+    let c = pool.pull(|| Vec::with_capacity(4));
+    assert_eq!(c.capacity(), 4);
+    drop((a, b, c));
+    assert_eq!(pool.len(), 3);
+
+    // Items are cleared when they're pulled again.
+    let a = pool.try_pull().unwrap();
+    assert!(a.is_empty());
+
+    let (_, item) = a.detach();
+    assert!(item.capacity() >= 4);
+    assert_eq!(pool.len(), 3);
+
+    pool.attach(Vec::new());
+    assert_eq!(pool.len(), 4);
+    assert_eq!(pool.into_inner().capacity(), 4);
+}
+
+#[test]
+fn lifeguard_adapter() {
+    let pool: lifeguard::Pool<String> = lifeguard::Pool::with_size(2);
+    assert_eq!((pool.size(), pool.max_size()), (2, 2));
+
+    let mut a = pool.new();
+    a.push_str("hello");
+    assert_eq!(pool.size(), 1);
+    drop(a);
+    assert_eq!(pool.size(), 2);
+
+    let b = pool.attach(String::from("attached"));
+    assert_eq!(*b, "attached");
+    assert_eq!(pool.size(), 1);
+    drop(b);
+
+    let mut c = pool.new();
+    c.push_str("detached");
+    let c = c.detach();
+    assert_eq!(c, "detached");
+    assert_eq!(pool.max_size(), 2);
+
+    let d = pool.detached();
+    assert!(d.is_empty());
+
+    // The pool grows when every item is checked out.
+    let checkouts: Vec<_> = (0..3).map(|_| pool.new()).collect();
+    assert!(pool.max_size() >= 3);
+    drop(checkouts);
+}
@@ -0,0 +1,68 @@
+use natatorium::{Event, EventKind};
+
+fn kinds(events: &[Event]) -> Vec<EventKind> {
+    events.iter().map(|event| event.kind).collect()
+}
+
+#[test]
+fn fixed_records_checkouts_and_releases() {
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::with_capacity(1);
+    let c = pool.checkout();
+    assert!(pool.try_checkout().is_none());
+    drop(c);
+
+    let events = pool.recent_events();
+    assert_eq!(
+        kinds(&events),
+        vec![
+            EventKind::Grow { size: 1 },
+            EventKind::Checkout { idx: 0 },
+            EventKind::Exhausted,
+            EventKind::Release { idx: 0 },
+        ]
+    );
+    let seqs: Vec<_> = events.iter().map(|event| event.seq).collect();
+    assert_eq!(seqs, vec![0, 1, 2, 3]);
+    assert!(events.iter().all(|event| event.thread == events[0].thread));
+}
+
+#[test]
+fn growable_records_growth() {
+    let pool: natatorium::growable::Pool<String> = natatorium::growable::Pool::with_capacity(1);
+    let _c1 = pool.checkout();
+    let _c2 = pool.checkout();
+    assert_eq!(
+        kinds(&pool.recent_events()),
+        vec![
+            EventKind::Grow { size: 1 },
+            EventKind::Checkout { idx: 0 },
+            EventKind::Exhausted,
+            EventKind::Grow { size: 2 },
+            EventKind::Checkout { idx: 1 },
+        ]
+    );
+}
+
+#[test]
+fn history_retains_only_recent_events() {
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::with_capacity(1);
+    for _ in 0..100 {
+        drop(pool.checkout());
+    }
+    let events = pool.recent_events();
+    assert_eq!(events.len(), 64);
+    assert_eq!(events.last().unwrap().seq, 200);
+    assert_eq!(events.last().unwrap().kind, EventKind::Release { idx: 0 });
+}
+
+#[test]
+fn threads_are_distinguished() {
+    let pool: natatorium::fixed::Pool<String> = natatorium::fixed::Pool::with_capacity(1);
+    drop(pool.checkout());
+    let p = pool.clone();
+    std::thread::spawn(move || drop(p.checkout()))
+        .join()
+        .unwrap();
+    let events = pool.recent_events();
+    assert_ne!(events[1].thread, events[3].thread);
+}
@@ -0,0 +1,108 @@
+//! Checks how evenly a fixed pool's items are shared between threads that
+//! contend for them, under sustained exhaustion.
+use natatorium::fixed::Pool;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Barrier,
+    },
+    thread,
+    time::Duration,
+};
+
+/// Runs `threads` threads that repeatedly check out an item from a pool of
+/// `cap` items, hold it for `hold`, and release it, returning the number of
+/// checkouts each thread made.
+fn checkouts_per_thread(threads: usize, cap: usize, hold: Duration) -> Vec<usize> {
+    let pool: Pool<String> = Pool::with_capacity(cap);
+    contend(threads, hold, move || pool.checkout())
+}
+
+/// Runs `threads` threads that repeatedly call `checkout`, hold the returned
+/// checkout for `hold`, and drop it, returning the number of checkouts each
+/// thread made.
+fn contend<C, G>(threads: usize, hold: Duration, checkout: C) -> Vec<usize>
+where
+    C: Fn() -> G + Clone + Send + 'static,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let start = Arc::new(Barrier::new(threads + 1));
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let (checkout, stop, start) = (checkout.clone(), stop.clone(), start.clone());
+            thread::spawn(move || {
+                start.wait();
+                let mut checkouts = 0;
+                while !stop.load(Ordering::Relaxed) {
+                    let item = checkout();
+                    if !hold.is_zero() {
+                        thread::sleep(hold);
+                    }
+                    drop(item);
+                    checkouts += 1;
+                }
+                checkouts
+            })
+        })
+        .collect();
+    start.wait();
+    thread::sleep(Duration::from_millis(300));
+    stop.store(true, Ordering::Relaxed);
+    workers
+        .into_iter()
+        .map(|worker| worker.join().expect("worker panicked"))
+        .collect()
+}
+
+/// Asserts that every thread made at least `1 / share` of its fair share of
+/// the checkouts.
+fn assert_no_starvation(checkouts: &[usize], share: usize) {
+    let fair = checkouts.iter().sum::<usize>() / checkouts.len();
+    for (thread, &n) in checkouts.iter().enumerate() {
+        assert!(
+            n * share >= fair,
+            "thread {} made {} checkouts, but a fair share is {} ({:?})",
+            thread,
+            n,
+            fair,
+            checkouts
+        );
+    }
+}
+
+#[test]
+fn threads_holding_items_are_not_starved() {
+    // Each thread holds its item for long enough that the others are waiting
+    // for it whenever it's released.
+    let checkouts = checkouts_per_thread(4, 1, Duration::from_micros(100));
+    assert_no_starvation(&checkouts, 4);
+}
+
+#[test]
+fn tight_loops_are_not_starved() {
+    let checkouts = checkouts_per_thread(4, 1, Duration::ZERO);
+    assert_no_starvation(&checkouts, 20);
+}
+
+#[test]
+fn more_threads_than_items() {
+    let checkouts = checkouts_per_thread(8, 3, Duration::from_micros(50));
+    assert_no_starvation(&checkouts, 4);
+}
+
+#[cfg(feature = "growable")]
+#[test]
+fn growable_pool_over_budget() {
+    use natatorium::{growable, Budget};
+
+    // The budget stops the pool from growing past a single item, so every
+    // checkout but one has to wait.
+    let item = growable::Pool::<String>::with_capacity(1).bytes_per_item();
+    let pool: growable::Pool<String> = growable::Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_budget(Budget::new(item))
+        .finish();
+    let checkouts = contend(4, Duration::ZERO, move || pool.checkout());
+    assert_no_starvation(&checkouts, 20);
+}
@@ -1,4 +1,8 @@
-use natatorium::fixed::Pool;
+mod support;
+
+use natatorium::fixed::{CheckoutOr, Owned, OwnedSet, Pool};
+use std::sync::Arc;
+use support::{poll, CountingWaker};
 
 #[test]
 fn new_checkouts_are_empty() {
@@ -52,6 +56,14 @@ fn capacity_released_when_checkout_is_dropped() {
     assert!(pool.try_checkout().is_some());
 }
 
+#[test]
+fn checkout_discard_releases_immediately() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    pool.checkout_discard();
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.stats().checkouts, 1);
+}
+
 #[test]
 fn capacity_released_when_all_shared_refs_are_dropped() {
     let pool: Pool<String> = Pool::with_capacity(1);
@@ -74,3 +86,1692 @@ fn capacity_released_when_all_shared_refs_are_dropped() {
     drop(shared3);
     assert!(pool.try_checkout().is_some());
 }
+
+#[test]
+fn stealing_free_items() {
+    let idle: Pool<String> = Pool::with_capacity(3);
+    let busy: Pool<String> = Pool::with_capacity(2);
+    {
+        // Warm up all of the idle pool's items.
+        let warm: Vec<_> = (0..3)
+            .map(|_| {
+                let mut item = idle.checkout();
+                item.reserve(1024);
+                item
+            })
+            .collect();
+        drop(warm);
+    }
+
+    // A checked out item can't be stolen, or exchanged.
+    let held = busy.checkout();
+    assert_eq!(busy.steal_free(&idle, 3), 1);
+    drop(held);
+    assert_eq!(busy.steal_free(&busy, 1), 0);
+
+    let (a, b) = (busy.checkout(), busy.checkout());
+    let warmed = [&a, &b]
+        .iter()
+        .filter(|item| item.capacity() >= 1024)
+        .count();
+    assert_eq!(warmed, 1);
+    assert_eq!(idle.capacity(), 3);
+    assert_eq!(busy.capacity(), 2);
+}
+
+#[test]
+fn upgrading_the_only_shared_ref() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let version = owned.version();
+    let shared = owned.downgrade();
+    let key = shared.key();
+
+    let clone = shared.clone();
+    let shared = shared.try_upgrade().expect_err("another reference exists");
+    drop(clone);
+
+    let mut owned = shared.try_upgrade().expect("the only reference");
+    assert_eq!(*owned, "hello");
+    assert_ne!(owned.version(), version);
+    assert!(pool.get(key).is_none());
+    owned.push_str(", world");
+
+    let shared = owned.downgrade();
+    assert_eq!(*pool.get(shared.key()).unwrap(), "hello, world");
+    drop(shared);
+    assert!(pool.try_checkout().is_some());
+}
+
+#[test]
+fn iterating_shared_refs_does_not_clone_checkouts() {
+    use natatorium::fixed::Shared;
+
+    let pool: Pool<String> = Pool::with_capacity(3);
+    let shared: Vec<_> = ["a", "b", "c"]
+        .iter()
+        .map(|s| {
+            let mut checkout = pool.checkout();
+            checkout.push_str(s);
+            checkout.downgrade()
+        })
+        .collect();
+
+    let count = pool.strong_count();
+    assert_eq!(shared[1].get_ref(), "b");
+    let refs = Shared::iter_refs(&shared);
+    assert_eq!(refs.len(), 3);
+    assert_eq!(refs.rev().collect::<Vec<_>>(), vec!["c", "b", "a"]);
+    assert_eq!(pool.strong_count(), count);
+}
+
+#[test]
+fn checkouts_compare_and_hash_like_their_items() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(t: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        t.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let pool: Pool<String> = Pool::with_capacity(2);
+
+    let mut c1 = pool.checkout();
+    c1.push_str("hello");
+    let mut c2 = pool.checkout();
+    c2.push_str("hello");
+    assert!(c1 == c2);
+    assert_eq!(c1.as_ref(), "hello");
+    assert_eq!(hash(&c1), hash(&String::from("hello")));
+
+    let s1 = c1.downgrade();
+    let s2 = c2.downgrade();
+    assert!(s1 == s2);
+    assert_eq!(hash(&s1), hash(&s2));
+}
+
+#[test]
+fn checkouts_forward_io() {
+    use natatorium::Clear;
+    use std::io::{self, Read, Write};
+
+    #[derive(Default)]
+    struct Buf(io::Cursor<Vec<u8>>);
+
+    impl Clear for Buf {
+        fn clear(&mut self) {
+            self.0.get_mut().clear();
+            self.0.set_position(0);
+        }
+    }
+
+    impl Read for Buf {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Buf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    let pool: Pool<Buf> = Pool::with_capacity(1);
+
+    let mut c = pool.checkout();
+    c.write_all(b"hello world").unwrap();
+    assert_eq!(c.0.get_ref(), b"hello world");
+    c.0.set_position(0);
+
+    let mut read = String::new();
+    c.read_to_string(&mut read).unwrap();
+    assert_eq!(read, "hello world");
+}
+
+#[test]
+fn write_to_checkout() {
+    use std::fmt::Write;
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    write!(pool.checkout(), "hello {}", 42).unwrap();
+
+    let mut c = pool.checkout();
+    assert_eq!("", *c);
+    write!(c, "hello {}", 42).unwrap();
+    assert_eq!("hello 42", *c);
+}
+
+#[test]
+fn drain_when_idle_waits_for_checkouts() {
+    use std::{sync::mpsc, thread, time::Duration};
+    let pool: Pool<String> = Pool::with_capacity(2);
+
+    let (tx, rx) = mpsc::channel();
+    let p = pool.clone();
+    let t = thread::spawn(move || {
+        let owned = p.checkout();
+        let shared = p.checkout().downgrade();
+        let shared2 = shared.clone();
+        tx.send(()).unwrap();
+
+        thread::sleep(Duration::from_millis(10));
+        drop(owned);
+        drop(shared);
+        thread::sleep(Duration::from_millis(10));
+        drop(shared2);
+    });
+
+    rx.recv().unwrap();
+    assert_eq!(pool.used(), 2);
+    pool.drain_when_idle();
+    assert_eq!(pool.used(), 0);
+    t.join().unwrap();
+
+    // Draining an idle pool returns immediately.
+    pool.drain_when_idle();
+}
+
+#[test]
+fn expired_leases_are_reclaimed() {
+    use std::{thread, time::Duration};
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_lease(Duration::from_millis(1))
+        .finish();
+
+    let released = pool.checkout();
+    drop(released);
+    let mut stuck = pool.checkout();
+    stuck.push_str("i'm stuck");
+    thread::sleep(Duration::from_millis(5));
+
+    assert_eq!(pool.reclaim_expired(), 1);
+    assert_eq!(pool.lost(), 1);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), 1);
+
+    assert!(pool.audit().is_consistent());
+
+    // Reclaiming again does not mark the slot as lost twice.
+    assert_eq!(pool.reclaim_expired(), 0);
+
+    // The lost slot is never handed out again, even once it is dropped.
+    let c = pool.checkout();
+    assert!(pool.try_checkout().is_none());
+    drop(stuck);
+    assert!(pool.try_checkout().is_none());
+    assert_eq!(pool.used(), 1);
+
+    drop(c);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), 1);
+}
+
+#[test]
+fn pool_handle_swap_keeps_old_pool_alive() {
+    use natatorium::PoolHandle;
+    use std::{
+        sync::{mpsc, Arc},
+        thread,
+        time::Duration,
+    };
+    let handle = PoolHandle::new(Pool::<String>::with_capacity(1));
+
+    let mut c = handle.load().checkout();
+    c.push_str("from the old pool");
+    assert!(handle.load().try_checkout().is_none());
+
+    let old = handle.swap(Pool::with_capacity(2));
+    assert_eq!(handle.load().capacity(), 2);
+    assert_eq!(old.used(), 1);
+
+    // The checkout from the old pool remains valid after the swap.
+    assert_eq!(*c, "from the old pool");
+    drop(c);
+    assert_eq!(old.used(), 0);
+
+    let handle = Arc::new(handle);
+    let (tx, rx) = mpsc::channel();
+    let h = handle.clone();
+    let t = thread::spawn(move || {
+        let c = h.load().checkout();
+        tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(10));
+        drop(c);
+    });
+
+    rx.recv().unwrap();
+    let old = handle.swap_and_drain(Pool::with_capacity(1));
+    assert_eq!(old.used(), 0);
+    assert_eq!(handle.load().capacity(), 1);
+    t.join().unwrap();
+}
+
+#[test]
+fn with_capacity_and_fn() {
+    let pool = Pool::with_capacity_and_fn(2, || String::with_capacity(64));
+    assert_eq!(pool.capacity(), 2);
+
+    let c = pool.checkout();
+    assert_eq!(*c, "");
+    assert!(c.capacity() >= 64);
+}
+
+#[test]
+fn checkouts_dropped_while_panicking_poison_their_slot() {
+    use std::panic::{self, AssertUnwindSafe};
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut c = pool.checkout();
+        c.push_str("half-written");
+        panic!("oh no");
+    }));
+    assert!(result.is_err());
+
+    assert_eq!(pool.poisoned(), 1);
+    assert_eq!(pool.poisoned_slots(), vec![0]);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), 0);
+    assert!(pool.try_checkout().is_none());
+
+    assert!(pool.audit().is_consistent());
+
+    assert!(pool.repair(0));
+    assert!(!pool.repair(0));
+    assert_eq!(pool.poisoned(), 0);
+    assert!(pool.audit().is_consistent());
+    assert_eq!(*pool.try_checkout().expect("slot was repaired"), "");
+}
+
+#[test]
+fn with_clear_fn() {
+    use std::collections::VecDeque;
+    let pool = Pool::builder()
+        .with_fn(|| VecDeque::<u8>::with_capacity(8))
+        .with_clear_fn(VecDeque::clear)
+        .with_elements(1)
+        .finish();
+
+    let mut c = pool.checkout();
+    c.push_back(1);
+    c.push_back(2);
+    drop(c);
+
+    let c = pool.checkout();
+    assert!(c.is_empty());
+    assert!(c.capacity() >= 8);
+}
+
+#[test]
+fn pre_touch() {
+    let pool = Pool::builder()
+        .with_fn(|| Vec::<u8>::with_capacity(64 * 1024))
+        .pre_touch(true)
+        .with_elements(2)
+        .finish();
+
+    let c = pool.checkout();
+    assert!(c.is_empty());
+    assert!(c.capacity() >= 64 * 1024);
+}
+
+#[test]
+fn try_into_items() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+
+    let mut c = pool.checkout();
+    c.push_str("hello");
+    let pool = pool.try_into_items().expect_err("an item is checked out");
+    drop(c);
+
+    let pool2 = pool.clone();
+    let pool = pool.try_into_items().expect_err("the pool has been cloned");
+    drop(pool2);
+
+    let items = pool
+        .try_into_items()
+        .unwrap_or_else(|_| panic!("pool is idle"));
+    assert_eq!(items.len(), 2);
+}
+
+#[test]
+fn const_capacity() {
+    fn checkout_all<const CAP: usize>(pool: &Pool<String, CAP>) -> Vec<Owned<String>> {
+        (0..Pool::<String, CAP>::CAPACITY)
+            .map(|_| pool.checkout())
+            .collect()
+    }
+
+    let pool: Pool<String, 4> = Pool::builder()
+        .with_default()
+        .with_const_elements::<4>()
+        .finish();
+    assert_eq!(pool.capacity(), 4);
+
+    let checkouts = checkout_all(&pool);
+    assert_eq!(checkouts.len(), 4);
+    assert!(pool.try_checkout().is_none());
+}
+
+#[test]
+fn promote_shared_checkouts_from_leaked_pool() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let leaked = pool.leak();
+
+    let mut c = leaked.checkout();
+    c.push_str("config");
+    let config: &'static String = leaked
+        .promote(c.downgrade())
+        .unwrap_or_else(|_| panic!("checkout is from the same pool"));
+    assert_eq!(config, "config");
+    assert_eq!(leaked.used(), 1);
+
+    let other: Pool<String> = Pool::with_capacity(1);
+    assert!(leaked.promote(other.checkout().downgrade()).is_err());
+}
+
+#[test]
+fn audit_accounts_for_every_slot() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    let report = pool.audit();
+    assert!(report.is_consistent(), "{:#?}", report);
+    assert_eq!(report.free, 4);
+
+    let c1 = pool.checkout();
+    let c2 = pool.checkout().downgrade();
+    let _c3 = c2.clone();
+    drop(c1);
+
+    let report = pool.audit();
+    assert!(report.is_consistent(), "{:#?}", report);
+    assert_eq!(report.missing(), 0);
+    assert_eq!(report.used, 1);
+    assert_eq!(report.checked_out, 1);
+    assert_eq!(report.free, 3);
+}
+
+#[test]
+fn contains() {
+    let pool1: Pool<String> = Pool::with_capacity(4);
+    let pool2: Pool<String> = Pool::with_capacity(4);
+
+    let c1 = pool1.checkout();
+    let c2 = pool2.checkout();
+    assert!(pool1.contains(&c1));
+    assert!(!pool1.contains(&c2));
+    assert!(pool2.contains(&c2));
+    assert!(!pool2.contains(&c1));
+
+    let shared = c1.downgrade();
+    assert!(pool1.contains(&shared));
+
+    let not_pooled = String::new();
+    assert!(!pool1.contains(&not_pooled));
+}
+
+#[test]
+fn owned_set_releases_all_at_once() {
+    let pool: Pool<String> = Pool::with_capacity(8);
+    let mut set = OwnedSet::new();
+    assert!(set.is_empty());
+    for i in 0..8 {
+        let mut checkout = pool.checkout();
+        checkout.push_str(&i.to_string());
+        set.push(checkout);
+    }
+    assert_eq!(set.len(), 8);
+    assert_eq!(pool.available(), 0);
+
+    for item in set.iter_mut() {
+        item.push('!');
+    }
+    let items: Vec<_> = set.iter().cloned().collect();
+    assert_eq!(items, (0..8).map(|i| format!("{}!", i)).collect::<Vec<_>>());
+
+    drop(set);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), 8);
+    assert!(pool.audit().is_consistent());
+
+    // Every released slot is reachable from the free list again.
+    let checkouts: Vec<_> = (0..8).map(|_| pool.checkout()).collect();
+    assert!(checkouts.iter().all(|c| c.is_empty()));
+    assert_eq!(pool.capacity(), 8);
+}
+
+#[test]
+#[should_panic(expected = "must all come from the same pool")]
+fn owned_set_rejects_other_pools() {
+    let pool1: Pool<String> = Pool::with_capacity(1);
+    let pool2: Pool<String> = Pool::with_capacity(1);
+    let mut set = OwnedSet::new();
+    set.push(pool1.checkout());
+    set.push(pool2.checkout());
+}
+
+#[test]
+fn pressure_watermarks() {
+    use natatorium::Pressure;
+    use std::sync::{Arc, Mutex};
+
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(4)
+        .with_watermarks(2, 3)
+        .on_pressure_change({
+            let changes = changes.clone();
+            move |pressure| changes.lock().unwrap().push(pressure)
+        })
+        .finish();
+
+    assert_eq!(pool.pressure(), Pressure::Low);
+    let c1 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::Low);
+    let c2 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::Medium);
+    let c3 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::High);
+    let c4 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::High);
+
+    drop((c3, c4));
+    assert_eq!(pool.pressure(), Pressure::Medium);
+    drop((c1, c2));
+    assert_eq!(pool.pressure(), Pressure::Low);
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![
+            Pressure::Medium,
+            Pressure::High,
+            Pressure::Medium,
+            Pressure::Low
+        ]
+    );
+}
+
+#[test]
+fn tagged_releases() {
+    use std::sync::{Arc, Mutex};
+
+    let released = Arc::new(Mutex::new(Vec::new()));
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(4)
+        .on_release({
+            let released = released.clone();
+            move |tag| released.lock().unwrap().push(tag)
+        })
+        .finish();
+
+    let mut c1 = pool.checkout().with_tag(1);
+    c1.push_str("hello");
+    assert_eq!(c1.tag(), 1);
+    let c2 = pool.checkout();
+    let c3 = pool.checkout().with_tag(3);
+    let c4 = pool.checkout().with_tag(4).into_owned();
+
+    drop(c2);
+    drop(c3);
+    drop(c1);
+    drop(c4);
+    assert_eq!(*released.lock().unwrap(), vec![3, 1]);
+    assert_eq!(pool.used(), 0);
+}
+
+#[test]
+fn pressure_without_watermarks_is_low() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let _c = pool.checkout();
+    assert_eq!(pool.pressure(), natatorium::Pressure::Low);
+}
+
+#[test]
+fn owned_or_shared_falls_back_to_template() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_template(String::from("template"))
+        .finish();
+
+    let owned = pool
+        .try_checkout_owned_or_shared()
+        .expect("pool has a free item");
+    assert!(!owned.is_shared());
+    assert_eq!(&*owned, "");
+
+    let shared1 = pool
+        .try_checkout_owned_or_shared()
+        .expect("pool has a template");
+    let shared2 = pool
+        .try_checkout_owned_or_shared()
+        .expect("pool has a template");
+    assert!(shared1.is_shared());
+    assert_eq!(&*shared1, "template");
+    assert_eq!(&*shared2, "template");
+    assert!(shared1.into_owned().is_none());
+
+    // The template is not counted as part of the pool.
+    assert_eq!(pool.capacity(), 1);
+    assert_eq!(pool.used(), 1);
+    drop(owned);
+    assert!(pool.audit().is_consistent());
+
+    // Releasing every shared checkout does not clear the template.
+    drop(shared2);
+    let clone = pool.clone();
+    drop(pool);
+    let _owned = clone.checkout();
+    let shared = clone.try_checkout_owned_or_shared().unwrap();
+    assert_eq!(&*shared, "template");
+}
+
+#[test]
+fn owned_or_shared_without_template() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let _owned = pool.checkout();
+    assert!(pool.try_checkout_owned_or_shared().is_none());
+}
+
+#[test]
+fn seeded_items_are_reused() {
+    let seed = (0..2).map(|_| String::with_capacity(100));
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(4)
+        .seeded(seed)
+        .finish();
+    assert_eq!(pool.capacity(), 4);
+    let checkouts: Vec<_> = (0..4).map(|_| pool.checkout()).collect();
+    assert_eq!(checkouts.iter().filter(|c| c.capacity() >= 100).count(), 2);
+    // Seeded items are cleared on checkout, like any other item.
+    assert!(checkouts.iter().all(|c| c.is_empty()));
+}
+
+#[test]
+fn seeded_items_can_exceed_capacity() {
+    let seed = vec![String::from("a"), String::from("b"), String::from("c")];
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .seeded(seed)
+        .finish();
+    assert_eq!(pool.capacity(), 3);
+}
+
+#[test]
+#[should_panic(expected = "cannot seed a pool of capacity 1 with 2 items")]
+fn seeding_const_pool_past_capacity_panics() {
+    let _pool: Pool<String, 1> = Pool::builder()
+        .with_default()
+        .with_const_elements::<1>()
+        .seeded(vec![String::new(), String::new()])
+        .finish();
+}
+
+#[test]
+fn retry_histogram() {
+    use std::{sync::mpsc, thread, time::Duration};
+
+    let pool: Pool<String> = Pool::with_capacity(1);
+    drop(pool.checkout());
+    drop(pool.checkout());
+    let histogram = pool.retry_histogram();
+    assert_eq!(histogram.checkouts(), 2);
+    assert_eq!(histogram.buckets()[0], 2);
+    assert_eq!(histogram.contended(), 0);
+
+    // Exhaust the pool from another thread, so that the next checkout has to
+    // wait for it.
+    let (tx, rx) = mpsc::channel();
+    let p = pool.clone();
+    let t = thread::spawn(move || {
+        let _c = p.checkout();
+        tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+    });
+    rx.recv().unwrap();
+    let _c = pool.checkout();
+    t.join().unwrap();
+
+    let histogram = pool.retry_histogram();
+    assert_eq!(histogram.checkouts(), 4);
+    assert_eq!(histogram.contended(), 1);
+}
+
+#[test]
+fn stats_delta() {
+    use natatorium::{Stats, StatsCursor};
+    use std::panic::{self, AssertUnwindSafe};
+
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let mut cursor = StatsCursor::new();
+    assert_eq!(pool.stats_delta(&mut cursor), Stats::default());
+
+    let c = pool.checkout();
+    drop(pool.checkout());
+    let delta = pool.stats_delta(&mut cursor);
+    assert_eq!(delta.checkouts, 2);
+    assert_eq!(delta.releases, 1);
+
+    drop(c);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let _c = pool.checkout();
+        panic!("oh no");
+    }));
+    assert!(result.is_err());
+    let delta = pool.stats_delta(&mut cursor);
+    assert_eq!(delta.checkouts, 1);
+    assert_eq!(delta.releases, 1);
+    assert_eq!(delta.poisoned, 1);
+
+    assert_eq!(pool.stats_delta(&mut cursor), Stats::default());
+
+    // Each cursor sees every count once, and the cumulative stats see them
+    // all.
+    let mut other = StatsCursor::new();
+    assert_eq!(pool.stats_delta(&mut other), pool.stats());
+    assert_eq!(pool.stats().checkouts, 3);
+}
+
+#[test]
+fn waiters_counts_blocked_checkouts() {
+    use std::{thread, time::Duration};
+
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let held = pool.checkout();
+    assert_eq!(pool.waiters(), 0);
+
+    let p = pool.clone();
+    let t = thread::spawn(move || drop(p.checkout()));
+    while pool.waiters() == 0 {
+        thread::sleep(Duration::from_millis(1));
+    }
+    assert_eq!(pool.waiters(), 1);
+    assert_eq!(pool.audit().waiters, 1);
+
+    drop(held);
+    t.join().unwrap();
+    assert_eq!(pool.waiters(), 0);
+}
+
+#[test]
+fn over_aligned_items() {
+    #[derive(Default)]
+    struct Buf([u8; 3]);
+    impl natatorium::Clear for Buf {
+        fn clear(&mut self) {
+            self.0 = [0; 3];
+        }
+    }
+
+    for &align in &[1, 8, 64, 4096] {
+        let pool: Pool<Buf> = Pool::builder()
+            .with_default()
+            .with_item_alignment(align)
+            .with_elements(5)
+            .finish();
+        let checkouts: Vec<_> = (0..5).map(|_| pool.checkout()).collect();
+        for checkout in &checkouts {
+            let addr = &**checkout as *const Buf as usize;
+            assert_eq!(
+                addr % align,
+                0,
+                "item at {:#x} not aligned to {}",
+                addr,
+                align
+            );
+            assert!(pool.contains(checkout));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "item alignment must be a power of two")]
+fn item_alignment_must_be_power_of_two() {
+    let _ = Pool::<String>::builder().with_item_alignment(48);
+}
+
+#[test]
+fn checkout_with_ctor() {
+    let pool: Pool<Vec<u8>> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_ctor("small", |buf: &mut Vec<u8>| buf.reserve(16))
+        .with_ctor("large", |buf: &mut Vec<u8>| buf.resize(1024, 0))
+        .finish();
+
+    let small = pool.checkout_with_ctor("small");
+    assert!(small.capacity() >= 16);
+    assert!(small.is_empty());
+    let large = pool.checkout_with_ctor("large");
+    assert_eq!(large.len(), 1024);
+    drop((small, large));
+
+    // Items are still cleared before they are initialized again.
+    let small = pool.checkout_with_ctor("small");
+    assert!(small.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "no constructor named \"huge\" was registered")]
+fn checkout_with_unknown_ctor() {
+    let pool: Pool<Vec<u8>> = Pool::builder()
+        .with_default()
+        .with_ctor("small", |buf: &mut Vec<u8>| buf.reserve(16))
+        .finish();
+    let _ = pool.checkout_with_ctor("huge");
+}
+
+#[test]
+fn get_by_key() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let key = owned.key();
+
+    // Owned checkouts can't be looked up, since they allow mutation.
+    assert!(pool.get(key).is_none());
+
+    let shared = owned.downgrade();
+    assert_eq!(shared.key(), key);
+    let found = pool.get(key).expect("shared checkout should be found");
+    assert_eq!(&*found, "hello");
+
+    drop((shared, found));
+    assert_eq!(pool.used(), 0);
+    assert!(pool.get(key).is_none());
+
+    // Checking the slot out again doesn't revive old keys.
+    let shared = pool.checkout().downgrade();
+    assert_eq!(shared.key().index(), key.index());
+    assert!(pool.get(key).is_none());
+    assert!(pool.get(shared.key()).is_some());
+}
+
+#[test]
+fn keys_are_small() {
+    assert_eq!(std::mem::size_of::<natatorium::Key>(), 8);
+}
+
+/// Checkouts are stored in bulk by some callers, so they shouldn't grow
+/// without good reason.
+#[cfg(not(feature = "tracing"))]
+#[test]
+fn checkouts_are_three_words() {
+    use std::mem::size_of;
+    let word = size_of::<usize>();
+    assert_eq!(size_of::<Owned<String>>(), 3 * word);
+    assert_eq!(size_of::<Option<Owned<String>>>(), 3 * word);
+    assert_eq!(size_of::<natatorium::fixed::Shared<String>>(), 3 * word);
+}
+
+#[test]
+fn keys_round_trip_through_bits() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    drop(pool.checkout());
+    let _first = pool.checkout();
+    let shared = pool.checkout().downgrade();
+    let key = natatorium::Key::from_bits(shared.key().to_bits());
+    assert_eq!(key, shared.key());
+    assert!(pool.get(key).is_some());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn keys_round_trip_through_serde() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let shared = pool.checkout().downgrade();
+    let json = serde_json::to_string(&shared.key()).unwrap();
+    let key: natatorium::Key = serde_json::from_str(&json).unwrap();
+    assert_eq!(key, shared.key());
+    assert!(pool.get(key).is_some());
+}
+
+#[test]
+fn default_clone_and_debug() {
+    // None of these require the item type to implement `Clone` or `Debug`.
+    #[derive(Default)]
+    struct Item(Vec<u8>);
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.0.clear()
+        }
+    }
+
+    let pool: Pool<Item> = Pool::default();
+    assert_eq!(pool.capacity(), Pool::<Item>::DEFAULT_CAPACITY);
+    let clone = pool.clone();
+    let _c = clone.checkout();
+    assert_eq!(pool.used(), 1);
+    assert!(format!("{:?}", pool).contains("used: 1"));
+
+    let pool: Pool<Item, 4> = Pool::default();
+    assert_eq!(pool.capacity(), 4);
+}
+
+#[test]
+fn pool_ids() {
+    let pool1: Pool<String> = Pool::with_capacity(2);
+    let pool2: Pool<String> = Pool::with_capacity(2);
+    assert_ne!(pool1.id(), pool2.id());
+    assert_eq!(pool1.clone().id(), pool1.id());
+
+    let owned = pool1.checkout();
+    assert_eq!(owned.pool_id(), pool1.id());
+    let shared = pool2.checkout().downgrade();
+    assert_eq!(shared.pool_id(), pool2.id());
+}
+
+#[test]
+fn capacity_and_available() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    assert_eq!(pool.capacity(), 2);
+    assert_eq!(pool.available(), 2);
+    assert!(!pool.is_exhausted());
+
+    let a = pool.checkout();
+    let _b = pool.checkout();
+    assert_eq!(pool.used(), 2);
+    assert_eq!(pool.available(), 0);
+    assert!(pool.is_exhausted());
+
+    drop(a);
+    assert_eq!(pool.available(), 1);
+    assert!(!pool.is_exhausted());
+
+    #[allow(deprecated)]
+    {
+        assert_eq!(pool.size(), pool.capacity());
+        assert_eq!(pool.remaining(), pool.available());
+    }
+}
+
+#[test]
+fn round_robin_reuse() {
+    let pool: Pool<String> = Pool::builder()
+        .with_elements(3)
+        .with_reuse(natatorium::Reuse::RoundRobin)
+        .with_default()
+        .fixed()
+        .finish();
+    let indices: Vec<_> = (0..7).map(|_| pool.checkout().key().index()).collect();
+    assert_eq!(indices, [0, 1, 2, 0, 1, 2, 0]);
+
+    // Checked out slots are skipped.
+    let held = pool.checkout();
+    assert_eq!(held.key().index(), 1);
+    let indices: Vec<_> = (0..3).map(|_| pool.checkout().key().index()).collect();
+    assert_eq!(indices, [2, 0, 2]);
+
+    let a = pool.checkout();
+    let b = pool.checkout();
+    assert!(pool.try_checkout().is_none());
+    drop((a, b, held));
+    let audit = pool.audit();
+    assert_eq!(audit.free, 3);
+    assert_eq!(audit.missing(), 0);
+
+    // By default, the most recently released slot is reused.
+    let pool: Pool<String> = Pool::with_capacity(3);
+    let indices: Vec<_> = (0..3).map(|_| pool.checkout().key().index()).collect();
+    assert_eq!(indices, [0, 0, 0]);
+}
+
+#[test]
+fn round_robin_owned_set_release() {
+    let pool: Pool<String> = Pool::builder()
+        .with_elements(4)
+        .with_reuse(natatorium::Reuse::RoundRobin)
+        .with_default()
+        .fixed()
+        .finish();
+    let mut set = OwnedSet::new();
+    for _ in 0..3 {
+        set.push(pool.checkout());
+    }
+    drop(set);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.audit().free, 4);
+    let indices: Vec<_> = (0..5).map(|_| pool.checkout().key().index()).collect();
+    assert_eq!(indices, [3, 0, 1, 2, 3]);
+}
+
+#[test]
+fn guards_forward_display_and_error() {
+    use std::{error::Error, fmt, io};
+
+    #[derive(Debug, Default)]
+    struct Failure(Option<io::Error>);
+
+    impl fmt::Display for Failure {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("request failed")
+        }
+    }
+
+    impl Error for Failure {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            self.0.as_ref().map(|e| e as &(dyn Error + 'static))
+        }
+    }
+
+    impl natatorium::Clear for Failure {
+        fn clear(&mut self) {
+            self.0 = None;
+        }
+    }
+
+    fn fail(pool: &Pool<Failure>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut failure = pool.checkout();
+        failure.0 = Some(io::Error::other("connection reset"));
+        Err(failure.into())
+    }
+
+    let pool: Pool<Failure> = Pool::with_capacity(1);
+    let err = fail(&pool).unwrap_err();
+    assert_eq!(err.to_string(), "request failed");
+    assert_eq!(err.source().unwrap().to_string(), "connection reset");
+    assert!(format!("{:?}", err).starts_with("Failure(Some("));
+    assert_eq!(pool.used(), 1);
+    drop(err);
+    assert_eq!(pool.used(), 0);
+
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    assert_eq!(format!("{} {:?}", owned, owned), "hello \"hello\"");
+    let shared = owned.downgrade();
+    assert_eq!(format!("{} {:?}", shared, shared), "hello \"hello\"");
+}
+
+#[test]
+fn checkout_or_prefers_scratch() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut scratch = Some(String::from("stale"));
+
+    let mut c = pool.checkout_or(&mut scratch);
+    assert!(matches!(c, CheckoutOr::Scratch(_)));
+    assert_eq!(*c, "", "scratch items are cleared like pooled ones");
+    c.push_str("from scratch");
+    drop(c);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(scratch.as_deref(), Some("from scratch"));
+
+    let mut empty = None;
+    let mut c = pool.checkout_or(&mut empty);
+    assert!(matches!(c, CheckoutOr::Owned(_)));
+    c.push_str("from pool");
+    assert_eq!(pool.used(), 1);
+    drop(c);
+    assert_eq!(pool.used(), 0);
+    assert!(empty.is_none());
+}
+
+#[test]
+fn raw_round_trip() {
+    use natatorium::fixed::Shared;
+    use std::sync::atomic::{AtomicPtr, Ordering};
+
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let slot = AtomicPtr::new(Owned::into_raw(owned));
+    assert_eq!(pool.used(), 1);
+
+    let p = pool.clone();
+    std::thread::spawn(move || {
+        let ptr = slot.swap(std::ptr::null_mut(), Ordering::AcqRel);
+        let mut owned = unsafe { Owned::from_raw(&p, ptr) };
+        owned.push_str(" world");
+        assert_eq!(*owned, "hello world");
+    })
+    .join()
+    .unwrap();
+    assert_eq!(pool.used(), 0);
+
+    let shared = pool.checkout().downgrade();
+    let clone = shared.clone();
+    let raw = Shared::into_raw(shared);
+    drop(clone);
+    assert_eq!(pool.used(), 1, "the raw pointer keeps the item checked out");
+    let shared = unsafe { Shared::from_raw(&pool, raw) };
+    drop(shared);
+    assert_eq!(pool.used(), 0);
+}
+
+#[test]
+#[should_panic(expected = "does not point to an item in this pool")]
+fn from_raw_checks_pool() {
+    let pool1: Pool<String> = Pool::with_capacity(1);
+    let pool2: Pool<String> = Pool::with_capacity(1);
+    let raw = Owned::into_raw(pool1.checkout());
+    let _ = unsafe { Owned::from_raw(&pool2, raw) };
+}
+
+#[test]
+fn dirty_tracking_skips_clearing_unmodified_items() {
+    #[derive(Default)]
+    struct Item {
+        data: Vec<u8>,
+        clears: usize,
+    }
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.data.clear();
+            self.clears += 1;
+        }
+    }
+
+    let pool: Pool<Item> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_dirty_tracking()
+        .finish();
+    // New items are cleared when they're first checked out.
+    let checkout = pool.checkout();
+    assert_eq!(checkout.clears, 1);
+
+    // Reading an item, or downgrading it, doesn't make it dirty.
+    drop(checkout.downgrade());
+    let checkout = pool.checkout();
+    assert_eq!(checkout.clears, 1);
+    drop(checkout);
+
+    let mut checkout = pool.checkout();
+    assert_eq!(checkout.clears, 1);
+    checkout.data.push(1);
+    drop(checkout.downgrade());
+    let checkout = pool.checkout();
+    assert_eq!((checkout.clears, checkout.data.len()), (2, 0));
+    drop(checkout);
+
+    // Modifying items in an `OwnedSet` marks them dirty as well.
+    let mut set = OwnedSet::new();
+    set.push(pool.checkout());
+    set.iter_mut().for_each(|item| item.data.push(2));
+    drop(set);
+    let checkout = pool.checkout();
+    assert_eq!((checkout.clears, checkout.data.len()), (3, 0));
+
+    drop(checkout);
+
+    // Without dirty tracking, every checkout is cleared.
+    let pool: Pool<Item> = Pool::builder().with_default().with_elements(1).finish();
+    drop(pool.checkout());
+    assert_eq!(pool.checkout().clears, 2);
+}
+
+#[test]
+fn clear_on_release() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CLEARS: AtomicUsize = AtomicUsize::new(0);
+    #[derive(Default)]
+    struct Item(Vec<u8>);
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.0.clear();
+            CLEARS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let pool: Pool<Item> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .clear_on_release()
+        .finish();
+    // New items are still cleared when they're first checked out.
+    let mut checkout = pool.checkout();
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 1);
+    checkout.0.extend_from_slice(b"secret");
+    drop(checkout);
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 2);
+
+    // Released items are not cleared again when they're checked out.
+    let mut checkout = pool.checkout();
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 2);
+    assert!(checkout.0.is_empty());
+    checkout.0.extend_from_slice(b"secret");
+
+    // Shared items are cleared once the last reference is dropped.
+    let shared = checkout.downgrade();
+    let clone = shared.clone();
+    drop(shared);
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 2);
+    drop(clone);
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 3);
+
+    let items = pool.try_into_items().ok().unwrap();
+    assert!(items.iter().all(|item| item.0.is_empty()));
+}
+
+#[test]
+fn scoped_checkouts() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let lens = pool.scope(|scope| {
+        let mut a = scope.checkout();
+        a.push_str("hello");
+        let mut b = scope.try_checkout().unwrap();
+        b.push_str("world!");
+        assert!(scope.try_checkout().is_none());
+        assert_eq!(pool.used(), 2);
+        (a.len(), b.len())
+    });
+    assert_eq!(lens, (5, 6));
+    // Every scoped checkout was released when the scope ended.
+    assert_eq!(pool.used(), 0);
+
+    // A scope may be shared with scoped threads, which block on each other's
+    // checkouts.
+    pool.scope(|scope| {
+        std::thread::scope(|threads| {
+            for _ in 0..4 {
+                threads.spawn(|| {
+                    for i in 0..100 {
+                        let mut checkout = scope.checkout();
+                        assert_eq!(*checkout, "");
+                        checkout.push_str(&i.to_string());
+                    }
+                });
+            }
+        });
+    });
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.audit().missing(), 0);
+}
+
+#[test]
+fn downcast() {
+    use natatorium::AnyItem;
+
+    let mut n = 0;
+    let pool: Pool<AnyItem> = Pool::with_capacity_and_fn(2, || {
+        n += 1;
+        if n % 2 == 0 {
+            Box::new(String::new()) as AnyItem
+        } else {
+            Box::new(Vec::<u8>::new()) as AnyItem
+        }
+    });
+
+    let (a, b) = (pool.checkout(), pool.checkout());
+    let (string, bytes) = match a.downcast::<String>() {
+        Ok(string) => (string, b.downcast::<Vec<u8>>().expect("not a string")),
+        Err(a) => (
+            b.downcast::<String>().expect("not bytes"),
+            a.downcast::<Vec<u8>>().expect("not a string"),
+        ),
+    };
+    let mut string = match string.into_inner().downcast::<Vec<u8>>() {
+        Ok(_) => panic!("a string was downcast to bytes"),
+        Err(string) => string.downcast::<String>().unwrap(),
+    };
+    string.push_str("hello");
+    assert_eq!(pool.used(), 2);
+    drop((string, bytes));
+    assert_eq!(pool.used(), 0);
+
+    // The items are still cleared before they are checked out again.
+    let (a, b) = (pool.checkout(), pool.checkout());
+    for item in [a, b] {
+        if let Ok(string) = item.downcast::<String>() {
+            assert_eq!(*string, "");
+        }
+    }
+}
+
+#[test]
+fn verify() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    let report = pool.verify();
+    assert!(report.is_ok(), "{:#?}", report);
+    assert_eq!(report.checked_out, 4);
+
+    // Checked out items are skipped.
+    let mut held = pool.checkout();
+    held.push_str("hello");
+    let report = pool.verify();
+    assert!(report.is_ok(), "{:#?}", report);
+    assert_eq!(report.checked_out, 3);
+    assert_eq!(report.audit.checked_out, 1);
+    assert_eq!(*held, "hello");
+}
+
+#[test]
+fn dump_all() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    pool.register("fixed-dump-all");
+    let c1 = pool.checkout();
+    drop(pool.checkout());
+    assert_eq!(pool.high_water(), 2);
+
+    let mut out = Vec::new();
+    natatorium::dump_all(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let row = out
+        .lines()
+        .find(|line| line.starts_with("fixed-dump-all "))
+        .expect("pool was registered");
+    let columns: Vec<_> = row.split_whitespace().collect();
+    assert_eq!(columns[1], pool.id().to_string());
+    assert_eq!(columns[2..], ["4", "1", "2", "1"]);
+
+    // Dropped pools are removed from the registry.
+    drop((c1, pool));
+    let mut out = Vec::new();
+    natatorium::dump_all(&mut out).unwrap();
+    assert!(!String::from_utf8(out).unwrap().contains("fixed-dump-all"));
+}
+
+#[test]
+fn versions() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let v1 = owned.version();
+    let shared = owned.downgrade();
+    assert_eq!(shared.version(), v1);
+    assert_eq!(shared.clone().version(), v1);
+    let key = shared.key();
+    assert_eq!(pool.get(key).expect("still shared").version(), v1);
+    drop(shared);
+
+    // The same slot is checked out again, with a new version.
+    let owned = pool.checkout();
+    assert_ne!(owned.version(), v1);
+    let v2 = owned.version();
+    drop(owned);
+    assert_ne!(pool.checkout().version(), v2);
+}
+
+#[test]
+fn checkout_nth() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    let mut third = pool.checkout_nth(2).expect("slot 2 is free");
+    assert_eq!(third.key().index(), 2);
+    third.push_str("third");
+    assert!(pool.checkout_nth(2).is_none());
+    assert!(pool.checkout_nth(4).is_none());
+    assert_eq!(pool.used(), 1);
+
+    // The other slots are handed out in the same order as before.
+    let first = pool.checkout();
+    assert_eq!(first.key().index(), 0);
+    assert_eq!(pool.checkout().key().index(), 1);
+}
+
+#[test]
+fn checkouts_outlive_the_pool() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Default)]
+    struct Item(String);
+
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.0.clear();
+        }
+    }
+
+    impl Drop for Item {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let pool: Pool<Item> = Pool::with_capacity(3);
+    let clone = pool.clone();
+    assert_eq!(pool.strong_count(), 2);
+    drop(clone);
+
+    let mut owned = pool.checkout();
+    let shared = pool.checkout().downgrade();
+    let shared2 = shared.clone();
+    assert_eq!(pool.strong_count(), 4);
+    drop(pool);
+
+    // The checkouts still work once the pool is gone...
+    owned.0.push_str("still here");
+    assert_eq!(owned.0, "still here");
+    assert_eq!(shared2.0, "");
+    drop((owned, shared));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+    // ...and the last one to be released drops every item.
+    drop(shared2);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn read_handles() {
+    use natatorium::fixed::ReadHandle;
+
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_template(String::from("defaults"))
+        .finish();
+    let handle = pool.read_handle().expect("pool has a template");
+    assert_eq!(handle.len(), 1);
+    assert_eq!(handle.get(0).map(String::as_str), Some("defaults"));
+
+    // Exhausting the pool doesn't affect the handle.
+    let _owned = pool.checkout();
+    let shared = handle.checkout_shared(0).expect("in bounds");
+    assert_eq!(*shared, "defaults");
+    assert!(handle.checkout_shared(1).is_none());
+
+    // Handles can be built from any shared checkouts.
+    let mut owned = Pool::<String>::with_capacity(2).checkout();
+    owned.push_str("reference data");
+    let handle = ReadHandle::new(vec![owned.downgrade(), shared]);
+    let items: Vec<_> = handle.clone().iter().map(String::clone).collect();
+    assert_eq!(items, ["reference data", "defaults"]);
+
+    let untemplated: Pool<String> = Pool::with_capacity(1);
+    assert!(untemplated.read_handle().is_none());
+}
+
+#[test]
+fn take_and_release() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut owned = pool.checkout();
+    owned.push_str("taken");
+    let item = owned.take_and_release();
+    assert_eq!(item, "taken");
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.stats().detached, 1);
+
+    let owned = pool.checkout();
+    assert_eq!(*owned, "");
+    assert_eq!(pool.capacity(), 1);
+    drop(owned);
+
+    // Slots that were never refilled hold no item to return.
+    pool.checkout().take_and_release();
+    assert_eq!(pool.try_into_items().unwrap(), Vec::<String>::new());
+}
+
+#[test]
+fn take_and_release_with_clear_on_release() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .clear_on_release()
+        .finish();
+    let mut owned = pool.checkout();
+    owned.push_str("taken");
+    assert_eq!(owned.take_and_release(), "taken");
+    assert_eq!(*pool.checkout(), "");
+}
+
+#[cfg(feature = "timing-stats")]
+#[test]
+fn latency_stats() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    for _ in 0..10 {
+        drop(pool.checkout());
+    }
+    let stats = pool.latency_stats();
+    assert_eq!(stats.checkout.count(), 10);
+    assert_eq!(stats.growth.count(), 0);
+    let p99 = stats.checkout.quantile(0.99).expect("checkouts are quick");
+    assert!(stats.checkout.quantile(0.5).unwrap() <= p99);
+}
+
+#[test]
+fn with_total_bytes() {
+    const KB: usize = 1024;
+    let pool: Pool<Vec<u8>> = Pool::builder().with_total_bytes(64 * KB, 16 * KB).finish();
+    assert_eq!(pool.capacity(), 4);
+    assert!(pool.checkout().capacity() >= 16 * KB);
+
+    // Any remainder that doesn't fit a whole item is left over.
+    let pool: Pool<String> = Pool::builder().with_total_bytes(100, 30).finish();
+    assert_eq!(pool.capacity(), 3);
+}
+
+#[test]
+fn split() {
+    let pool: Pool<Vec<u8>> = Pool::with_capacity(1);
+    let mut owned = pool.checkout();
+    owned.extend_from_slice(b"headerbody");
+    let key = owned.key();
+    let (mut header, body) = owned.split(6);
+    assert_eq!(&*header, b"header");
+    assert_eq!(&*body, b"body");
+    header.make_ascii_uppercase();
+
+    // A split item isn't shared, so it can't be looked up by its key.
+    assert!(pool.get(key).is_none());
+    drop(header);
+    assert_eq!(pool.used(), 1);
+    drop(body);
+    assert_eq!(pool.used(), 0);
+
+    let owned = pool.checkout();
+    assert!(owned.is_empty());
+}
+
+#[test]
+fn split_parts_move_to_other_threads() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut owned = pool.checkout();
+    owned.push_str("hello world");
+    let (mut head, tail) = owned.split(5);
+    let t = std::thread::spawn(move || tail.trim().to_string());
+    head.make_ascii_uppercase();
+    assert_eq!(&*head, "HELLO");
+    assert_eq!(t.join().unwrap(), "world");
+}
+
+#[test]
+fn checkout_async_waits_for_release() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let waker = Arc::new(CountingWaker::default());
+
+    let mut free = pool.checkout_async();
+    let mut held = match poll(&mut free, &waker) {
+        std::task::Poll::Ready(held) => held,
+        std::task::Poll::Pending => panic!("the pool had a free item"),
+    };
+    held.push_str("hello");
+    let held = held.downgrade();
+
+    let mut waiting = pool.checkout_async();
+    assert!(poll(&mut waiting, &waker).is_pending());
+    assert!(poll(&mut waiting, &waker).is_pending());
+    assert_eq!(waker.woken(), 0);
+
+    // Releasing the last reference to a shared checkout wakes the task.
+    let clone = held.clone();
+    drop(held);
+    assert_eq!(waker.woken(), 0);
+    drop(clone);
+    assert_eq!(waker.woken(), 1);
+    match poll(&mut waiting, &waker) {
+        std::task::Poll::Ready(item) => assert_eq!(*item, ""),
+        std::task::Poll::Pending => panic!("an item was released"),
+    }
+}
+
+#[test]
+fn checkout_async_wakes_in_order_and_passes_on_cancelled_wakeups() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let (first, second) = (
+        Arc::new(CountingWaker::default()),
+        Arc::new(CountingWaker::default()),
+    );
+    let held = pool.checkout();
+
+    let mut a = pool.checkout_async();
+    let mut b = pool.checkout_async();
+    assert!(poll(&mut a, &first).is_pending());
+    assert!(poll(&mut b, &second).is_pending());
+
+    // Only the task that started waiting first is woken.
+    drop(held);
+    assert_eq!((first.woken(), second.woken()), (1, 0));
+
+    // It gave up without taking the item, so the next task is woken.
+    drop(a);
+    assert_eq!((first.woken(), second.woken()), (1, 1));
+    assert!(poll(&mut b, &second).is_ready());
+}
+
+#[test]
+fn checkout_async_keeps_its_place_when_an_item_is_taken() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let (first, second) = (
+        Arc::new(CountingWaker::default()),
+        Arc::new(CountingWaker::default()),
+    );
+    let held = pool.checkout();
+
+    let mut a = pool.checkout_async();
+    let mut b = pool.checkout_async();
+    assert!(poll(&mut a, &first).is_pending());
+    assert!(poll(&mut b, &second).is_pending());
+
+    // Another checkout takes the item before the woken task polls again.
+    drop(held);
+    let held = pool.try_checkout().expect("an item was released");
+    assert!(poll(&mut a, &first).is_pending());
+
+    // The woken task is still first in line.
+    drop(held);
+    assert_eq!((first.woken(), second.woken()), (2, 0));
+    assert!(poll(&mut a, &first).is_ready());
+}
+
+#[test]
+fn recycler_sees_released_items() {
+    use natatorium::{fixed::Recycled, Builder};
+    use std::sync::{Arc, Mutex};
+
+    let flushed = Arc::new(Mutex::new(Vec::new()));
+    let pool: Pool<String> = Builder::new()
+        .with_default()
+        .fixed()
+        .with_elements(2)
+        .with_recycler({
+            let flushed = flushed.clone();
+            move |item: Recycled<String>| flushed.lock().unwrap().push(item.clone())
+        })
+        .finish();
+
+    let mut a = pool.checkout();
+    a.push_str("hello");
+    drop(a);
+    // Shared checkouts go straight back to the pool.
+    let mut b = pool.checkout();
+    b.push_str("world");
+    drop(b.downgrade());
+
+    assert_eq!(*flushed.lock().unwrap(), ["hello"]);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(*pool.checkout(), "");
+}
+
+#[test]
+fn recycled_items_stay_checked_out_until_completed() {
+    use natatorium::Builder;
+    use std::sync::mpsc;
+
+    let (tx, rx) = mpsc::channel();
+    let pool: Pool<Vec<u8>> = Builder::new()
+        .with_default()
+        .fixed()
+        .with_elements(1)
+        .with_recycler(tx)
+        .finish();
+
+    let mut buf = pool.checkout();
+    buf.extend_from_slice(b"unflushed");
+    let key = buf.key();
+    drop(buf);
+    assert_eq!(pool.used(), 1);
+    assert!(pool.try_checkout().is_none());
+
+    let mut recycled = rx.try_recv().expect("the item was sent to the recycler");
+    assert_eq!(recycled.key(), key);
+    assert_eq!(&recycled[..], b"unflushed");
+    recycled.clear();
+    recycled.complete();
+    assert_eq!(pool.used(), 0);
+
+    // Once the receiver is gone, items are returned to the pool directly.
+    drop(rx);
+    drop(pool.checkout());
+    assert_eq!(pool.used(), 0);
+    assert!(pool.try_checkout().is_some());
+}
+
+#[test]
+fn checkout_timeout_gives_up() {
+    use std::time::{Duration, Instant};
+
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let held = pool.checkout();
+
+    let start = Instant::now();
+    assert!(pool.checkout_timeout(Duration::from_millis(20)).is_none());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(pool.waiters(), 0);
+
+    // The expired checkout doesn't keep its place in the queue.
+    drop(held);
+    assert!(pool.checkout_timeout(Duration::from_secs(0)).is_some());
+}
+
+#[test]
+fn checkout_timeout_succeeds_once_released() {
+    use std::time::Duration;
+
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut held = pool.checkout();
+    held.push_str("hello");
+    let releaser = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        drop(held);
+    });
+
+    let item = pool
+        .checkout_timeout(Duration::from_secs(60))
+        .expect("the item was released before the timeout");
+    assert_eq!(*item, "");
+    releaser.join().unwrap();
+}
+
+#[test]
+fn checkout_blocking_parks_until_released() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let held = pool.checkout();
+
+    let waiter = std::thread::spawn({
+        let pool = pool.clone();
+        move || drop(pool.checkout_blocking())
+    });
+    while pool.waiters() == 0 {
+        std::thread::yield_now();
+    }
+    drop(held);
+    waiter.join().unwrap();
+    assert_eq!(pool.used(), 0);
+}
@@ -26,7 +26,7 @@ fn new_checkouts_are_empty() {
         c.push_str("i'm checkout 3");
 
         t1.join().expect("thread 1 panicked");
-        t2.join().expect("thread 2 panicked");;
+        t2.join().expect("thread 2 panicked");
     });
 }
 
@@ -83,11 +83,11 @@ fn capacity_released_when_checkout_is_dropped() {
         let t = thread::spawn(move || {
             let checkout = pool2.checkout();
 
-            let &(ref lock, ref cv) = &*checked_out2;
+            let (lock, cv) = &*checked_out2;
             *lock.lock().unwrap() = true;
             cv.notify_one();
 
-            let &(ref lock, ref cv) = &*can_drop2;
+            let (lock, cv) = &*can_drop2;
             let mut can_drop = lock.lock().unwrap();
             while !*can_drop {
                 can_drop = cv.wait(can_drop).unwrap();
@@ -95,7 +95,7 @@ fn capacity_released_when_checkout_is_dropped() {
             drop(checkout);
         });
 
-        let &(ref lock, ref cv) = &*checked_out;
+        let (lock, cv) = &*checked_out;
         let mut checked_out = lock.lock().unwrap();
         while !*checked_out {
             checked_out = cv.wait(checked_out).unwrap();
@@ -104,7 +104,7 @@ fn capacity_released_when_checkout_is_dropped() {
         let ch = pool.try_checkout();
         assert!(ch.is_none());
 
-        let &(ref lock, ref cv) = &*can_drop;
+        let (lock, cv) = &*can_drop;
         *lock.lock().unwrap() = true;
         cv.notify_one();
 
@@ -1,4 +1,8 @@
-use natatorium::growable::Pool;
+mod support;
+
+use natatorium::growable::{CheckoutOr, Growth, OwnedSet, Pool};
+use std::sync::Arc;
+use support::{poll, CountingWaker};
 
 #[test]
 fn new_checkouts_are_empty() {
@@ -52,6 +56,15 @@ fn capacity_released_when_checkout_is_dropped() {
     assert!(pool.try_checkout().is_some());
 }
 
+#[test]
+fn checkout_discard_grows_empty_pool() {
+    let pool: Pool<String> = Pool::new();
+    assert_eq!(pool.capacity(), 0);
+    pool.checkout_discard();
+    assert!(pool.capacity() > 0);
+    assert_eq!(pool.used(), 0);
+}
+
 #[test]
 fn capacity_released_when_all_shared_refs_are_dropped() {
     let pool: Pool<String> = Pool::with_capacity(1);
@@ -75,43 +88,1594 @@ fn capacity_released_when_all_shared_refs_are_dropped() {
     assert!(pool.try_checkout().is_some());
 }
 
+#[test]
+fn stealing_free_items() {
+    let idle: Pool<String> = Pool::with_capacity(3);
+    let busy: Pool<String> = Pool::with_capacity(2);
+    {
+        // Warm up all of the idle pool's items.
+        let warm: Vec<_> = (0..3)
+            .map(|_| {
+                let mut item = idle.checkout();
+                item.reserve(1024);
+                item
+            })
+            .collect();
+        drop(warm);
+    }
+
+    // A checked out item can't be stolen, or exchanged.
+    let held = busy.checkout();
+    assert_eq!(busy.steal_free(&idle, 3), 1);
+    drop(held);
+    assert_eq!(busy.steal_free(&busy, 1), 0);
+
+    let (a, b) = (busy.checkout(), busy.checkout());
+    let warmed = [&a, &b]
+        .iter()
+        .filter(|item| item.capacity() >= 1024)
+        .count();
+    assert_eq!(warmed, 1);
+    assert_eq!(idle.capacity(), 3);
+    assert_eq!(busy.capacity(), 2);
+}
+
+#[test]
+fn upgrading_the_only_shared_ref() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let version = owned.version();
+    let shared = owned.downgrade();
+    let key = shared.key();
+
+    let clone = shared.clone();
+    let shared = shared.try_upgrade().expect_err("another reference exists");
+    drop(clone);
+
+    let mut owned = shared.try_upgrade().expect("the only reference");
+    assert_eq!(*owned, "hello");
+    assert_ne!(owned.version(), version);
+    assert!(pool.get(key).is_none());
+    owned.push_str(", world");
+
+    let shared = owned.downgrade();
+    assert_eq!(*pool.get(shared.key()).unwrap(), "hello, world");
+    drop(shared);
+    assert!(pool.try_checkout().is_some());
+}
+
+#[test]
+fn iterating_shared_refs_does_not_clone_checkouts() {
+    use natatorium::growable::Shared;
+
+    let pool: Pool<String> = Pool::with_capacity(3);
+    let shared: Vec<_> = ["a", "b", "c"]
+        .iter()
+        .map(|s| {
+            let mut checkout = pool.checkout();
+            checkout.push_str(s);
+            checkout.downgrade()
+        })
+        .collect();
+
+    let count = pool.strong_count();
+    assert_eq!(shared[1].get_ref(), "b");
+    let refs = Shared::iter_refs(&shared);
+    assert_eq!(refs.len(), 3);
+    assert_eq!(refs.rev().collect::<Vec<_>>(), vec!["c", "b", "a"]);
+    assert_eq!(pool.strong_count(), count);
+}
+
 #[test]
 fn checkout_grows_empty_pool() {
     let pool: Pool<String> = Pool::with_capacity(0);
-    assert_eq!(pool.size(), 0);
+    assert_eq!(pool.capacity(), 0);
     let _c = pool.checkout();
 
-    assert!(pool.size() > 0);
+    assert!(pool.capacity() > 0);
     assert_eq!(pool.used(), 1);
-    assert_eq!(pool.remaining(), pool.used() - 1);
+    assert_eq!(pool.available(), pool.used() - 1);
 }
 
 #[test]
 fn checkout_does_not_grow_if_free_capacity_remains() {
     let pool: Pool<String> = Pool::with_capacity(1);
-    assert_eq!(pool.size(), 1);
+    assert_eq!(pool.capacity(), 1);
     assert_eq!(pool.used(), 0);
-    assert_eq!(pool.remaining(), 1);
+    assert_eq!(pool.available(), 1);
 
     {
         let _c = pool.checkout();
-        assert_eq!(pool.size(), 1);
+        assert_eq!(pool.capacity(), 1);
         assert_eq!(pool.used(), 1);
-        assert_eq!(pool.remaining(), 0);
+        assert_eq!(pool.available(), 0);
     }
 
-    assert_eq!(pool.size(), 1);
+    assert_eq!(pool.capacity(), 1);
     assert_eq!(pool.used(), 0);
-    assert_eq!(pool.remaining(), 1);
+    assert_eq!(pool.available(), 1);
 
     // Dropping the checkout will release its capacity.
     let _c = pool.checkout();
-    assert_eq!(pool.size(), 1);
+    assert_eq!(pool.capacity(), 1);
+    assert_eq!(pool.used(), 1);
+    assert_eq!(pool.available(), 0);
+
+    let _c2 = pool.checkout();
+    assert!(pool.capacity() > 1);
+    assert_ne!(pool.used(), 1);
+}
+
+#[test]
+fn checkouts_compare_and_hash_like_their_items() {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+
+    fn hash(t: &impl Hash) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        t.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let pool: Pool<String> = Pool::with_capacity(2);
+
+    let mut c1 = pool.checkout();
+    c1.push_str("hello");
+    let mut c2 = pool.checkout();
+    c2.push_str("hello");
+    assert!(c1 == c2);
+    assert_eq!(c1.as_ref(), "hello");
+    assert_eq!(hash(&c1), hash(&String::from("hello")));
+
+    let s1 = c1.downgrade();
+    let s2 = c2.downgrade();
+    assert!(s1 == s2);
+    assert_eq!(hash(&s1), hash(&s2));
+}
+
+#[test]
+fn checkouts_forward_io() {
+    use natatorium::Clear;
+    use std::io::{self, Read, Write};
+
+    #[derive(Default)]
+    struct Buf(io::Cursor<Vec<u8>>);
+
+    impl Clear for Buf {
+        fn clear(&mut self) {
+            self.0.get_mut().clear();
+            self.0.set_position(0);
+        }
+    }
+
+    impl Read for Buf {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Buf {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.0.flush()
+        }
+    }
+
+    let pool: Pool<Buf> = Pool::with_capacity(1);
+
+    let mut c = pool.checkout();
+    write!(c, "hello {}", 42).unwrap();
+    c.0.set_position(0);
+
+    let mut read = String::new();
+    c.read_to_string(&mut read).unwrap();
+    assert_eq!(read, "hello 42");
+}
+
+#[test]
+fn write_to_checkout() {
+    use std::fmt::Write;
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    write!(pool.checkout(), "hello {}", 42).unwrap();
+
+    let mut c = pool.checkout();
+    assert_eq!("", *c);
+    write!(c, "hello {}", 42).unwrap();
+    assert_eq!("hello 42", *c);
+}
+
+#[test]
+fn drain_when_idle_waits_for_checkouts() {
+    use std::{sync::mpsc, thread, time::Duration};
+    let pool: Pool<String> = Pool::with_capacity(2);
+
+    let (tx, rx) = mpsc::channel();
+    let p = pool.clone();
+    let t = thread::spawn(move || {
+        let owned = p.checkout();
+        let shared = p.checkout().downgrade();
+        let shared2 = shared.clone();
+        tx.send(()).unwrap();
+
+        thread::sleep(Duration::from_millis(10));
+        drop(owned);
+        drop(shared);
+        thread::sleep(Duration::from_millis(10));
+        drop(shared2);
+    });
+
+    rx.recv().unwrap();
+    assert_eq!(pool.used(), 2);
+    pool.drain_when_idle();
+    assert_eq!(pool.used(), 0);
+    t.join().unwrap();
+
+    // Draining an idle pool returns immediately.
+    pool.drain_when_idle();
+}
+
+#[test]
+fn expired_leases_are_reclaimed() {
+    use std::{thread, time::Duration};
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_lease(Duration::from_millis(1))
+        .finish();
+
+    let stuck = pool.checkout().downgrade();
+    let stuck2 = stuck.clone();
+    thread::sleep(Duration::from_millis(5));
+
+    assert_eq!(pool.reclaim_expired(), 1);
+    assert_eq!(pool.lost(), 1);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), 0);
+
+    // Checking out grows the pool rather than reusing the lost slot.
+    let c = pool.checkout();
+    assert_eq!(*c, "");
+    assert!(pool.capacity() > 1);
+    drop(stuck);
+    drop(stuck2);
+    assert_eq!(pool.used(), 1);
+    assert_eq!(pool.lost(), 1);
+}
+
+#[test]
+fn remaining_never_exceeds_size_during_growth() {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+    };
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let done = Arc::new(AtomicBool::new(false));
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                for i in 0..100 {
+                    let checkouts: Vec<_> = (0..i % 8).map(|_| pool.checkout()).collect();
+                    drop(checkouts);
+                }
+            })
+        })
+        .collect();
+
+    let p = pool.clone();
+    let d = done.clone();
+    let observer = thread::spawn(move || {
+        while !d.load(Ordering::Acquire) {
+            let remaining = p.available();
+            let size = p.capacity();
+            assert!(
+                remaining <= size,
+                "remaining ({}) > size ({})",
+                remaining,
+                size
+            );
+        }
+    });
+
+    for t in threads {
+        t.join().unwrap();
+    }
+    done.store(true, Ordering::Release);
+    observer.join().unwrap();
+
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), pool.capacity());
+}
+
+#[test]
+fn with_capacity_and_fn() {
+    let pool = Pool::with_capacity_and_fn(2, || String::with_capacity(64));
+    assert_eq!(pool.capacity(), 2);
+
+    let c = pool.checkout();
+    assert_eq!(*c, "");
+    assert!(c.capacity() >= 64);
+
+    let pool = Pool::with_fn(|| String::with_capacity(64));
+    assert_eq!(pool.capacity(), 0);
+    assert!(pool.checkout().capacity() >= 64);
+}
+
+#[test]
+fn checkouts_dropped_while_panicking_poison_their_slot() {
+    use std::panic::{self, AssertUnwindSafe};
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut c = pool.checkout();
+        c.push_str("half-written");
+        panic!("oh no");
+    }));
+    assert!(result.is_err());
+
+    assert_eq!(pool.poisoned(), 1);
+    assert_eq!(pool.poisoned_slots(), vec![0]);
+    assert!(pool.try_checkout().is_none());
+
+    // Checking out grows the pool rather than reusing the poisoned slot.
+    let c = pool.checkout();
+    assert_eq!(pool.capacity(), 2);
+    drop(c);
+
+    assert!(pool.repair(0));
+    assert_eq!(pool.poisoned(), 0);
+    assert_eq!(pool.available(), 2);
+}
+
+#[test]
+fn with_clear_fn() {
+    use std::collections::VecDeque;
+    let pool = Pool::builder()
+        .with_fn(|| VecDeque::<u8>::with_capacity(8))
+        .with_clear_fn(VecDeque::clear)
+        .with_elements(1)
+        .finish();
+
+    let mut c = pool.checkout();
+    c.push_back(1);
+    c.push_back(2);
+    drop(c);
+
+    let c = pool.checkout();
+    assert!(c.is_empty());
+    assert!(c.capacity() >= 8);
+}
+
+#[test]
+fn pre_touch_applies_to_grown_slots() {
+    let pool = Pool::builder()
+        .with_fn(|| String::with_capacity(64 * 1024))
+        .pre_touch(true)
+        .with_elements(0)
+        .finish();
+
+    let c = pool.checkout();
+    assert_eq!(*c, "");
+    assert!(c.capacity() >= 64 * 1024);
+}
+
+#[test]
+fn try_into_items() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+
+    let mut c = pool.checkout();
+    c.push_str("hello");
+    let pool = pool.try_into_items().expect_err("an item is checked out");
+    drop(c);
+
+    let pool2 = pool.clone();
+    let pool = pool.try_into_items().expect_err("the pool has been cloned");
+    drop(pool2);
+
+    let items = pool
+        .try_into_items()
+        .unwrap_or_else(|_| panic!("pool is idle"));
+    assert_eq!(items.len(), 2);
+}
+
+#[test]
+fn promote_shared_checkouts_from_leaked_pool() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let leaked = pool.leak();
+
+    let mut c = leaked.checkout();
+    c.push_str("config");
+    let config: &'static String = leaked
+        .promote(c.downgrade())
+        .unwrap_or_else(|_| panic!("checkout is from the same pool"));
+    assert_eq!(config, "config");
+    assert_eq!(leaked.used(), 1);
+    assert!(format!("{:?}", leaked).contains("used: 1"));
+
+    let other: Pool<String> = Pool::with_capacity(1);
+    assert!(leaked.promote(other.checkout().downgrade()).is_err());
+}
+
+#[test]
+fn audit_accounts_for_every_slot() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    let report = pool.audit();
+    assert!(report.is_consistent(), "{:#?}", report);
+    assert_eq!(report.free, 4);
+
+    let c1 = pool.checkout();
+    let c2 = pool.checkout().downgrade();
+    let _c3 = c2.clone();
+    drop(c1);
+
+    let report = pool.audit();
+    assert!(report.is_consistent(), "{:#?}", report);
+    assert_eq!(report.missing(), 0);
+    assert_eq!(report.used, 1);
+    assert_eq!(report.checked_out, 1);
+    assert_eq!(report.free, 3);
+}
+
+#[test]
+fn small_capacities_are_exact() {
+    for cap in 0..8 {
+        let pool: Pool<String> = Pool::with_capacity(cap);
+        assert_eq!(pool.capacity(), cap);
+        assert_eq!(pool.available(), cap);
+
+        let checkouts: Vec<_> = (0..cap).map(|_| pool.checkout()).collect();
+        assert_eq!(
+            pool.capacity(),
+            cap,
+            "checkouts within capacity must not grow"
+        );
+        assert_eq!(pool.available(), 0);
+        drop(checkouts);
+    }
+}
+
+#[test]
+fn growing_by_half_always_makes_progress() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .grow_by_half()
+        .finish();
+    let _c1 = pool.checkout();
+    let _c2 = pool.checkout();
+    assert_eq!(pool.capacity(), 2);
+
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(0)
+        .grow_by(0)
+        .finish();
+    let _c = pool.checkout();
+    assert_eq!(pool.capacity(), 1);
+}
+
+#[test]
+fn custom_growth() {
+    // Double, but never add more than 4 slots at a time.
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_growth(Growth::Custom(Arc::new(|size| size.min(4))))
+        .finish();
+    let mut checkouts = Vec::new();
+    let mut sizes = Vec::new();
+    for _ in 0..16 {
+        checkouts.push(pool.checkout());
+        if sizes.last() != Some(&pool.capacity()) {
+            sizes.push(pool.capacity());
+        }
+    }
+    assert_eq!(sizes, vec![2, 4, 8, 12, 16]);
+
+    // A custom policy returning zero still makes progress.
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(0)
+        .with_growth(Growth::Custom(Arc::new(|_| 0)))
+        .finish();
+    let _c = pool.checkout();
+    assert_eq!(pool.capacity(), 1);
+}
+
+#[test]
+fn builders_with_custom_growth_can_be_cloned() {
+    let builder = Pool::<String, ()>::builder()
+        .with_default()
+        .with_elements(1)
+        .with_growth(Growth::Custom(Arc::new(|_| 3)));
+    for pool in [builder.clone().finish(), builder.finish()] {
+        let _a = pool.checkout();
+        let _b = pool.checkout();
+        assert_eq!(pool.capacity(), 4);
+    }
+}
+
+#[test]
+fn try_grow() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_growth(Growth::Double)
+        .finish();
+    // The pool grows even though none of its items are checked out.
+    assert_eq!(pool.try_grow(), Ok(2));
+    assert_eq!(pool.capacity(), 4);
+    assert_eq!(pool.available(), 4);
+    assert_eq!(pool.try_grow(), Ok(4));
+    assert_eq!(pool.capacity(), 8);
+
+    // Checkouts use the slots added ahead of time, without growing further.
+    let checkouts: Vec<_> = (0..8).map(|_| pool.checkout()).collect();
+    assert_eq!(pool.capacity(), 8);
+    drop(checkouts);
+
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(0)
+        .with_growth(Growth::Fixed(0))
+        .finish();
+    assert_eq!(pool.try_grow(), Ok(1));
+}
+
+#[test]
+fn checkouts_are_stable_across_growth() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut owned = pool.checkout();
+    owned.push_str("owned");
+    let owned_ptr = &*owned as *const String;
+    let owned_key = owned.key();
+
+    let mut shared = pool.checkout();
+    shared.push_str("shared");
+    let shared = shared.downgrade();
+    let shared_ptr = &*shared as *const String;
+    let shared_key = shared.key();
+
+    // Grow the pool many times over, both by checking out more items and
+    // explicitly.
+    let mut checkouts = Vec::new();
+    for i in 0..64 {
+        let mut checkout = pool.checkout();
+        checkout.push_str(&i.to_string());
+        checkouts.push(checkout);
+    }
+    pool.try_grow().unwrap();
+    assert!(pool.capacity() > 64);
+
+    assert_eq!(&*owned as *const String, owned_ptr);
+    assert_eq!(owned.key(), owned_key);
+    assert_eq!(*owned, "owned");
+    assert_eq!(&*shared as *const String, shared_ptr);
+    assert_eq!(shared.key(), shared_key);
+    assert_eq!(*shared, "shared");
+    let got = pool.get(shared_key).expect("shared checkout is still held");
+    assert_eq!(&*got as *const String, shared_ptr);
+    for (i, checkout) in checkouts.iter().enumerate() {
+        assert_eq!(**checkout, i.to_string());
+    }
+}
+
+#[test]
+fn get_races_with_growth() {
+    use std::thread;
+
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_growth(Growth::Fixed(1))
+        .finish();
+    let mut shared = pool.checkout();
+    shared.push_str("hello");
+    let shared = shared.downgrade();
+    let ptr = &*shared as *const String;
+    let addr = ptr as usize;
+    let key = shared.key();
+
+    thread::scope(|scope| {
+        let reader = scope.spawn(|| {
+            for _ in 0..1000 {
+                let got = pool.get(key).expect("shared checkout is still held");
+                assert_eq!(&*got as *const String as usize, addr);
+                assert_eq!(*got, "hello");
+            }
+        });
+        // Growing by one slot at a time reallocates the slot array often.
+        let checkouts: Vec<_> = (0..256).map(|_| pool.checkout()).collect();
+        reader.join().unwrap();
+        drop(checkouts);
+    });
+    assert_eq!(pool.capacity(), 257);
+    assert_eq!(&*shared as *const String, ptr);
+}
+
+#[test]
+fn elements_per_block() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(3)
+        .with_elements_per_block(5)
+        .finish();
+    let mut checkouts = Vec::new();
+    let mut sizes = Vec::new();
+    for _ in 0..20 {
+        checkouts.push(pool.checkout());
+        if sizes.last() != Some(&pool.capacity()) {
+            sizes.push(pool.capacity());
+        }
+    }
+    assert_eq!(sizes, vec![3, 8, 13, 18, 23]);
+    assert_eq!(pool.try_grow(), Ok(5));
+}
+
+#[test]
+fn blocks() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_growth(Growth::Double)
+        .finish();
+    let checkouts: Vec<_> = (0..5).map(|_| pool.checkout()).collect();
+    assert_eq!(pool.capacity(), 8);
+    let blocks: Vec<_> = (0..8).map(|idx| pool.block_of(idx).unwrap()).collect();
+    assert_eq!(blocks, vec![0, 0, 1, 1, 2, 2, 2, 2]);
+    assert_eq!(pool.block_of(8), None);
+
+    for checkout in &checkouts {
+        assert_eq!(
+            Some(checkout.block_id()),
+            pool.block_of(checkout.key().index())
+        );
+    }
+    let shared = pool.checkout().downgrade();
+    assert_eq!(shared.block_id(), 2);
+
+    // Growing explicitly adds a block, too.
+    pool.try_grow().unwrap();
+    assert_eq!(pool.block_of(8), Some(3));
+}
+
+#[test]
+fn retain_free() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    let mut checkouts: Vec<_> = (0..4).map(|_| pool.checkout()).collect();
+    for (i, checkout) in checkouts.iter_mut().enumerate() {
+        checkout.push_str(if i % 2 == 0 { "stale" } else { "fresh" });
+    }
+    let held = checkouts.pop().unwrap();
+    let stale = checkouts.pop().unwrap().downgrade();
+    drop(checkouts);
+
+    // Only the free stale item is replaced; the checked out ones are left
+    // alone.
+    let mut visited = Vec::new();
+    let replaced = pool.retain_free(|item| {
+        visited.push(item.clone());
+        item != "stale"
+    });
+    assert_eq!(replaced, 1);
+    visited.sort();
+    assert_eq!(visited, vec!["fresh", "stale"]);
+    assert_eq!(*held, "fresh");
+    assert_eq!(*stale, "stale");
+    assert_eq!(pool.used(), 2);
+    assert_eq!(pool.available(), 2);
+    assert_eq!(pool.audit().missing(), 0);
+
+    // The stale item was replaced with a new one.
+    let mut visited = Vec::new();
+    let replaced = pool.retain_free(|item| {
+        visited.push(item.clone());
+        true
+    });
+    assert_eq!(replaced, 0);
+    visited.sort();
+    assert_eq!(visited, vec!["", "fresh"]);
+
+    // Every free item can still be checked out.
+    let a = pool.checkout();
+    let b = pool.checkout();
+    assert!(pool.try_checkout().is_none());
+    drop((a, b, held, stale));
+    assert_eq!(pool.available(), 4);
+}
+
+#[test]
+fn grow_past_max_slots() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_growth(Growth::Custom(Arc::new(|_| natatorium::MAX_SLOTS - 1)))
+        .finish();
+    let error = pool.try_grow().unwrap_err();
+    assert!(error.is_too_many_slots());
+    assert!(!error.is_alloc_failed());
+    assert_eq!(
+        error.to_string(),
+        format!("a pool may have at most {} slots", natatorium::MAX_SLOTS)
+    );
+    assert_eq!(pool.capacity(), 2);
+}
+
+#[test]
+fn contains() {
+    let pool1: Pool<String> = Pool::with_capacity(4);
+    let pool2: Pool<String> = Pool::with_capacity(4);
+
+    let c1 = pool1.checkout();
+    let c2 = pool2.checkout();
+    assert!(pool1.contains(&c1));
+    assert!(!pool1.contains(&c2));
+    assert!(pool2.contains(&c2));
+    assert!(!pool2.contains(&c1));
+
+    let shared = c1.downgrade();
+    assert!(pool1.contains(&shared));
+
+    let not_pooled = String::new();
+    assert!(!pool1.contains(&not_pooled));
+}
+
+#[test]
+fn owned_set_releases_all_at_once() {
+    let pool: Pool<String> = Pool::with_capacity(8);
+    let mut set = OwnedSet::new();
+    assert!(set.is_empty());
+    for i in 0..8 {
+        let mut checkout = pool.checkout();
+        checkout.push_str(&i.to_string());
+        set.push(checkout);
+    }
+    assert_eq!(set.len(), 8);
+    assert_eq!(pool.available(), 0);
+
+    for item in set.iter_mut() {
+        item.push('!');
+    }
+    let items: Vec<_> = set.iter().cloned().collect();
+    assert_eq!(items, (0..8).map(|i| format!("{}!", i)).collect::<Vec<_>>());
+
+    drop(set);
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.available(), 8);
+    assert!(pool.audit().is_consistent());
+
+    // Every released slot is reachable from the free list again.
+    let checkouts: Vec<_> = (0..8).map(|_| pool.checkout()).collect();
+    assert!(checkouts.iter().all(|c| c.is_empty()));
+    assert_eq!(pool.capacity(), 8);
+}
+
+#[test]
+#[should_panic(expected = "must all come from the same pool")]
+fn owned_set_rejects_other_pools() {
+    let pool1: Pool<String> = Pool::with_capacity(1);
+    let pool2: Pool<String> = Pool::with_capacity(1);
+    let mut set = OwnedSet::new();
+    set.push(pool1.checkout());
+    set.push(pool2.checkout());
+}
+
+#[test]
+fn pressure_watermarks() {
+    use natatorium::Pressure;
+    use std::sync::{Arc, Mutex};
+
+    let changes = Arc::new(Mutex::new(Vec::new()));
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(4)
+        .with_watermarks(2, 3)
+        .on_pressure_change({
+            let changes = changes.clone();
+            move |pressure| changes.lock().unwrap().push(pressure)
+        })
+        .finish();
+
+    assert_eq!(pool.pressure(), Pressure::Low);
+    let c1 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::Low);
+    let c2 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::Medium);
+    let c3 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::High);
+    let c4 = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::High);
+
+    drop((c3, c4));
+    assert_eq!(pool.pressure(), Pressure::Medium);
+    drop((c1, c2));
+    assert_eq!(pool.pressure(), Pressure::Low);
+
+    assert_eq!(
+        *changes.lock().unwrap(),
+        vec![
+            Pressure::Medium,
+            Pressure::High,
+            Pressure::Medium,
+            Pressure::Low
+        ]
+    );
+}
+
+#[test]
+fn tagged_releases() {
+    use std::sync::{Arc, Mutex};
+
+    let released = Arc::new(Mutex::new(Vec::new()));
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(4)
+        .on_release({
+            let released = released.clone();
+            move |tag| released.lock().unwrap().push(tag)
+        })
+        .finish();
+
+    let mut c1 = pool.checkout().with_tag(1);
+    c1.push_str("hello");
+    assert_eq!(c1.tag(), 1);
+    let c2 = pool.checkout();
+    let c3 = pool.checkout().with_tag(3);
+    let c4 = pool.checkout().with_tag(4).into_owned();
+
+    drop(c2);
+    drop(c3);
+    drop(c1);
+    drop(c4);
+    assert_eq!(*released.lock().unwrap(), vec![3, 1]);
+    assert_eq!(pool.used(), 0);
+}
+
+#[test]
+fn pressure_without_watermarks_is_low() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let _c = pool.checkout();
+    assert_eq!(pool.pressure(), natatorium::Pressure::Low);
+}
+
+#[test]
+fn seeded_items_are_reused() {
+    let seed = (0..2).map(|_| String::with_capacity(100));
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(4)
+        .seeded(seed)
+        .finish();
+    assert_eq!(pool.capacity(), 4);
+    let checkouts: Vec<_> = (0..4).map(|_| pool.checkout()).collect();
+    assert_eq!(checkouts.iter().filter(|c| c.capacity() >= 100).count(), 2);
+    // Seeded items are cleared on checkout, like any other item.
+    assert!(checkouts.iter().all(|c| c.is_empty()));
+}
+
+#[test]
+fn seeded_items_can_exceed_capacity() {
+    let seed = vec![String::from("a"), String::from("b"), String::from("c")];
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .seeded(seed)
+        .finish();
+    assert_eq!(pool.capacity(), 3);
+}
+
+#[test]
+fn retry_histogram() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    // Growing the pool is not counted as a retry.
+    let _c1 = pool.checkout();
+    let _c2 = pool.checkout();
+    let histogram = pool.retry_histogram();
+    assert_eq!(histogram.checkouts(), 2);
+    assert_eq!(histogram.buckets()[0], 2);
+}
+
+#[test]
+fn checkout_with_ctor() {
+    let pool: Pool<Vec<u8>> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_ctor("small", |buf: &mut Vec<u8>| buf.reserve(16))
+        .with_ctor("large", |buf: &mut Vec<u8>| buf.resize(1024, 0))
+        .finish();
+
+    let small = pool.checkout_with_ctor("small");
+    assert!(small.capacity() >= 16);
+    assert!(small.is_empty());
+    let large = pool.checkout_with_ctor("large");
+    assert_eq!(large.len(), 1024);
+    drop((small, large));
+
+    // Items are still cleared before they are initialized again.
+    let small = pool.checkout_with_ctor("small");
+    assert!(small.is_empty());
+}
+
+#[test]
+fn stats_delta() {
+    use natatorium::{Stats, StatsCursor};
+
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let mut cursor = StatsCursor::new();
+
+    // Growing the pool is not counted as anything but a checkout.
+    let c1 = pool.checkout();
+    let c2 = pool.checkout();
+    let delta = pool.stats_delta(&mut cursor);
+    assert_eq!(delta.checkouts, 2);
+    assert_eq!(delta.releases, 0);
+
+    drop((c1, c2));
+    drop(pool.checkout());
+    let delta = pool.stats_delta(&mut cursor);
+    assert_eq!(delta.checkouts, 1);
+    assert_eq!(delta.releases, 3);
+
+    assert_eq!(pool.stats_delta(&mut cursor), Stats::default());
+    assert_eq!(pool.stats().checkouts, 3);
+}
+
+#[test]
+#[should_panic(expected = "no constructor named \"huge\" was registered")]
+fn checkout_with_unknown_ctor() {
+    let pool: Pool<Vec<u8>> = Pool::builder()
+        .with_default()
+        .with_ctor("small", |buf: &mut Vec<u8>| buf.reserve(16))
+        .finish();
+    let _ = pool.checkout_with_ctor("huge");
+}
+
+#[test]
+fn get_by_key() {
+    let pool: Pool<String> = Pool::with_capacity(2);
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let key = owned.key();
+
+    // Owned checkouts can't be looked up, since they allow mutation.
+    assert!(pool.get(key).is_none());
+
+    let shared = owned.downgrade();
+    assert_eq!(shared.key(), key);
+    let found = pool.get(key).expect("shared checkout should be found");
+    assert_eq!(&*found, "hello");
+
+    drop((shared, found));
+    assert_eq!(pool.used(), 0);
+    assert!(pool.get(key).is_none());
+
+    // Checking the slot out again doesn't revive old keys.
+    let shared = pool.checkout().downgrade();
+    assert_eq!(shared.key().index(), key.index());
+    assert!(pool.get(key).is_none());
+    assert!(pool.get(shared.key()).is_some());
+}
+
+#[test]
+fn default_clone_and_debug() {
+    // None of these require the item type to implement `Clone` or `Debug`.
+    #[derive(Default)]
+    struct Item(Vec<u8>);
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.0.clear()
+        }
+    }
+
+    let pool: Pool<Item> = Pool::default();
+    assert_eq!(pool.capacity(), Pool::<Item>::DEFAULT_CAPACITY);
+    let clone = pool.clone();
+    let _c = clone.checkout();
     assert_eq!(pool.used(), 1);
-    assert_eq!(pool.remaining(), 0);
+    assert!(format!("{:?}", pool).contains("used: 1"));
+}
+
+#[test]
+fn concurrent_growth_does_not_overshoot() {
+    use std::{
+        sync::{Arc, Barrier},
+        thread,
+    };
+
+    const THREADS: usize = 16;
+    for _ in 0..50 {
+        let pool: Pool<String> = Pool::builder()
+            .with_default()
+            .with_elements(0)
+            .with_growth(Growth::Double)
+            .finish();
+        let start = Arc::new(Barrier::new(THREADS));
+        let done = Arc::new(Barrier::new(THREADS + 1));
+        let threads: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let pool = pool.clone();
+                let (start, done) = (start.clone(), done.clone());
+                thread::spawn(move || {
+                    start.wait();
+                    let checkout = pool.checkout();
+                    // Hold every checkout until they've all been made.
+                    done.wait();
+                    drop(checkout);
+                })
+            })
+            .collect();
+
+        done.wait();
+        // The pool only grows while every item is checked out, so doubling
+        // never takes it past the number of concurrent checkouts.
+        assert_eq!(pool.capacity(), THREADS);
+        for t in threads {
+            t.join().unwrap();
+        }
+    }
+}
+
+#[test]
+fn pool_ids() {
+    let pool1: Pool<String> = Pool::new();
+    let pool2: Pool<String> = Pool::new();
+    assert_ne!(pool1.id(), pool2.id());
+
+    // Growing the pool doesn't change its id.
+    let id = pool1.id();
+    let owned = pool1.checkout();
+    assert_eq!(pool1.clone().id(), id);
+    assert_eq!(owned.pool_id(), id);
+    let shared = pool2.checkout().downgrade();
+    assert_eq!(shared.pool_id(), pool2.id());
+}
+
+#[test]
+fn round_robin_reuse_after_growth() {
+    let pool: Pool<String> = Pool::builder()
+        .with_elements(2)
+        .with_reuse(natatorium::Reuse::RoundRobin)
+        .with_default()
+        .growable()
+        .finish();
+    let a = pool.checkout();
+    let b = pool.checkout();
+    let c = pool.checkout();
+    assert!(pool.capacity() > 2);
+    assert_eq!(c.key().index(), 2);
+    drop((a, b, c));
+
+    let capacity = pool.capacity();
+    let indices: Vec<_> = (0..capacity)
+        .map(|_| pool.checkout().key().index())
+        .collect();
+    let mut sorted = indices.clone();
+    sorted.sort_unstable();
+    sorted.dedup();
+    assert_eq!(
+        sorted.len(),
+        capacity,
+        "every slot reused once: {:?}",
+        indices
+    );
+    assert_eq!(pool.audit().free, capacity);
+}
+
+#[test]
+fn guards_forward_display_and_debug() {
+    let pool: Pool<String> = Pool::new();
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    assert_eq!(format!("{} {:?}", owned, owned), "hello \"hello\"");
+    let shared = owned.downgrade();
+    assert_eq!(format!("{} {:?}", shared, shared), "hello \"hello\"");
+}
+
+#[test]
+fn checkout_or_prefers_scratch() {
+    let pool: Pool<String> = Pool::new();
+    let mut scratch = Some(String::from("stale"));
+    let c = pool.checkout_or(&mut scratch);
+    assert!(matches!(c, CheckoutOr::Scratch(_)));
+    assert_eq!(*c, "");
+    assert_eq!(pool.used(), 0);
+
+    let mut empty = None;
+    let c = pool.checkout_or(&mut empty);
+    assert!(matches!(c, CheckoutOr::Owned(_)));
+    assert_eq!(pool.used(), 1);
+}
+
+#[test]
+fn dirty_tracking_skips_clearing_unmodified_items() {
+    #[derive(Default)]
+    struct Item {
+        data: Vec<u8>,
+        clears: usize,
+    }
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.data.clear();
+            self.clears += 1;
+        }
+    }
+
+    let pool: Pool<Item> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_dirty_tracking()
+        .finish();
+    // New items are cleared when they're first checked out.
+    let checkout = pool.checkout();
+    assert_eq!(checkout.clears, 1);
+
+    // Reading an item, or downgrading it, doesn't make it dirty.
+    drop(checkout.downgrade());
+    let checkout = pool.checkout();
+    assert_eq!(checkout.clears, 1);
+    drop(checkout);
+
+    let mut checkout = pool.checkout();
+    assert_eq!(checkout.clears, 1);
+    checkout.data.push(1);
+    drop(checkout.downgrade());
+    let checkout = pool.checkout();
+    assert_eq!((checkout.clears, checkout.data.len()), (2, 0));
+    drop(checkout);
+
+    // Modifying items in an `OwnedSet` marks them dirty as well.
+    let mut set = OwnedSet::new();
+    set.push(pool.checkout());
+    set.iter_mut().for_each(|item| item.data.push(2));
+    drop(set);
+    let checkout = pool.checkout();
+    assert_eq!((checkout.clears, checkout.data.len()), (3, 0));
+
+    drop(checkout);
+
+    // Without dirty tracking, every checkout is cleared.
+    let pool: Pool<Item> = Pool::builder().with_default().with_elements(1).finish();
+    drop(pool.checkout());
+    assert_eq!(pool.checkout().clears, 2);
+}
 
+#[test]
+fn clear_on_release() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static CLEARS: AtomicUsize = AtomicUsize::new(0);
+    #[derive(Default)]
+    struct Item(Vec<u8>);
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.0.clear();
+            CLEARS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let pool: Pool<Item> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .clear_on_release()
+        .finish();
+    // New items are still cleared when they're first checked out.
+    let mut checkout = pool.checkout();
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 1);
+    checkout.0.extend_from_slice(b"secret");
+    drop(checkout);
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 2);
+
+    // Released items are not cleared again when they're checked out.
+    let mut checkout = pool.checkout();
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 2);
+    assert!(checkout.0.is_empty());
+    checkout.0.extend_from_slice(b"secret");
+
+    // Shared items are cleared once the last reference is dropped.
+    let shared = checkout.downgrade();
+    let clone = shared.clone();
+    drop(shared);
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 2);
+    drop(clone);
+    assert_eq!(CLEARS.load(Ordering::SeqCst), 3);
+
+    let items = pool.try_into_items().ok().unwrap();
+    assert!(items.iter().all(|item| item.0.is_empty()));
+}
+
+#[test]
+fn downcast() {
+    use natatorium::AnyItem;
+
+    let pool: Pool<AnyItem, _> =
+        Pool::with_capacity_and_fn(1, || Box::new(String::new()) as AnyItem);
+
+    let item = pool.checkout();
+    let item = item.downcast::<Vec<u8>>().expect_err("not bytes");
+    let mut string = item.downcast::<String>().expect("a string");
+    string.push_str("hello");
+    drop(string);
+
+    let string = pool.checkout().downcast::<String>().unwrap();
+    assert_eq!(*string, "");
+}
+
+#[test]
+fn verify() {
+    let pool: Pool<String> = Pool::with_capacity(4);
+    let held = pool.checkout();
+    let report = pool.verify();
+    assert!(report.is_ok(), "{:#?}", report);
+    assert_eq!(report.checked_out, 3);
+
+    // Verifying the pool doesn't grow it.
+    assert_eq!(pool.capacity(), 4);
+    drop(held);
+    assert_eq!(pool.verify().checked_out, 4);
+}
+
+#[test]
+fn dump_all() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    pool.register("growable-dump-all");
+    let _c1 = pool.checkout();
     let _c2 = pool.checkout();
-    assert!(pool.size() > 1);
-    assert_ne!(pool.used(), 1);;
 
+    let mut out = Vec::new();
+    natatorium::dump_all(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let row = out
+        .lines()
+        .find(|line| line.starts_with("growable-dump-all "))
+        .expect("pool was registered");
+    let columns: Vec<_> = row.split_whitespace().collect();
+    assert_eq!(columns[2..], ["2", "2", "2", "2"]);
+}
+
+#[test]
+fn versions() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+
+    let mut owned = pool.checkout();
+    owned.push_str("hello");
+    let v1 = owned.version();
+    let shared = owned.downgrade();
+    assert_eq!(shared.version(), v1);
+    assert_eq!(shared.clone().version(), v1);
+    let key = shared.key();
+    assert_eq!(pool.get(key).expect("still shared").version(), v1);
+    drop(shared);
+
+    // The same slot is checked out again, with a new version.
+    let owned = pool.checkout();
+    assert_ne!(owned.version(), v1);
+    let v2 = owned.version();
+    drop(owned);
+    assert_ne!(pool.checkout().version(), v2);
+}
+
+#[test]
+fn checkout_nth() {
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(2)
+        .with_elements_per_block(2)
+        .finish();
+    let held = (pool.checkout(), pool.checkout());
+    // Grow the pool by a block, and take the block's last slot.
+    drop(pool.checkout());
+    assert_eq!(pool.capacity(), 4);
+    let last = pool.checkout_nth(3).expect("slot 3 is free");
+    assert_eq!(last.block_id(), 1);
+    assert!(pool.checkout_nth(0).is_none());
+    drop(held);
+    assert_eq!(
+        pool.checkout_nth(0).expect("slot 0 is free").key().index(),
+        0
+    );
+}
+
+#[test]
+fn checkouts_outlive_the_pool() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Default)]
+    struct Item(String);
+
+    impl natatorium::Clear for Item {
+        fn clear(&mut self) {
+            self.0.clear();
+        }
+    }
+
+    impl Drop for Item {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let pool: Pool<Item> = Pool::with_capacity(3);
+    let clone = pool.clone();
+    assert_eq!(pool.strong_count(), 2);
+    drop(clone);
+
+    let mut owned = pool.checkout();
+    let shared = pool.checkout().downgrade();
+    let shared2 = shared.clone();
+    assert_eq!(pool.strong_count(), 4);
+    drop(pool);
+
+    // The checkouts still work once the pool is gone...
+    owned.0.push_str("still here");
+    assert_eq!(owned.0, "still here");
+    assert_eq!(shared2.0, "");
+    drop((owned, shared));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+    // ...and the last one to be released drops every item.
+    drop(shared2);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3);
+}
+
+#[test]
+fn budget() {
+    use natatorium::{growable::CheckoutError, Budget};
+    use std::mem::size_of;
+
+    // Each item is charged for its box and for its slot.
+    let item = Pool::<String>::with_capacity(1).bytes_per_item();
+    assert!(item > size_of::<String>());
+    let budget = Budget::new(4 * item);
+    let a: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_budget(budget.clone())
+        .finish();
+    let b: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_budget(budget.clone())
+        .finish();
+    assert_eq!(budget.used(), 2 * item);
+
+    let a1 = a.checkout();
+    let a2 = a.checkout();
+    let b1 = b.checkout();
+    let b2 = b.checkout();
+    assert_eq!(budget.used(), 4 * item);
+    assert_eq!(budget.available(), 0);
+
+    // Neither pool may grow any further.
+    assert_eq!(
+        a.checkout_fallible().unwrap_err(),
+        CheckoutError::OverBudget
+    );
+    assert!(b.try_grow().unwrap_err().is_over_budget());
+    assert_eq!(a.capacity(), 2);
+
+    // Dropping a pool returns its items' budget to the others.
+    drop((b1, b2, b));
+    assert_eq!(budget.used(), 2 * item);
+    let a3 = a.checkout_fallible().expect("budget was released");
+    assert_eq!(a.capacity(), 4);
+    drop((a1, a2, a3));
+}
+
+#[test]
+fn checkout_waits_when_over_budget() {
+    use natatorium::Budget;
+    use std::{sync::mpsc, thread, time::Duration};
+
+    let budget = Budget::new(Pool::<String>::with_capacity(1).bytes_per_item());
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_budget(budget)
+        .finish();
+
+    let (tx, rx) = mpsc::channel();
+    let p = pool.clone();
+    let t = thread::spawn(move || {
+        let _c = p.checkout();
+        tx.send(()).unwrap();
+        thread::sleep(Duration::from_millis(50));
+    });
+    rx.recv().unwrap();
+    let _c = pool.checkout();
+    t.join().unwrap();
+    assert_eq!(pool.capacity(), 1);
+}
+
+#[test]
+fn with_config() {
+    use natatorium::{Config, GrowthConfig, Pressure};
+
+    let config = Config::new()
+        .with_capacity(2)
+        .with_growth(GrowthConfig::Fixed(3))
+        .with_watermarks(1, 2);
+    let pool: Pool<String> = Pool::builder().with_default().with_config(&config).finish();
+    assert_eq!(pool.capacity(), 2);
+
+    let a = pool.checkout();
+    assert_eq!(pool.pressure(), Pressure::Medium);
+    let b = pool.checkout();
+    let c = pool.checkout();
+    assert_eq!(pool.capacity(), 5);
+    drop((a, b, c));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn config_from_json() {
+    use natatorium::{Config, GrowthConfig, Reuse};
+
+    let config: Config = serde_json::from_str(
+        r#"{
+            "name": "buffers",
+            "capacity": 4,
+            "growth": { "fixed": 2 },
+            "watermarks": { "low": 2, "high": 3 },
+            "reuse": "round_robin"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.name.as_deref(), Some("buffers"));
+    assert_eq!(config.capacity, Some(4));
+    assert_eq!(config.growth, Some(GrowthConfig::Fixed(2)));
+    assert_eq!(config.reuse, Some(Reuse::RoundRobin));
+
+    // Unset fields keep their defaults, and unknown ones are rejected.
+    let empty: Config = serde_json::from_str("{}").unwrap();
+    assert_eq!(empty, Config::default());
+    assert!(serde_json::from_str::<Config>(r#"{ "capcity": 4 }"#).is_err());
+
+    let pool: Pool<String> = Pool::builder().with_default().with_config(&config).finish();
+    assert_eq!(pool.capacity(), 4);
+}
+
+#[test]
+fn take_and_release() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let made = Arc::new(AtomicUsize::new(0));
+    let pool = Pool::with_capacity_and_fn(1, {
+        let made = made.clone();
+        move || {
+            made.fetch_add(1, Ordering::Relaxed);
+            String::with_capacity(64)
+        }
+    });
+    let mut owned = pool.checkout();
+    owned.push_str("taken");
+    let item = owned.take_and_release();
+    assert_eq!(item, "taken");
+    assert_eq!(pool.used(), 0);
+    assert_eq!(pool.stats().detached, 1);
+    // The slot isn't refilled until it's needed.
+    assert_eq!(made.load(Ordering::Relaxed), 1);
+    assert!(!pool.contains(&item));
+
+    let owned = pool.checkout();
+    assert_eq!(made.load(Ordering::Relaxed), 2);
+    assert_eq!(*owned, "");
+    assert!(owned.capacity() >= 64);
+    assert_eq!(pool.capacity(), 1);
+    drop(owned);
+
+    // Slots that were never refilled hold no item to return.
+    pool.checkout().take_and_release();
+    assert_eq!(pool.try_into_items().unwrap(), Vec::<String>::new());
+}
+
+#[cfg(feature = "timing-stats")]
+#[test]
+fn latency_stats() {
+    let pool: Pool<String> = Pool::with_capacity(1);
+    let checkouts: Vec<_> = (0..4).map(|_| pool.checkout()).collect();
+    let stats = pool.latency_stats();
+    assert_eq!(stats.checkout.count(), 4);
+    // The pool grew from one item to two, then to four.
+    assert_eq!(stats.growth.count(), 2);
+    assert!(stats.growth.quantile(1.0).is_some());
+    drop(checkouts);
+}
+
+#[test]
+fn split() {
+    let pool: Pool<Vec<u8>> = Pool::with_capacity(1);
+    let mut owned = pool.checkout();
+    owned.extend_from_slice(b"headerbody");
+    let key = owned.key();
+    let (mut header, body) = owned.split(6);
+    assert_eq!(&*header, b"header");
+    assert_eq!(&*body, b"body");
+    header.make_ascii_uppercase();
+
+    // A split item isn't shared, so it can't be looked up by its key.
+    assert!(pool.get(key).is_none());
+    drop(header);
+    assert_eq!(pool.used(), 1);
+    drop(body);
+    assert_eq!(pool.used(), 0);
+
+    let owned = pool.checkout();
+    assert!(owned.is_empty());
+}
+
+#[test]
+fn reentrant_growth_is_an_error() {
+    use natatorium::growable::CheckoutError;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static POOL: RefCell<Option<Pool<String>>> = const { RefCell::new(None) };
+        static SEEN: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    // Constructs an item, trying to use the pool being grown while doing so.
+    fn new_item() -> String {
+        POOL.with(|pool| {
+            if let Some(pool) = &*pool.borrow() {
+                let checkout = pool.checkout_fallible().map(drop);
+                assert_eq!(checkout, Err(CheckoutError::Reentrant));
+                assert!(pool.try_checkout().is_none());
+                let grow = pool.try_grow().unwrap_err();
+                assert!(grow.is_reentrant());
+                SEEN.with(|seen| seen.borrow_mut().push(grow.to_string()));
+            }
+        });
+        String::new()
+    }
+
+    let pool: Pool<String> = Pool::with_fn(new_item);
+    POOL.with(|p| *p.borrow_mut() = Some(pool.clone()));
+    let checkout = pool.checkout();
+    assert_eq!(SEEN.with(|seen| seen.borrow().len()), 1);
+
+    // Once growth is done, the pool can be used (and grown) again.
+    POOL.with(|p| p.borrow_mut().take());
+    assert_eq!(pool.try_grow(), Ok(1));
+    drop(checkout);
+}
+
+#[test]
+fn checkout_async_grows_then_waits_when_over_budget() {
+    use natatorium::Budget;
+
+    let budget = Budget::new(2 * Pool::<String>::with_capacity(1).bytes_per_item());
+    let pool: Pool<String> = Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_growth(Growth::Fixed(1))
+        .with_budget(budget)
+        .finish();
+    let waker = Arc::new(CountingWaker::default());
+
+    let first = pool.checkout();
+    // The pool can still grow, so this doesn't wait.
+    let mut growing = pool.checkout_async();
+    let second = match poll(&mut growing, &waker) {
+        std::task::Poll::Ready(second) => second,
+        std::task::Poll::Pending => panic!("the pool could grow"),
+    };
+    assert_eq!(pool.capacity(), 2);
+
+    let mut waiting = pool.checkout_async();
+    assert!(poll(&mut waiting, &waker).is_pending());
+    drop(second);
+    assert_eq!(waker.woken(), 1);
+    assert!(poll(&mut waiting, &waker).is_ready());
+    drop(first);
 }
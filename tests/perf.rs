@@ -0,0 +1,89 @@
+//! Performance smoke tests for the slab's hot paths.
+//!
+//! These don't replace the benchmarks in `benches/`: they only catch changes
+//! that make checking out and releasing items grossly slower, such as an
+//! accidental lock or allocation on every checkout. The budgets are generous
+//! enough to pass on a loaded CI machine and in unoptimized builds. Set
+//! `NATATORIUM_PERF_BUDGET_NS` to override the budget per operation.
+//!
+//! Run them with `cargo test --features bench --test perf`.
+use natatorium::{fixed, growable};
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+const ITERS: u32 = 100_000;
+
+/// Returns the budget for a single operation.
+fn budget() -> Duration {
+    let nanos = std::env::var("NATATORIUM_PERF_BUDGET_NS")
+        .ok()
+        .map(|budget| {
+            budget
+                .parse()
+                .expect("NATATORIUM_PERF_BUDGET_NS must be a number of nanoseconds")
+        })
+        // Debug builds check the slab's invariants on every checkout, which
+        // is far slower than the checkout itself.
+        .unwrap_or(if cfg!(debug_assertions) {
+            20_000
+        } else {
+            2_000
+        });
+    Duration::from_nanos(nanos)
+}
+
+/// Asserts that `op` takes no longer than the budget, on average over
+/// `ITERS` runs.
+fn assert_within_budget(name: &str, mut op: impl FnMut()) {
+    // Warm up, so that the pool's items are allocated and in cache.
+    for _ in 0..ITERS / 10 {
+        op();
+    }
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        op();
+    }
+    let per_op = start.elapsed() / ITERS;
+    let budget = budget();
+    assert!(
+        per_op <= budget,
+        "{} took {:?} per operation, over the budget of {:?}",
+        name,
+        per_op,
+        budget
+    );
+}
+
+#[test]
+fn fixed_checkout_drop() {
+    let pool: fixed::Pool<String> = fixed::Pool::with_capacity(16);
+    assert_within_budget("fixed/checkout_drop", || drop(black_box(pool.checkout())));
+}
+
+#[test]
+fn fixed_shared_clone_drop() {
+    let pool: fixed::Pool<String> = fixed::Pool::with_capacity(16);
+    let shared = pool.checkout().downgrade();
+    assert_within_budget("fixed/shared_clone_drop", || {
+        drop(black_box(shared.clone()))
+    });
+}
+
+#[test]
+fn growable_checkout_drop() {
+    let pool: growable::Pool<String> = growable::Pool::with_capacity(16);
+    assert_within_budget("growable/checkout_drop", || {
+        drop(black_box(pool.checkout()))
+    });
+}
+
+#[test]
+fn growable_shared_clone_drop() {
+    let pool: growable::Pool<String> = growable::Pool::with_capacity(16);
+    let shared = pool.checkout().downgrade();
+    assert_within_budget("growable/shared_clone_drop", || {
+        drop(black_box(shared.clone()))
+    });
+}
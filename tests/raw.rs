@@ -0,0 +1,86 @@
+use natatorium::raw::{Error, Slab, Slot};
+use std::{mem::ManuallyDrop, ops::Deref, ptr::NonNull};
+
+/// A minimal pool guard, borrowing the slab it was checked out of.
+struct Guard<'a> {
+    slab: &'a Slab<String>,
+    slot: NonNull<Slot<String>>,
+    generation: usize,
+}
+
+impl<'a> Guard<'a> {
+    fn checkout(slab: &'a Slab<String>, s: &str) -> Result<Self, Error> {
+        let mut slot = slab.try_checkout()?;
+        // We hold the slot's only reference, so its item is ours to mutate.
+        let generation = unsafe {
+            slot.as_mut().item_mut().push_str(s);
+            slot.as_ref().generation()
+        };
+        Ok(Self {
+            slab,
+            slot,
+            generation,
+        })
+    }
+}
+
+impl Deref for Guard<'_> {
+    type Target = str;
+    fn deref(&self) -> &str {
+        unsafe { self.slot.as_ref().item() }
+    }
+}
+
+impl Drop for Guard<'_> {
+    fn drop(&mut self) {
+        let slot = unsafe { self.slot.as_ref() };
+        let _ = unsafe { slot.drop_ref(self.slab, self.generation) };
+    }
+}
+
+#[test]
+fn custom_guard() {
+    let slab: Slab<String> = Slab::from_fn(2, &mut String::new);
+    assert_eq!(slab.size(), 2);
+
+    let a = Guard::checkout(&slab, "hello").unwrap();
+    let b = Guard::checkout(&slab, "world").unwrap();
+    assert_eq!(&*a, "hello");
+    assert_eq!(&*b, "world");
+    assert_eq!(slab.used(), 2);
+    assert_eq!(Guard::checkout(&slab, "!").err(), Some(Error::AtCapacity));
+
+    drop(a);
+    assert_eq!(slab.remaining(), 1);
+    // Items are cleared when they are checked out again.
+    let c = Guard::checkout(&slab, "again").unwrap();
+    assert_eq!(&*c, "again");
+
+    drop((b, c));
+    assert!(slab.is_idle());
+    assert!(slab.audit().is_consistent());
+}
+
+#[test]
+fn look_up_slots_by_index_after_growth() {
+    let mut slab: Slab<String> = Slab::new();
+    assert_eq!(slab.try_checkout().err(), Some(Error::AtCapacity));
+
+    slab.grow_by(1, &mut || String::from("first"));
+    // Keep the slot checked out across the growth, but hold on to its index
+    // rather than the guard's pointer.
+    let (idx, generation) = {
+        let guard = ManuallyDrop::new(Guard::checkout(&slab, "").unwrap());
+        (
+            slab.index_of(unsafe { guard.slot.as_ref() }),
+            guard.generation,
+        )
+    };
+    slab.grow_by(1, &mut String::new);
+    assert_eq!(slab.size(), 2);
+    let slot = slab.slot(idx);
+    assert_eq!(unsafe { slot.item() }, "");
+    let _ = unsafe { slot.drop_ref(&slab, generation) };
+    assert!(slab.is_idle());
+    assert_eq!(slab.into_items().len(), 2);
+}
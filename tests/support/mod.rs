@@ -0,0 +1,40 @@
+//! Helpers shared by the integration tests.
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+};
+
+/// A waker that counts how many times it was woken.
+#[derive(Default)]
+pub struct CountingWaker(AtomicUsize);
+
+impl CountingWaker {
+    pub fn woken(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+impl Wake for CountingWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Polls `future` once, with `waker` as its waker.
+pub fn poll<F>(future: &mut F, waker: &Arc<CountingWaker>) -> Poll<F::Output>
+where
+    F: Future + Unpin,
+{
+    let waker = Waker::from(waker.clone());
+    let mut cx = Context::from_waker(&waker);
+    Pin::new(future).poll(&mut cx)
+}
@@ -0,0 +1,60 @@
+use natatorium::{
+    testing::{assert_clear_retains_capacity, assert_clear_retains_capacity_of},
+    traits::{HasCapacity, WithCapacity},
+    Clear,
+};
+use std::collections::HashMap;
+
+/// A buffer whose `Clear` implementation (incorrectly) frees its allocation.
+#[derive(Default)]
+struct Shrinks(Vec<u8>);
+
+impl Clear for Shrinks {
+    fn clear(&mut self) {
+        self.0 = Vec::new();
+    }
+
+    fn retained_capacity(&self) -> Option<usize> {
+        Some(self.0.capacity())
+    }
+}
+
+impl HasCapacity for Shrinks {
+    fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit()
+    }
+}
+
+impl WithCapacity for Shrinks {
+    fn with_capacity(cap: usize) -> Self {
+        Shrinks(Vec::with_capacity(cap))
+    }
+}
+
+#[test]
+fn std_types_retain_capacity() {
+    assert_clear_retains_capacity::<Vec<u8>>();
+    assert_clear_retains_capacity::<String>();
+    assert_clear_retains_capacity::<HashMap<u32, u32>>();
+    assert_clear_retains_capacity_of(vec![1, 2, 3]);
+    assert_clear_retains_capacity_of(String::from("hello"));
+}
+
+#[test]
+#[should_panic(expected = "did not retain capacity")]
+fn detects_clear_releasing_capacity() {
+    assert_clear_retains_capacity::<Shrinks>();
+}
+
+#[cfg(all(feature = "fixed", debug_assertions))]
+#[test]
+#[should_panic(expected = "did not retain capacity")]
+fn pools_detect_clear_releasing_capacity() {
+    let pool: natatorium::fixed::Pool<Shrinks> = natatorium::fixed::Pool::with_capacity(1);
+    pool.checkout().0.reserve(64);
+    let _ = pool.checkout();
+}
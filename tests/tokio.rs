@@ -0,0 +1,138 @@
+use natatorium::{fixed, growable};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+fn block_on<F: std::future::Future>(f: F) -> F::Output {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(f)
+}
+
+#[test]
+fn read_buf_into_fixed_checkout() {
+    let pool: fixed::Pool<Vec<u8>> =
+        fixed::Pool::with_capacity_and_fn(1, || Vec::with_capacity(64));
+    block_on(async {
+        let mut src: &[u8] = b"hello world";
+        let mut c = pool.checkout();
+        let n = src.read_buf(&mut c).await.unwrap();
+        assert_eq!(n, 11);
+        assert_eq!(&c[..], b"hello world");
+    });
+}
+
+#[test]
+fn write_from_growable_checkout() {
+    let pool: growable::Pool<Vec<u8>> = growable::Pool::new();
+    block_on(async {
+        let mut c = pool.checkout();
+        c.write_all(b"hello ").await.unwrap();
+        c.write_all(b"world").await.unwrap();
+        assert_eq!(&c[..], b"hello world");
+    });
+}
+
+#[test]
+fn tasks_share_an_exhausted_pool() {
+    const TASKS: usize = 8;
+    let pool: fixed::Pool<Vec<u8>> = fixed::Pool::with_capacity(2);
+    block_on(async {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let tasks: Vec<_> = (0..TASKS)
+                    .map(|i| {
+                        let pool = pool.clone();
+                        tokio::task::spawn_local(async move {
+                            let mut item = pool.checkout_async().await;
+                            item.push(i as u8);
+                            // Hold the item across a yield, so that the other
+                            // tasks have to wait for it.
+                            tokio::task::yield_now().await;
+                            item.len()
+                        })
+                    })
+                    .collect();
+                for task in tasks {
+                    assert_eq!(task.await.unwrap(), 1);
+                }
+            })
+            .await;
+    });
+    assert_eq!(pool.used(), 0);
+}
+
+#[test]
+fn cancelled_checkout_async_does_not_lose_a_release() {
+    let pool: fixed::Pool<Vec<u8>> = fixed::Pool::with_capacity(1);
+    block_on(async {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let held = pool.checkout();
+                let cancelled = {
+                    let pool = pool.clone();
+                    tokio::task::spawn_local(async move {
+                        tokio::time::timeout(Duration::from_millis(10), pool.checkout_async())
+                            .await
+                            .is_err()
+                    })
+                };
+                let queued = {
+                    let pool = pool.clone();
+                    tokio::task::spawn_local(async move {
+                        let mut item = pool.checkout_async().await;
+                        item.push(1);
+                        item.len()
+                    })
+                };
+                // Let both tasks start waiting, with the first one ahead of
+                // the second in the queue.
+                tokio::task::yield_now().await;
+
+                assert!(cancelled.await.unwrap(), "checkout should have timed out");
+                drop(held);
+                assert_eq!(queued.await.unwrap(), 1);
+            })
+            .await;
+    });
+    assert_eq!(pool.used(), 0);
+}
+
+#[test]
+fn checkout_async_cancelled_after_wakeup_passes_it_on() {
+    let pool: fixed::Pool<Vec<u8>> = fixed::Pool::with_capacity(1);
+    block_on(async {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async {
+                let held = pool.checkout();
+                let queued = {
+                    let pool = pool.clone();
+                    tokio::task::spawn_local(async move {
+                        let mut item = pool.checkout_async().await;
+                        item.push(1);
+                        item.len()
+                    })
+                };
+                // The first branch starts waiting before the spawned task
+                // does. The second branch then releases the held item, which
+                // wakes the first branch, but `select!` drops it without
+                // polling it again. The wakeup must go to the spawned task
+                // instead.
+                tokio::select! {
+                    biased;
+                    _ = pool.checkout_async() => panic!("checkout should have been cancelled"),
+                    _ = async {
+                        tokio::task::yield_now().await;
+                        drop(held);
+                    } => {}
+                }
+                assert_eq!(queued.await.unwrap(), 1);
+            })
+            .await;
+    });
+    assert_eq!(pool.used(), 0);
+}
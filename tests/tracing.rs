@@ -0,0 +1,140 @@
+use natatorium::{fixed, growable};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Metadata, Subscriber,
+};
+
+/// A subscriber that records the messages of every event.
+#[derive(Clone, Default)]
+struct Messages(Arc<Mutex<Vec<String>>>);
+
+impl Messages {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+impl Subscriber for Messages {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        struct Message(String);
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = format!("{:?}", value);
+                }
+            }
+        }
+        let mut message = Message(String::new());
+        event.record(&mut message);
+        self.0.lock().unwrap().push(message.0);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn traces_long_held_checkouts() {
+    let messages = Messages::default();
+    let _guard = tracing::subscriber::set_default(messages.clone());
+
+    let pool: growable::Pool<String> = growable::Pool::builder()
+        .with_default()
+        .trace_checkouts(Duration::from_millis(5))
+        .finish();
+
+    drop(pool.checkout());
+    assert!(messages.take().is_empty());
+
+    let checkout = pool.checkout();
+    thread::sleep(Duration::from_millis(10));
+    drop(checkout);
+    assert_eq!(messages.take(), ["checkout held for longer than expected"]);
+}
+
+#[test]
+fn traces_checkouts_released_on_another_thread() {
+    let messages = Messages::default();
+    let pool: fixed::Pool<String> = fixed::Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .trace_checkouts(Duration::from_secs(60))
+        .finish();
+
+    let checkout = pool.checkout();
+    let m = messages.clone();
+    thread::spawn(move || {
+        let _guard = tracing::subscriber::set_default(m);
+        drop(checkout);
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        messages.take(),
+        ["checkout released on a different thread than it was checked out on"]
+    );
+}
+
+#[test]
+fn untraced_pools_are_quiet() {
+    let messages = Messages::default();
+    let _guard = tracing::subscriber::set_default(messages.clone());
+
+    let pool: fixed::Pool<String> = fixed::Pool::with_capacity(1);
+    let checkout = pool.checkout();
+    thread::spawn(move || drop(checkout)).join().unwrap();
+    assert!(messages.take().is_empty());
+}
+
+#[test]
+fn traces_growth() {
+    let messages = Messages::default();
+    let _guard = tracing::subscriber::set_default(messages.clone());
+
+    let pool: growable::Pool<String> = growable::Pool::with_capacity(1);
+    assert!(messages.take().is_empty());
+
+    let _c1 = pool.checkout();
+    assert!(messages.take().is_empty());
+    let _c2 = pool.checkout();
+    assert_eq!(messages.take(), ["pool grew"]);
+}
+
+#[test]
+fn traces_reclaimed_leases() {
+    let messages = Messages::default();
+    let _guard = tracing::subscriber::set_default(messages.clone());
+
+    let pool: fixed::Pool<String> = fixed::Pool::builder()
+        .with_default()
+        .with_elements(1)
+        .with_lease(Duration::from_millis(1))
+        .finish();
+    let _stuck = pool.checkout();
+    thread::sleep(Duration::from_millis(5));
+
+    assert_eq!(pool.reclaim_expired(), 1);
+    assert_eq!(
+        messages.take(),
+        ["reclaimed a checkout whose lease expired"]
+    );
+}